@@ -1,7 +1,8 @@
 // Behavior Tree Implementation - Production-ready behavior tree for complex AI logic
 use bevy::prelude::*;
 use game_physics::prelude::*;
-use game_units::{Team, Unit};
+use game_physics::{Abilities, AbilityTarget, UseAbility};
+use game_units::{Health, Team, Unit};
 use std::collections::HashMap;
 
 // Core behavior tree node types
@@ -347,7 +348,29 @@ fn execute_action(
         }
 
         AIAction::UseAbility(ability_name) => {
-            // Trigger ability usage
+            // Only worth trying if the ability is ready; activation checks the rest
+            let ready = world
+                .get_entity(entity)
+                .ok()
+                .and_then(|entity_ref| entity_ref.get::<Abilities>())
+                .is_some_and(|abilities| abilities.is_ready(ability_name));
+            if !ready {
+                return NodeStatus::Failure;
+            }
+
+            // Aim at the current target if there is one
+            let target = if let Some(target) = blackboard.get_entity("attack_target") {
+                AbilityTarget::Unit(target)
+            } else if let Some(position) = blackboard.get_vec3("target_position") {
+                AbilityTarget::Point(position)
+            } else {
+                AbilityTarget::Caster
+            };
+            commands.write_message(UseAbility {
+                caster: entity,
+                ability: ability_name.clone(),
+                target,
+            });
             blackboard.set(
                 "ability_used".to_string(),
                 BlackboardValue::String(ability_name.clone()),
@@ -382,18 +405,18 @@ fn check_condition(
 
         AICondition::HealthAbove(threshold) => {
             if let Ok(entity_ref) = world.get_entity(entity)
-                && let Some(unit) = entity_ref.get::<Unit>()
+                && let Some(health) = entity_ref.get::<Health>()
             {
-                return unit.health / unit.max_health > *threshold;
+                return health.percentage() > *threshold;
             }
             true
         }
 
         AICondition::HealthBelow(threshold) => {
             if let Ok(entity_ref) = world.get_entity(entity)
-                && let Some(unit) = entity_ref.get::<Unit>()
+                && let Some(health) = entity_ref.get::<Health>()
             {
-                return unit.health / unit.max_health < *threshold;
+                return health.percentage() < *threshold;
             }
             false
         }
@@ -2,6 +2,7 @@
 use crate::types::{AICoordination, AIRole};
 use bevy::prelude::*;
 use bevy_ai_toolkit::prelude::*; // Use toolkit types
+use game_physics::{ModifierOp, ModifierSource, Sanity, StatKind, Stats};
 use std::collections::HashMap;
 
 /// Cult-specific AI behavioral modifiers
@@ -309,6 +310,41 @@ pub fn update_psychological_state_system(
     }
 }
 
+/// System to keep fear at least as high as the sanity a unit has lost
+pub fn sanity_fear_system(mut query: Query<(&mut PsychologicalState, &Sanity), Changed<Sanity>>) {
+    for (mut psychological_state, sanity) in query.iter_mut() {
+        let dread = 1.0 - sanity.percentage();
+        if dread > psychological_state.fear_level {
+            psychological_state.fear_level = dread.min(1.0);
+        }
+    }
+}
+
+/// System to apply cult doctrine bonuses through the stat pipeline.
+///
+/// Defense is modelled as extra max health.
+pub fn cult_stat_modifier_system(
+    mut query: Query<(&CultProfile, &mut Stats), Changed<CultProfile>>,
+) {
+    for (cult_profile, mut stats) in query.iter_mut() {
+        let modifiers = &cult_profile.behavioral_modifiers;
+        stats.replace_source(
+            ModifierSource::Cult,
+            [
+                (
+                    StatKind::AttackDamage,
+                    ModifierOp::Multiply(modifiers.attack_bonus),
+                ),
+                (
+                    StatKind::MaxHealth,
+                    ModifierOp::Multiply(modifiers.defense_bonus),
+                ),
+            ],
+            None,
+        );
+    }
+}
+
 /// Helper function to create AI coordination for cult units
 pub fn create_cult_coordination(cult_profile: &CultProfile, role: AIRole) -> AICoordination {
     let base_radius = match cult_profile.cult_name.as_str() {
@@ -1,6 +1,7 @@
 // Decision Making System - Strategic decision making for AI entities
+use crate::perception::AIKnowledge;
 use bevy::prelude::*;
-use game_units::{Team, Unit};
+use game_units::{Health, Team, Unit};
 use std::collections::VecDeque;
 
 // Decision maker component for strategic AI decisions
@@ -345,15 +346,16 @@ pub fn decision_system(
         Entity,
         &mut DecisionMaker,
         &Transform,
-        Option<&Unit>,
+        Option<&Health>,
         Option<&Team>,
     )>,
-    enemy_query: Query<(Entity, &Transform, &Team)>,
+    ally_query: Query<(&Transform, &Team)>,
+    knowledge: Res<AIKnowledge>,
     time: Res<Time>,
 ) {
     let current_time = time.elapsed_seconds();
 
-    for (entity, mut decision_maker, transform, unit, team) in query.iter_mut() {
+    for (entity, mut decision_maker, transform, health, team) in query.iter_mut() {
         // Check if it's time to re-evaluate
         if current_time - decision_maker.last_evaluation < decision_maker.evaluation_interval {
             continue;
@@ -366,7 +368,7 @@ pub fn decision_system(
             entity,
             position: transform.translation,
             team_id: team.map(|t| t.id).unwrap_or(0),
-            health_percentage: unit.map(|u| u.health / u.max_health).unwrap_or(1.0),
+            health_percentage: health.map(Health::percentage).unwrap_or(1.0),
             nearby_enemies: 0,
             nearby_allies: 0,
             threat_level: 0.0,
@@ -374,19 +376,22 @@ pub fn decision_system(
             time_elapsed: current_time,
         };
 
-        // Count nearby units
+        // Count nearby allies, and the enemies the team knows are nearby
         let detection_range = 30.0;
-        for (enemy_entity, enemy_transform, enemy_team) in enemy_query.iter() {
-            let distance = transform.translation.distance(enemy_transform.translation);
-            if distance <= detection_range {
-                if enemy_team.id != context.team_id {
-                    context.nearby_enemies += 1;
-                    context.threat_level += 1.0 / distance.max(1.0);
-                } else {
-                    context.nearby_allies += 1;
-                }
+        for (ally_transform, ally_team) in ally_query.iter() {
+            if ally_team.id == context.team_id
+                && transform.translation.distance(ally_transform.translation) <= detection_range
+            {
+                context.nearby_allies += 1;
             }
         }
+        for known in knowledge.known_within(context.team_id, transform.translation, detection_range)
+        {
+            let distance = transform.translation.distance(known.position);
+            context.nearby_enemies += 1;
+            // Stale sightings count for less
+            context.threat_level += known.confidence / distance.max(1.0);
+        }
 
         // Update decision
         decision_maker.update(&context);
@@ -293,6 +293,147 @@ fn test_ai_behavior_execution() {
     println!("✓ AI behavior execution test passed");
 }
 
+/// Test AI knowledge comes from the team's enemy memory and fades once enemies
+/// are out of sight
+#[test]
+fn test_ai_knowledge_respects_fog_and_decays() {
+    use crate::perception::{
+        AIDifficulty, AIKnowledge, AIPerceptionSettings, TeamPerceptionSettings,
+        ai_knowledge_system,
+    };
+    use game_units::{Health, Team, Unit};
+    use game_world::ghosts::update_enemy_memory_system;
+    use game_world::{
+        EnemyMemory, GameMap, Observable, ObservedKind, SharedVision, VisibilityMap,
+        VisibilityState,
+    };
+    use std::time::Duration;
+
+    let mut app = App::new();
+    app.init_resource::<Time>()
+        .init_resource::<GameMap>()
+        .init_resource::<VisibilityMap>()
+        .init_resource::<SharedVision>()
+        .init_resource::<EnemyMemory>()
+        .init_resource::<AIPerceptionSettings>()
+        .init_resource::<AIKnowledge>()
+        .add_systems(
+            Update,
+            (update_enemy_memory_system, ai_knowledge_system).chain(),
+        );
+
+    let team = |id| Team {
+        id,
+        ..Default::default()
+    };
+    let observable = |team| Observable {
+        team,
+        kind: ObservedKind::Unit,
+        type_name: "cultist".to_string(),
+        health: 100.0,
+        max_health: 100.0,
+    };
+    let scout = app
+        .world_mut()
+        .spawn((
+            Transform::from_xyz(50.0, 0.0, 0.0),
+            team(1),
+            Unit::default(),
+            Health::new(100.0),
+            observable(1),
+        ))
+        .id();
+    let enemy = app
+        .world_mut()
+        .spawn((
+            Transform::default(),
+            team(2),
+            Unit::default(),
+            Health::new(100.0),
+            observable(2),
+        ))
+        .id();
+
+    // Team 1 sees the enemy's tile; team 2 sees nothing
+    app.world_mut()
+        .resource_mut::<VisibilityMap>()
+        .layer_mut(1)
+        .tiles
+        .insert((0, 0), VisibilityState::Visible);
+    app.update();
+
+    let knowledge = app.world().resource::<AIKnowledge>();
+    assert!(knowledge.is_in_sight(1, enemy));
+    assert_eq!(knowledge.known_enemies(2).count(), 0);
+    // The AI knows exactly what the player's ghosts show
+    assert!(
+        app.world()
+            .resource::<EnemyMemory>()
+            .last_known(1, enemy)
+            .is_some_and(|ghost| ghost.in_sight)
+    );
+
+    // The tile falls back under fog: the enemy is remembered, then forgotten
+    app.world_mut()
+        .resource_mut::<VisibilityMap>()
+        .layer_mut(1)
+        .tiles
+        .insert((0, 0), VisibilityState::Revealed);
+    let memory_duration = app
+        .world()
+        .resource::<AIPerceptionSettings>()
+        .for_team(1)
+        .memory_duration;
+    app.world_mut()
+        .resource_mut::<Time>()
+        .advance_by(Duration::from_secs_f32(memory_duration * 0.5));
+    app.update();
+
+    let known = app
+        .world()
+        .resource::<AIKnowledge>()
+        .get(1, enemy)
+        .cloned()
+        .expect("enemy should still be remembered");
+    assert!(!known.in_sight);
+    assert!(known.confidence > 0.0 && known.confidence < 1.0);
+    let ghost = app
+        .world()
+        .resource::<EnemyMemory>()
+        .last_known(1, enemy)
+        .cloned()
+        .unwrap();
+    assert_eq!(
+        (known.position, known.last_seen),
+        (ghost.position, ghost.last_seen)
+    );
+
+    app.world_mut()
+        .resource_mut::<Time>()
+        .advance_by(Duration::from_secs_f32(memory_duration));
+    app.update();
+    assert!(
+        app.world()
+            .resource::<AIKnowledge>()
+            .get(1, enemy)
+            .is_none()
+    );
+
+    // A cheating AI team sees through the fog; other teams still don't
+    app.world_mut()
+        .resource_mut::<AIPerceptionSettings>()
+        .set_team(
+            2,
+            TeamPerceptionSettings::for_difficulty(AIDifficulty::Nightmare),
+        );
+    app.update();
+    let knowledge = app.world().resource::<AIKnowledge>();
+    assert!(knowledge.is_in_sight(2, scout));
+    assert!(!knowledge.is_in_sight(1, enemy));
+
+    println!("✓ AI knowledge test passed");
+}
+
 /// Run all integration tests
 pub fn run_integration_tests() {
     println!("Running AI-Physics integration tests...");
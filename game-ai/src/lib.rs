@@ -14,6 +14,7 @@ pub mod behaviors;
 pub mod cult_profiles;
 pub mod decision;
 pub mod game_behaviors;
+pub mod perception;
 pub mod states;
 pub mod systems;
 pub mod targeting;
@@ -29,6 +30,10 @@ pub use cult_profiles::{
 };
 pub use decision::*;
 pub use game_behaviors::{AttackBehavior, DefendBehavior, GatheringBehavior, RetreatBehavior};
+pub use perception::{
+    AIDifficulty, AIKnowledge, AIPerceptionSettings, KnownEnemy, TeamPerception,
+    TeamPerceptionSettings,
+};
 pub use states::StateTransitionTrigger;
 pub use systems::ai_execution::{AICommandEvent, AIGlobalState, AIPerceptionEvent};
 pub use systems::decision_making::AIDecisionMaker;
@@ -60,8 +65,13 @@ impl Plugin for GameAIPlugin {
             .add_message::<PsychologicalEvent>()
             .add_message::<crate::systems::AICommandEvent>()
             .add_message::<crate::systems::AIPerceptionEvent>()
+            // Abilities are activated through the same API as player input
+            .add_message::<game_physics::UseAbility>()
             // Add resources
             .insert_resource(crate::systems::AIGlobalState::default())
+            .init_resource::<GlobalSpatialGrid>()
+            .init_resource::<crate::perception::AIPerceptionSettings>()
+            .init_resource::<crate::perception::AIKnowledge>()
             // Configure SystemSets for AI ordering
             .configure_sets(
                 Update,
@@ -83,6 +93,8 @@ impl Plugin for GameAIPlugin {
                     crate::states::state_transition_system,
                     crate::behaviors::behavior_tree_execution_system,
                     crate::cult_profiles::update_psychological_state_system,
+                    crate::cult_profiles::sanity_fear_system,
+                    crate::cult_profiles::cult_stat_modifier_system,
                 )
                     .chain()
                     .in_set(AISystemSet::CoreAI),
@@ -91,6 +103,7 @@ impl Plugin for GameAIPlugin {
             .add_systems(
                 Update,
                 (
+                    crate::perception::ai_knowledge_system,
                     crate::systems::decision_making::decision_making_system,
                     crate::decision::decision_system,
                     crate::decision::goal_execution_system,
@@ -119,6 +132,7 @@ impl Plugin for GameAIPlugin {
                     ai_action_execution_system,
                     crate::systems::ai_movement_system,
                     crate::systems::ai_combat_system,
+                    crate::systems::ai_ability_system,
                     crate::targeting::line_of_sight_system,
                     crate::targeting::target_prediction_system,
                 )
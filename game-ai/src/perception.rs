@@ -0,0 +1,291 @@
+// AI Perception - what AI teams know about their enemies
+//
+// AI sees through the same per-team fog of war and stealth rules as players.
+// What it has seen comes from the world's `EnemyMemory`, the same ghosts the
+// player sees, with confidence that fades over time on top.
+use bevy::ecs::system::SystemParam;
+use bevy::prelude::*;
+use game_physics::{StealthState, is_hidden_from};
+use game_units::{Health, Leader, StatKind, Stats, Team, Unit};
+use game_world::map::world_to_grid;
+use game_world::{EnemyMemory, GameMap, ObservedKind, VisibilityMap};
+use std::collections::HashMap;
+
+// AI difficulty levels
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum AIDifficulty {
+    Easy,
+    #[default]
+    Normal,
+    Hard,
+    Nightmare,
+}
+
+impl AIDifficulty {
+    /// Seconds an unseen enemy stays in an AI team's memory
+    pub fn memory_duration(&self) -> f32 {
+        match self {
+            AIDifficulty::Easy => 4.0,
+            AIDifficulty::Normal => 8.0,
+            AIDifficulty::Hard => 15.0,
+            AIDifficulty::Nightmare => 30.0,
+        }
+    }
+
+    /// Whether this difficulty ignores the fog of war by default
+    pub fn cheats_vision(&self) -> bool {
+        matches!(self, AIDifficulty::Nightmare)
+    }
+}
+
+/// How one AI team perceives the battlefield
+#[derive(Clone, Debug)]
+pub struct TeamPerceptionSettings {
+    pub difficulty: AIDifficulty,
+    /// See through the fog of war. Stealth still applies: undetected units
+    /// can't be targeted by anyone.
+    pub cheat_vision: bool,
+    /// Seconds before an unseen enemy is forgotten
+    pub memory_duration: f32,
+}
+
+impl TeamPerceptionSettings {
+    pub fn for_difficulty(difficulty: AIDifficulty) -> Self {
+        Self {
+            difficulty,
+            cheat_vision: difficulty.cheats_vision(),
+            memory_duration: difficulty.memory_duration(),
+        }
+    }
+}
+
+impl Default for TeamPerceptionSettings {
+    fn default() -> Self {
+        Self::for_difficulty(AIDifficulty::default())
+    }
+}
+
+/// Perception settings for every AI team, so opponents of different
+/// difficulties can share a match. Teams without an entry use `default`.
+#[derive(Resource, Clone, Debug, Default)]
+pub struct AIPerceptionSettings {
+    pub default: TeamPerceptionSettings,
+    pub teams: HashMap<u32, TeamPerceptionSettings>,
+}
+
+impl AIPerceptionSettings {
+    pub fn for_team(&self, team: u32) -> &TeamPerceptionSettings {
+        self.teams.get(&team).unwrap_or(&self.default)
+    }
+
+    /// Give one team its own settings
+    pub fn set_team(&mut self, team: u32, settings: TeamPerceptionSettings) {
+        self.teams.insert(team, settings);
+    }
+
+    /// Mutable settings for a team, starting from the default
+    pub fn team_mut(&mut self, team: u32) -> &mut TeamPerceptionSettings {
+        self.teams
+            .entry(team)
+            .or_insert_with(|| self.default.clone())
+    }
+}
+
+/// Per-team visibility and detection checks for AI systems.
+///
+/// Without fog of war data (no world plugin) every position counts as
+/// visible, so AI still works in stripped-down apps and tests.
+#[derive(SystemParam)]
+pub struct TeamPerception<'w> {
+    settings: Res<'w, AIPerceptionSettings>,
+    visibility_map: Option<Res<'w, VisibilityMap>>,
+    game_map: Option<Res<'w, GameMap>>,
+}
+
+impl TeamPerception<'_> {
+    /// Whether a team currently has vision of a position
+    pub fn can_see_position(&self, team: u32, position: Vec3) -> bool {
+        if self.settings.for_team(team).cheat_vision {
+            return true;
+        }
+
+        match (&self.visibility_map, &self.game_map) {
+            (Some(visibility_map), Some(game_map)) => {
+                visibility_map.is_visible_to(team, world_to_grid(position, game_map.tile_size))
+            }
+            _ => true,
+        }
+    }
+
+    /// Whether a team can currently perceive an entity at a position
+    pub fn can_perceive(&self, team: u32, position: Vec3, stealth: Option<&StealthState>) -> bool {
+        !is_hidden_from(stealth, team) && self.can_see_position(team, position)
+    }
+
+    /// Perception settings of one team
+    pub fn settings(&self, team: u32) -> &TeamPerceptionSettings {
+        self.settings.for_team(team)
+    }
+}
+
+/// What an AI team knows about one enemy
+#[derive(Clone, Debug)]
+pub struct KnownEnemy {
+    pub entity: Entity,
+    pub team: u32,
+    /// Last observed position
+    pub position: Vec3,
+    pub health: f32,
+    pub max_health: f32,
+    pub damage: f32,
+    pub is_leader: bool,
+    /// Elapsed time of the last observation
+    pub last_seen: f32,
+    /// 1.0 while in sight, fading to 0.0 over the memory duration
+    pub confidence: f32,
+    pub in_sight: bool,
+}
+
+/// Every AI team's knowledge of its enemies, rebuilt each frame from
+/// `EnemyMemory`
+#[derive(Resource, Default, Debug)]
+pub struct AIKnowledge {
+    pub teams: HashMap<u32, HashMap<Entity, KnownEnemy>>,
+}
+
+impl AIKnowledge {
+    /// Enemies a team currently knows about, seen or remembered
+    pub fn known_enemies(&self, team: u32) -> impl Iterator<Item = &KnownEnemy> {
+        self.teams
+            .get(&team)
+            .into_iter()
+            .flat_map(|known| known.values())
+    }
+
+    pub fn get(&self, team: u32, entity: Entity) -> Option<&KnownEnemy> {
+        self.teams.get(&team)?.get(&entity)
+    }
+
+    /// Known enemies whose last known position is within `radius` of `center`
+    pub fn known_within(
+        &self,
+        team: u32,
+        center: Vec3,
+        radius: f32,
+    ) -> impl Iterator<Item = &KnownEnemy> {
+        self.known_enemies(team)
+            .filter(move |known| known.position.distance(center) <= radius)
+    }
+
+    /// Whether a team has the enemy in sight right now
+    pub fn is_in_sight(&self, team: u32, entity: Entity) -> bool {
+        self.get(team, entity).is_some_and(|known| known.in_sight)
+    }
+
+    /// Forget everything a team knows
+    pub fn clear_team(&mut self, team: u32) {
+        self.teams.remove(&team);
+    }
+}
+
+// Knowledge update system - read each team's enemy memory and fade what is
+// no longer in sight. Teams that cheat vision, or apps without the world's
+// memory, perceive enemy units directly instead.
+#[allow(clippy::type_complexity)]
+pub fn ai_knowledge_system(
+    mut knowledge: ResMut<AIKnowledge>,
+    perception: TeamPerception,
+    memory: Option<Res<EnemyMemory>>,
+    units: Query<
+        (
+            Entity,
+            &Transform,
+            &Team,
+            &Health,
+            Option<&Stats>,
+            Option<&Leader>,
+            Option<&StealthState>,
+        ),
+        With<Unit>,
+    >,
+    time: Res<Time>,
+) {
+    let now = time.elapsed_seconds();
+
+    let mut teams: Vec<u32> = units.iter().map(|(_, _, team, ..)| team.id).collect();
+    teams.sort_unstable();
+    teams.dedup();
+
+    // Teams without units left know nothing
+    knowledge.teams.retain(|team, _| teams.contains(team));
+
+    for team in teams {
+        let settings = perception.settings(team);
+        let memory_duration = settings.memory_duration.max(f32::EPSILON);
+        let known = knowledge.teams.entry(team).or_default();
+        known.clear();
+
+        let memory = memory.as_deref().filter(|_| !settings.cheat_vision);
+        let Some(memory) = memory else {
+            for (entity, transform, enemy_team, health, stats, leader, stealth) in units.iter() {
+                if enemy_team.id == team
+                    || health.is_dead()
+                    || !perception.can_perceive(team, transform.translation, stealth)
+                {
+                    continue;
+                }
+                known.insert(
+                    entity,
+                    KnownEnemy {
+                        entity,
+                        team: enemy_team.id,
+                        position: transform.translation,
+                        health: health.current,
+                        max_health: health.maximum,
+                        damage: stats.map_or(0.0, |stats| stats.get(StatKind::AttackDamage)),
+                        is_leader: leader.is_some(),
+                        last_seen: now,
+                        confidence: 1.0,
+                        in_sight: true,
+                    },
+                );
+            }
+            continue;
+        };
+
+        for ghost in memory.known_of_kind(team, ObservedKind::Unit) {
+            // Ghosts linger until re-observed; the AI stops trusting them sooner
+            let confidence = 1.0 - ghost.age(now) / memory_duration;
+            if confidence <= 0.0 {
+                continue;
+            }
+            let live = units.get(ghost.entity).ok();
+            if ghost.in_sight && live.is_some_and(|(_, _, _, health, ..)| health.is_dead()) {
+                continue;
+            }
+            // What kind of unit it is was learnt when it was seen
+            let (damage, is_leader) = live.map_or((0.0, false), |(.., stats, leader, _)| {
+                (
+                    stats.map_or(0.0, |stats| stats.get(StatKind::AttackDamage)),
+                    leader.is_some(),
+                )
+            });
+
+            known.insert(
+                ghost.entity,
+                KnownEnemy {
+                    entity: ghost.entity,
+                    team: ghost.team,
+                    position: ghost.position,
+                    health: ghost.health,
+                    max_health: ghost.max_health,
+                    damage,
+                    is_leader,
+                    last_seen: ghost.last_seen,
+                    confidence,
+                    in_sight: ghost.in_sight,
+                },
+            );
+        }
+    }
+}
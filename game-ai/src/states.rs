@@ -1,7 +1,7 @@
 // AI State Machine - Production-ready state management for AI entities
 use bevy::prelude::*;
 use game_physics::prelude::*;
-use game_units::{Leader, Team, Unit};
+use game_units::{Health, Leader, StatKind, Stats, Team, Unit};
 use std::collections::HashMap;
 
 // Core AI state enum - defines all possible states an AI unit can be in
@@ -241,7 +241,7 @@ pub fn state_execution_system(
         Entity,
         &mut AIStateMachine,
         &Transform,
-        Option<&Unit>,
+        Option<&Stats>,
         Option<&Team>,
     )>,
     mut movement_events: MessageWriter<MovementCommandEvent>,
@@ -250,7 +250,7 @@ pub fn state_execution_system(
 ) {
     let delta = time.delta_seconds();
 
-    for (entity, mut state_machine, transform, unit, team) in query.iter_mut() {
+    for (entity, mut state_machine, transform, stats, team) in query.iter_mut() {
         state_machine.update(delta);
 
         match state_machine.current_state {
@@ -261,7 +261,7 @@ pub fn state_execution_system(
                 execute_patrol_state(entity, &mut state_machine, transform, &mut movement_events);
             }
             AIState::Attacking => {
-                execute_attack_state(entity, &state_machine, transform, unit, &mut commands);
+                execute_attack_state(entity, &state_machine, transform, stats, &mut commands);
             }
             AIState::Fleeing => {
                 execute_flee_state(entity, &state_machine, transform, &mut movement_events);
@@ -331,7 +331,7 @@ fn execute_attack_state(
     entity: Entity,
     state_machine: &AIStateMachine,
     transform: &Transform,
-    unit: Option<&Unit>,
+    stats: Option<&Stats>,
     commands: &mut Commands,
 ) {
     if let Some(target) = state_machine.state_data.target_entity {
@@ -340,7 +340,9 @@ fn execute_attack_state(
             .entity(entity)
             .insert(crate::game_behaviors::AttackBehavior {
                 target: Some(target),
-                aggression_level: unit.map(|u| u.attack_damage / 10.0).unwrap_or(1.0),
+                aggression_level: stats
+                    .map(|stats| stats.get(StatKind::AttackDamage) / 10.0)
+                    .unwrap_or(1.0),
             });
     }
 }
@@ -430,11 +432,11 @@ fn execute_search_state(
 
 // System to trigger state transitions based on game events
 pub fn state_transition_system(
-    mut query: Query<(Entity, &mut AIStateMachine, &Transform, Option<&Unit>)>,
+    mut query: Query<(Entity, &mut AIStateMachine, &Transform, Option<&Health>)>,
     enemy_query: Query<(Entity, &Transform, &Team), Without<AIStateMachine>>,
     time: Res<Time>,
 ) {
-    for (entity, mut state_machine, transform, unit) in query.iter_mut() {
+    for (entity, mut state_machine, transform, health) in query.iter_mut() {
         // Check for enemies in detection range
         let detection_range = 15.0;
         let mut enemy_detected = false;
@@ -463,14 +465,10 @@ pub fn state_transition_system(
         }
 
         // Check health for flee trigger
-        if let Some(unit) = unit {
-            if unit.health < unit.max_health * 0.3
-                && state_machine.current_state == AIState::Attacking
-            {
+        if let Some(health) = health {
+            if health.percentage() < 0.3 && state_machine.current_state == AIState::Attacking {
                 state_machine.transition(StateTransitionTrigger::HealthLow);
-            } else if unit.health > unit.max_health * 0.5
-                && state_machine.current_state == AIState::Fleeing
-            {
+            } else if health.percentage() > 0.5 && state_machine.current_state == AIState::Fleeing {
                 state_machine.transition(StateTransitionTrigger::HealthRestored);
             }
         }
@@ -1,8 +1,11 @@
 // AI Execution Systems - handles AI movement, combat, perception and coordination
 use bevy::prelude::*;
 use game_physics::prelude::*;
+use game_physics::{AbilityTarget, Madness, UseAbility};
 use game_units::{Leader, Team, Unit};
 
+use crate::perception::TeamPerception;
+
 // Events for AI communication
 #[derive(Event, Clone, Debug)]
 pub struct AICommandEvent {
@@ -17,7 +20,7 @@ pub enum AICommand {
     Defend(Vec3),
     Follow(Entity),
     Patrol(Vec<Vec3>),
-    UseAbility(String),
+    UseAbility(String, AbilityTarget),
 }
 
 #[derive(Event, Clone, Debug)]
@@ -52,12 +55,13 @@ pub struct FactionState {
     pub resources: f32,
 }
 
-// AI Movement System - handles movement commands for AI-controlled units
+// AI Movement System - handles movement commands for AI-controlled units.
+// Units gripped by madness don't take orders.
 pub fn ai_movement_system(
     mut commands: Commands,
     mut ai_command_events: MessageReader<AICommandEvent>,
     mut movement_events: MessageWriter<MovementCommandEvent>,
-    mut query: Query<(&mut MovementController, &Transform), With<Unit>>,
+    mut query: Query<(&mut MovementController, &Transform), (With<Unit>, Without<Madness>)>,
 ) {
     for event in ai_command_events.read() {
         match &event.command {
@@ -131,23 +135,54 @@ pub fn ai_combat_system(
     }
 }
 
-// Perception System - handles what AI entities can perceive
+// AI Ability System - passes ability commands to the same activation API the
+// player uses. Units gripped by madness don't take orders.
+pub fn ai_ability_system(
+    mut ai_command_events: MessageReader<AICommandEvent>,
+    mut ability_events: MessageWriter<UseAbility>,
+    query: Query<(), (With<Unit>, Without<Madness>)>,
+) {
+    for event in ai_command_events.read() {
+        if let AICommand::UseAbility(ability, target) = &event.command
+            && query.contains(event.entity)
+        {
+            ability_events.write(UseAbility {
+                caster: event.entity,
+                ability: ability.clone(),
+                target: *target,
+            });
+        }
+    }
+}
+
+// Perception System - handles what AI entities can perceive.
+// Enemies are only spotted through the team's fog of war and detection.
 pub fn perception_system(
     mut perception_events: MessageWriter<AIPerceptionEvent>,
-    query: Query<(Entity, &Transform, &Team), With<Unit>>,
+    query: Query<(Entity, &Transform, &Team, Option<&StealthState>), With<Unit>>,
+    spatial_grid: Res<GlobalSpatialGrid>,
+    perception: TeamPerception,
 ) {
-    // Simple perception - units detect enemies within range
-    for (entity, transform, team) in query.iter() {
-        for (other_entity, other_transform, other_team) in query.iter() {
+    // Simple perception - units detect nearby enemies via the spatial grid
+    for (entity, transform, team, _) in query.iter() {
+        for other_entity in spatial_grid.grid.query_radius(transform.translation, 20.0) {
             if entity == other_entity {
                 continue;
             }
 
+            let Ok((_, other_transform, other_team, stealth)) = query.get(other_entity) else {
+                continue;
+            };
+
             let distance = transform.translation.distance(other_transform.translation);
 
             // Perception range
             if distance < 20.0 {
                 if team.id != other_team.id {
+                    if !perception.can_perceive(team.id, other_transform.translation, stealth) {
+                        continue;
+                    }
+
                     // Enemy spotted
                     perception_events.write(AIPerceptionEvent {
                         perceiver: entity,
@@ -174,7 +209,7 @@ pub fn squad_coordination_system(
     leader_query: Query<(Entity, &Transform, &Team), With<Leader>>,
     mut follower_query: Query<
         (&mut MovementController, &Transform, &Team),
-        (With<Unit>, Without<Leader>),
+        (With<Unit>, Without<Leader>, Without<Madness>),
     >,
     mut movement_events: MessageWriter<MovementCommandEvent>,
 ) {
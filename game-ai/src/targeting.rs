@@ -1,9 +1,12 @@
 // Target Selection and Prioritization System - Smart target selection for AI entities
 use bevy::prelude::*;
 use game_physics::prelude::*;
-use game_units::{Leader, Team, Unit};
+use game_physics::{is_hidden_from, sight_blocking_layers};
+use game_units::{Health, Team, Unit};
 use std::cmp::Ordering;
 
+use crate::perception::AIKnowledge;
+
 // Target selector component for AI entities
 #[derive(Component, Clone, Debug)]
 pub struct TargetSelector {
@@ -167,11 +170,13 @@ impl TargetSelector {
     }
 }
 
-// Target acquisition system
+// Target acquisition system - picks targets from what the team knows about,
+// not from where enemies really are
 pub fn target_acquisition_system(
     mut query: Query<(Entity, &mut TargetSelector, &Transform, &Team)>,
-    enemy_query: Query<(Entity, &Transform, &Team, &Unit, Option<&Leader>)>,
+    enemy_query: Query<Option<&StealthState>, With<Unit>>,
     resource_query: Query<(Entity, &Transform), With<ResourceMarker>>,
+    knowledge: Res<AIKnowledge>,
     time: Res<Time>,
 ) {
     let current_time = time.elapsed_seconds();
@@ -179,12 +184,16 @@ pub fn target_acquisition_system(
     for (entity, mut selector, transform, team) in query.iter_mut() {
         // Check if it's time to reacquire target
         if current_time - selector.last_target_check < selector.reacquisition_time {
-            // Check if current target still exists and is valid
+            // Check if current target is still known, in range and targetable
             if let Some(target) = selector.current_target
-                && let Ok((_, target_transform, _, target_unit, _)) = enemy_query.get(target)
+                && let Some(known) = knowledge.get(team.id, target)
+                && let Ok(stealth) = enemy_query.get(target)
             {
-                let distance = transform.translation.distance(target_transform.translation);
-                if distance <= selector.max_range && target_unit.health > 0.0 {
+                let distance = transform.translation.distance(known.position);
+                if distance <= selector.max_range
+                    && known.health > 0.0
+                    && !is_hidden_from(stealth, team.id)
+                {
                     continue; // Keep current target
                 }
             }
@@ -215,33 +224,40 @@ pub fn target_acquisition_system(
                 }
             }
         } else {
-            // Look for enemies
-            for (enemy_entity, enemy_transform, enemy_team, enemy_unit, leader) in
-                enemy_query.iter()
-            {
-                // Skip same team
-                if enemy_team.id == team.id {
+            // Look for enemies the team has seen or still remembers
+            for known in knowledge.known_enemies(team.id) {
+                // Skip enemies that have since slipped into stealth
+                let Ok(stealth) = enemy_query.get(known.entity) else {
+                    continue;
+                };
+                if is_hidden_from(stealth, team.id) {
                     continue;
                 }
 
                 // Skip dead units
-                if enemy_unit.health <= 0.0 {
+                if known.health <= 0.0 {
                     continue;
                 }
 
-                let distance = transform.translation.distance(enemy_transform.translation);
+                let distance = transform.translation.distance(known.position);
 
                 if distance <= selector.max_range {
-                    let threat_level = calculate_threat_level(enemy_unit, distance);
+                    // Remembered enemies matter less the staler the memory
+                    let threat_level = calculate_threat_level(
+                        known.damage,
+                        known.health,
+                        known.max_health,
+                        distance,
+                    ) * known.confidence;
 
                     candidates.push(TargetCandidate {
-                        entity: enemy_entity,
-                        position: enemy_transform.translation,
+                        entity: known.entity,
+                        position: known.position,
                         distance,
-                        health: enemy_unit.health,
-                        max_health: enemy_unit.max_health,
-                        damage: enemy_unit.attack_damage,
-                        is_leader: leader.is_some(),
+                        health: known.health,
+                        max_health: known.max_health,
+                        damage: known.damage,
+                        is_leader: known.is_leader,
                         threat_level,
                         priority_score: 0.0, // Will be calculated
                     });
@@ -255,9 +271,9 @@ pub fn target_acquisition_system(
 }
 
 // Calculate threat level of a unit
-fn calculate_threat_level(unit: &Unit, distance: f32) -> f32 {
-    let damage_threat = unit.attack_damage / 10.0;
-    let health_threat = unit.health / unit.max_health;
+fn calculate_threat_level(damage: f32, health: f32, max_health: f32, distance: f32) -> f32 {
+    let damage_threat = damage / 10.0;
+    let health_threat = health / max_health;
     let distance_threat = 1.0 / (distance + 1.0);
 
     (damage_threat + health_threat + distance_threat) / 3.0
@@ -281,19 +297,21 @@ pub fn target_validation_system(
 
 // Line of sight system - checks if target is visible
 pub fn line_of_sight_system(
-    mut query: Query<(&mut TargetSelector, &Transform)>,
-    target_query: Query<&Transform, Without<TargetSelector>>,
-    obstacle_query: Query<(&Transform, &CollisionMask), Without<Unit>>,
+    mut query: Query<(Entity, &mut TargetSelector, &Transform, Has<Airborne>)>,
+    target_query: Query<(&Transform, Has<Airborne>), Without<TargetSelector>>,
+    raycast: PhysicsRaycast,
 ) {
-    for (mut selector, transform) in query.iter_mut() {
+    for (entity, mut selector, transform, airborne) in query.iter_mut() {
         if let Some(target) = selector.current_target
-            && let Ok(target_transform) = target_query.get(target)
+            && let Ok((target_transform, target_airborne)) = target_query.get(target)
         {
-            // Check if line of sight is blocked
+            // Check if line of sight is blocked (cliffs don't block air units)
             if is_line_of_sight_blocked(
                 transform.translation,
                 target_transform.translation,
-                &obstacle_query,
+                airborne || target_airborne,
+                &raycast,
+                &[entity, target],
             ) {
                 // Can't see target, clear it
                 selector.clear_target();
@@ -302,42 +320,28 @@ pub fn line_of_sight_system(
     }
 }
 
-fn is_line_of_sight_blocked(
+/// Buildings and terrain block sight; units never do, and terrain doesn't
+/// block when either end is in the air
+pub fn is_line_of_sight_blocked(
     from: Vec3,
     to: Vec3,
-    obstacles: &Query<(&Transform, &CollisionMask), Without<Unit>>,
+    airborne: bool,
+    raycast: &PhysicsRaycast,
+    ignore: &[Entity],
 ) -> bool {
-    let direction = (to - from).normalize();
-    let distance = from.distance(to);
-
-    // Simple raycast check
-    for (obstacle_transform, _) in obstacles.iter() {
-        let obstacle_pos = obstacle_transform.translation;
-
-        // Check if obstacle is near the line
-        let to_obstacle = obstacle_pos - from;
-        let projection = to_obstacle.dot(direction);
-
-        if projection > 0.0 && projection < distance {
-            let closest_point = from + direction * projection;
-            let distance_to_line = closest_point.distance(obstacle_pos);
-
-            if distance_to_line < 2.0 {
-                return true; // Line of sight blocked
-            }
-        }
-    }
-
-    false
+    !raycast.line_of_sight(from, to, sight_blocking_layers(airborne, false), ignore)
 }
 
-// Target prediction system - predicts where moving targets will be
+// Target prediction system - predicts where moving targets will be.
+// Targets out of sight stay at their last known position.
 pub fn target_prediction_system(
-    mut query: Query<(&mut TargetSelector, &Transform)>,
+    mut query: Query<(&mut TargetSelector, &Transform, &Team)>,
     target_query: Query<(&Transform, &Velocity), Without<TargetSelector>>,
+    knowledge: Res<AIKnowledge>,
 ) {
-    for (mut selector, transform) in query.iter_mut() {
+    for (mut selector, transform, team) in query.iter_mut() {
         if let Some(target) = selector.current_target
+            && knowledge.is_in_sight(team.id, target)
             && let Ok((target_transform, target_velocity)) = target_query.get(target)
         {
             // Predict where target will be
@@ -383,19 +387,19 @@ pub fn get_weakest_enemy(
     position: Vec3,
     team_id: u32,
     max_range: f32,
-    enemies: &Query<(Entity, &Transform, &Team, &Unit)>,
+    enemies: &Query<(Entity, &Transform, &Team, &Health)>,
 ) -> Option<Entity> {
     let mut weakest = None;
     let mut min_health = f32::MAX;
 
-    for (entity, transform, team, unit) in enemies.iter() {
+    for (entity, transform, team, health) in enemies.iter() {
         if team.id == team_id {
             continue;
         }
 
         let distance = position.distance(transform.translation);
-        if distance <= max_range && unit.health < min_health {
-            min_health = unit.health;
+        if distance <= max_range && health.current < min_health {
+            min_health = health.current;
             weakest = Some(entity);
         }
     }
@@ -0,0 +1,116 @@
+// Damage and status effects of resolved abilities
+use crate::components::*;
+use crate::damage::{AreaDamageEvent, DamageEvent};
+use crate::status::ApplyStatusEffect;
+use crate::systems::ATTACK_REVEAL_DURATION;
+use bevy::prelude::*;
+use game_physics::{AbilityEffect, AbilityResolved, RevealStealth, StatusKind};
+
+/// Status effect of the given kind with an ability's magnitude
+pub fn status_effect_type(kind: StatusKind, magnitude: f32) -> StatusEffectType {
+    match kind {
+        StatusKind::AttackSpeed => StatusEffectType::AttackSpeed(magnitude),
+        StatusKind::MovementSpeed => StatusEffectType::MovementSpeed(magnitude),
+        StatusKind::DamageBoost => StatusEffectType::DamageBoost(magnitude),
+        StatusKind::ArmorBoost => StatusEffectType::ArmorBoost(magnitude),
+        StatusKind::Regeneration => StatusEffectType::Regeneration(magnitude),
+        StatusKind::Slow => StatusEffectType::Slow(magnitude),
+        StatusKind::Stun => StatusEffectType::Stun,
+        StatusKind::Silence => StatusEffectType::Silence,
+        StatusKind::Blind => StatusEffectType::Blind,
+        StatusKind::Poison => StatusEffectType::Poison(magnitude),
+        StatusKind::Burn => StatusEffectType::Burn(magnitude),
+        StatusKind::Freeze => StatusEffectType::Freeze,
+        StatusKind::Madness => StatusEffectType::Madness(magnitude),
+        StatusKind::Corruption => StatusEffectType::Corruption(magnitude),
+        StatusKind::VoidTouch => StatusEffectType::VoidTouch,
+        StatusKind::DeepCurse => StatusEffectType::DeepCurse,
+    }
+}
+
+/// System to turn resolved abilities into damage and status effect requests.
+///
+/// Offensive abilities reveal a stealthed caster like an attack does.
+pub fn ability_combat_effect_system(
+    mut resolved_events: MessageReader<AbilityResolved>,
+    mut damage_events: MessageWriter<DamageEvent>,
+    mut area_damage_events: MessageWriter<AreaDamageEvent>,
+    mut status_requests: MessageWriter<ApplyStatusEffect>,
+    mut reveal_events: MessageWriter<RevealStealth>,
+) {
+    for event in resolved_events.read() {
+        let mut offensive = false;
+
+        for effect in &event.effects {
+            match effect {
+                AbilityEffect::Damage {
+                    amount,
+                    damage_type,
+                } => {
+                    offensive |= !event.targets.is_empty();
+                    for target in &event.targets {
+                        damage_events.write(DamageEvent {
+                            attacker: event.caster,
+                            target: *target,
+                            amount: *amount,
+                            damage_type: *damage_type,
+                            penetration: 0.0,
+                            is_critical: false,
+                        });
+                    }
+                }
+                AbilityEffect::AreaDamage {
+                    amount,
+                    damage_type,
+                    radius,
+                    falloff,
+                    friendly_fire,
+                    line_of_sight,
+                } => {
+                    offensive = true;
+                    area_damage_events.write(AreaDamageEvent {
+                        attacker: event.caster,
+                        center: event.point,
+                        amount: *amount,
+                        damage_type: *damage_type,
+                        penetration: 0.0,
+                        is_critical: false,
+                        area: AreaDamage {
+                            radius: *radius,
+                            falloff: *falloff,
+                            friendly_fire: *friendly_fire,
+                            line_of_sight: *line_of_sight,
+                        },
+                        direct_hit: None,
+                    });
+                }
+                AbilityEffect::Status {
+                    kind,
+                    magnitude,
+                    duration,
+                } => {
+                    let effect_type = status_effect_type(*kind, *magnitude);
+                    offensive |= effect_type.is_debuff() && !event.targets.is_empty();
+                    for target in &event.targets {
+                        status_requests.write(ApplyStatusEffect {
+                            target: *target,
+                            effect: StatusEffect::new(
+                                effect_type.clone(),
+                                *duration,
+                                Some(event.caster),
+                            ),
+                        });
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        if offensive {
+            reveal_events.write(RevealStealth {
+                entity: event.caster,
+                duration: ATTACK_REVEAL_DURATION,
+            });
+        }
+    }
+}
@@ -1,8 +1,11 @@
 // Core combat components that are shared across systems
 use bevy::prelude::*;
+use game_physics::{StatKind, Stats};
 use serde::{Deserialize, Serialize};
 
-/// Combat stats for units
+/// Combat stat preset for units, turned into `Stats` at spawn.
+///
+/// Systems read final values from `Stats` so modifiers apply.
 #[derive(Component, Clone, Debug, Serialize, Deserialize)]
 pub struct CombatStats {
     pub damage: f32,
@@ -28,32 +31,24 @@ impl Default for CombatStats {
     }
 }
 
-/// Weapon component
-#[derive(Component, Clone, Debug, Serialize, Deserialize)]
-pub struct Weapon {
-    pub weapon_type: WeaponType,
-    pub range: f32,
-    pub projectile_speed: Option<f32>,
-    pub area_of_effect: Option<f32>,
-    pub penetration: f32,
+impl CombatStats {
+    /// Base values for the stat pipeline
+    pub fn to_stats(&self) -> Stats {
+        Stats::new()
+            .with(StatKind::AttackDamage, self.damage)
+            .with(StatKind::AttackSpeed, self.attack_speed)
+            .with(StatKind::Armor, self.armor)
+            .with(StatKind::MagicResist, self.magic_resist)
+            .with(StatKind::CriticalChance, self.critical_chance)
+            .with(StatKind::CriticalDamage, self.critical_damage)
+            .with(StatKind::LifeSteal, self.life_steal)
+    }
 }
 
-#[derive(Clone, Debug, Serialize, Deserialize)]
-pub enum WeaponType {
-    Melee,
-    Ranged,
-    Magic,
-    Siege,
-}
+// Weapons are shared with unit spawning, which attaches them to templates
+pub use game_physics::{Weapon, WeaponType, Weapons};
 
-/// Damage types for resistance calculations
-#[derive(Clone, Debug, Serialize, Deserialize)]
-pub enum DamageType {
-    Physical,
-    Magic,
-    True,  // Ignores armor/resist
-    Chaos, // Lovecraftian - mixed damage
-}
+pub use game_physics::DamageType;
 
 /// Team component for faction identification
 #[derive(Component, Clone, Debug, Serialize, Deserialize)]
@@ -71,27 +66,11 @@ pub enum Faction {
     Neutral,
 }
 
-/// Unit type classification
-#[derive(Component, Clone, Debug, Serialize, Deserialize)]
-pub struct UnitType {
-    pub classification: UnitClassification,
-    pub is_flying: bool,
-    pub is_mechanical: bool,
-    pub is_biological: bool,
-    pub is_ethereal: bool,
-}
-
-#[derive(Clone, Debug, Serialize, Deserialize)]
-pub enum UnitClassification {
-    Infantry,
-    Vehicle,
-    Monster,
-    Hero,
-    Building,
-}
+// Unit types are shared with unit and building spawning, which attach them
+pub use game_physics::{UnitClassification, UnitType};
 
-/// Buff/Debuff component
-#[derive(Component, Clone, Debug)]
+/// One buff or debuff instance, held in a `StatusEffects` container
+#[derive(Clone, Debug)]
 pub struct StatusEffect {
     pub effect_type: StatusEffectType,
     pub duration: f32,
@@ -100,6 +79,18 @@ pub struct StatusEffect {
     pub source: Option<Entity>,
 }
 
+impl StatusEffect {
+    pub fn new(effect_type: StatusEffectType, duration: f32, source: Option<Entity>) -> Self {
+        Self {
+            effect_type,
+            duration,
+            remaining: duration,
+            stacks: 1,
+            source,
+        }
+    }
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub enum StatusEffectType {
     // Buffs
@@ -137,16 +128,45 @@ pub struct CombatLog {
 }
 
 /// Projectile component
+///
+/// Moved by `projectile_movement_system` according to its motion model. Hits
+/// are found by sweeping the path travelled each frame (see
+/// `game_physics::ContinuousCollision`), so fast projectiles don't tunnel.
 #[derive(Component)]
+#[require(game_physics::ContinuousCollision)]
 pub struct Projectile {
     pub owner: Entity,
+    /// Owner's team when fired; projectiles pass through it
+    pub team: Option<u32>,
     pub damage: f32,
     pub damage_type: DamageType,
+    /// Fraction of armor or magic resist ignored, from the firing weapon
+    pub penetration: f32,
+    /// Critical strike rolled when fired, carried to every hit
+    pub is_critical: bool,
     pub speed: f32,
+    pub motion: ProjectileMotion,
+    pub velocity: Vec3,
     pub lifetime: f32,
     pub remaining_lifetime: f32,
     pub pierce_count: u32,
     pub area_damage: Option<AreaDamage>,
+    /// Victims already hit, so piercing projectiles hit each one once
+    pub hit_entities: Vec<Entity>,
+}
+
+/// How a projectile travels
+#[derive(Clone, Debug, PartialEq)]
+pub enum ProjectileMotion {
+    /// Flies in a straight line at constant speed
+    Straight,
+    /// Arcs under gravity and detonates when it comes down to `impact_height`
+    Ballistic { gravity: f32, impact_height: f32 },
+    /// Turns towards its target by up to `turn_rate` radians per second
+    Homing {
+        target: Option<Entity>,
+        turn_rate: f32,
+    },
 }
 
 #[derive(Clone, Debug)]
@@ -154,18 +174,21 @@ pub struct AreaDamage {
     pub radius: f32,
     pub falloff: f32, // Damage reduction per unit distance
     pub friendly_fire: bool,
+    /// Buildings and cliffs shelter units from the blast
+    pub line_of_sight: bool,
 }
 
-/// Shield component for extra protection
-#[derive(Component, Clone, Debug)]
-pub struct Shield {
-    pub current: f32,
-    pub maximum: f32,
-    pub regeneration_rate: f32,
-    pub regeneration_delay: f32,
-    pub time_since_damage: f32,
+impl AreaDamage {
+    /// Damage dealt `distance` away from the centre; `falloff` is the fraction
+    /// of full damage lost per unit of distance
+    pub fn damage_at(&self, amount: f32, distance: f32) -> f32 {
+        amount * (1.0 - self.falloff * distance).clamp(0.0, 1.0)
+    }
 }
 
+/// Shield component for extra protection, shared with units
+pub use game_physics::Shield;
+
 /// Marker component for invulnerable entities
 #[derive(Component)]
 pub struct Invulnerable {
@@ -1,19 +1,31 @@
 // Damage calculation and application system
 use crate::components::*;
+use crate::damage_matrix::DamageMatrix;
 use crate::states::Health;
+use crate::status::StatusEffects;
 use bevy::prelude::*;
+use game_physics::{
+    Airborne, CollisionTeam, GlobalSpatialGrid, Healed, PhysicsRaycast, StatKind, Stats,
+    sight_blocking_layers,
+};
 
 pub struct DamagePlugin;
 
 impl Plugin for DamagePlugin {
     fn build(&self, app: &mut App) {
-        app.add_message::<DamageEvent>()
+        // Bundled balance unless the game inserted its own matrix
+        app.init_resource::<DamageMatrix>()
+            .add_message::<DamageEvent>()
+            .add_message::<AreaDamageEvent>()
+            .add_message::<DamageApplied>()
             .add_message::<DeathEvent>()
+            .add_message::<Healed>()
             .add_systems(
                 Update,
                 (
-                    process_damage_events,
+                    area_damage_system,
                     apply_damage_modifiers,
+                    process_damage_events,
                     check_for_deaths,
                 )
                     .chain(),
@@ -27,6 +39,36 @@ pub struct DamageEvent {
     pub target: Entity,
     pub amount: f32,
     pub damage_type: DamageType,
+    /// Fraction of the target's armor or magic resist ignored
+    pub penetration: f32,
+    pub is_critical: bool,
+}
+
+/// Blast that damages everything around a point: explosions, exploding
+/// projectiles and area abilities
+#[derive(Event, Clone, Debug)]
+pub struct AreaDamageEvent {
+    pub attacker: Entity,
+    pub center: Vec3,
+    pub amount: f32,
+    pub damage_type: DamageType,
+    pub penetration: f32,
+    pub is_critical: bool,
+    pub area: AreaDamage,
+    /// Struck directly, so takes full damage wherever its centre is
+    pub direct_hit: Option<Entity>,
+}
+
+/// Damage that landed after matchups, resistances and shields
+#[derive(Event, Clone, Debug)]
+pub struct DamageApplied {
+    pub attacker: Entity,
+    pub target: Entity,
+    /// Damage after the damage matrix and resistances, without overkill
+    pub amount: f32,
+    /// Part of `amount` soaked up by a shield
+    pub absorbed: f32,
+    pub damage_type: DamageType,
     pub is_critical: bool,
 }
 
@@ -36,44 +78,150 @@ pub struct DeathEvent {
     pub killer: Option<Entity>,
 }
 
-/// Process damage events and apply damage
+/// Split blasts into one `DamageEvent` per victim so logs and XP credit each hit.
+///
+/// Victims come from the spatial grid, so stealthed units are caught too. The
+/// attacker is never hit by its own blast.
+#[allow(clippy::type_complexity)]
+pub fn area_damage_system(
+    mut area_events: MessageReader<AreaDamageEvent>,
+    mut damage_events: MessageWriter<DamageEvent>,
+    victim_query: Query<(&Transform, &Health, Option<&CollisionTeam>, Has<Airborne>)>,
+    team_query: Query<&CollisionTeam>,
+    spatial_grid: Res<GlobalSpatialGrid>,
+    raycast: PhysicsRaycast,
+) {
+    for event in area_events.read() {
+        let attacker_team = team_query.get(event.attacker).ok().map(|team| team.0);
+
+        let mut victims = spatial_grid
+            .grid
+            .query_radius(event.center, event.area.radius);
+        if let Some(direct_hit) = event.direct_hit
+            && !victims.contains(&direct_hit)
+        {
+            victims.push(direct_hit);
+        }
+
+        for victim in victims {
+            if victim == event.attacker {
+                continue;
+            }
+            let Ok((transform, health, team, airborne)) = victim_query.get(victim) else {
+                continue;
+            };
+            if health.is_dead() {
+                continue;
+            }
+            if !event.area.friendly_fire
+                && attacker_team.is_some()
+                && attacker_team == team.map(|team| team.0)
+            {
+                continue;
+            }
+
+            let direct = event.direct_hit == Some(victim);
+            if !direct
+                && event.area.line_of_sight
+                && !raycast.line_of_sight(
+                    event.center,
+                    transform.translation,
+                    sight_blocking_layers(false, airborne),
+                    &[victim, event.attacker],
+                )
+            {
+                continue;
+            }
+
+            let amount = if direct {
+                event.amount
+            } else {
+                let distance = event.center.distance(transform.translation);
+                event.area.damage_at(event.amount, distance)
+            };
+            if amount <= 0.0 {
+                continue;
+            }
+
+            damage_events.write(DamageEvent {
+                attacker: event.attacker,
+                target: victim,
+                amount,
+                damage_type: event.damage_type,
+                penetration: event.penetration,
+                is_critical: event.is_critical,
+            });
+        }
+    }
+}
+
+/// Process damage events and apply damage.
+///
+/// The killing blow marks the victim `Dead` and credits the attacker, unless
+/// the damage was self-inflicted; later hits on the corpse are ignored.
+#[allow(clippy::type_complexity)]
 pub fn process_damage_events(
+    mut commands: Commands,
     mut damage_events: MessageReader<DamageEvent>,
-    mut health_query: Query<&mut Health>,
-    combat_stats_query: Query<&CombatStats>,
-    mut shield_query: Query<&mut Shield>,
-    invulnerable_query: Query<&Invulnerable>,
+    mut target_query: Query<(
+        &mut Health,
+        Option<&mut Shield>,
+        Option<&Stats>,
+        Option<&UnitType>,
+        Has<Invulnerable>,
+        Has<Dead>,
+    )>,
+    damage_matrix: Res<DamageMatrix>,
+    mut applied_events: MessageWriter<DamageApplied>,
     mut death_events: MessageWriter<DeathEvent>,
+    time: Res<Time>,
 ) {
     for event in damage_events.read() {
-        // Skip if target is invulnerable
-        if invulnerable_query.contains(event.target) {
+        let Ok((mut health, shield, stats, unit_type, invulnerable, dead)) =
+            target_query.get_mut(event.target)
+        else {
+            continue;
+        };
+        // Skip if target is invulnerable or already down
+        if invulnerable || dead || health.current <= 0.0 {
             continue;
         }
 
-        // Calculate final damage after resistances
-        let final_damage = calculate_damage(
-            event.amount,
-            &event.damage_type,
-            combat_stats_query.get(event.target).ok(),
-        );
+        // Damage type matchups first, then resistances
+        let amount = event.amount * damage_matrix.multiplier(event.damage_type, unit_type);
+        let final_damage = calculate_damage(amount, &event.damage_type, event.penetration, stats);
 
         // Apply damage to shield first, then health
-        let remaining_damage = if let Ok(mut shield) = shield_query.get_mut(event.target) {
+        let remaining_damage = if let Some(mut shield) = shield {
             apply_shield_damage(&mut shield, final_damage)
         } else {
             final_damage
         };
 
-        if let Ok(mut health) = health_query.get_mut(event.target) {
-            health.current = (health.current - remaining_damage).max(0.0);
+        // Overkill doesn't count as damage dealt
+        let health_lost = remaining_damage.min(health.current);
+        health.current -= health_lost;
+        let absorbed = final_damage - remaining_damage;
 
-            if health.current <= 0.0 {
-                death_events.write(DeathEvent {
-                    entity: event.target,
-                    killer: Some(event.attacker),
-                });
-            }
+        applied_events.write(DamageApplied {
+            attacker: event.attacker,
+            target: event.target,
+            amount: absorbed + health_lost,
+            absorbed,
+            damage_type: event.damage_type,
+            is_critical: event.is_critical,
+        });
+
+        if health.current <= 0.0 {
+            let killer = (event.attacker != event.target).then_some(event.attacker);
+            commands.entity(event.target).insert(Dead {
+                killer,
+                death_time: time.elapsed_seconds(),
+            });
+            death_events.write(DeathEvent {
+                entity: event.target,
+                killer,
+            });
         }
     }
 }
@@ -81,17 +229,22 @@ pub fn process_damage_events(
 fn calculate_damage(
     base_damage: f32,
     damage_type: &DamageType,
-    target_stats: Option<&CombatStats>,
+    penetration: f32,
+    target_stats: Option<&Stats>,
 ) -> f32 {
     if let Some(stats) = target_stats {
+        // Penetration ignores a share of the target's resistances
+        let remaining = 1.0 - penetration.clamp(0.0, 1.0);
+        let armor = stats.get(StatKind::Armor) * remaining;
+        let magic_resist = stats.get(StatKind::MagicResist) * remaining;
         match damage_type {
-            DamageType::Physical => {
-                // Armor reduces physical damage
-                base_damage * (100.0 / (100.0 + stats.armor))
+            DamageType::Physical | DamageType::Siege => {
+                // Armor reduces physical and siege damage
+                base_damage * (100.0 / (100.0 + armor))
             }
             DamageType::Magic => {
                 // Magic resist reduces magic damage
-                base_damage * (100.0 / (100.0 + stats.magic_resist))
+                base_damage * (100.0 / (100.0 + magic_resist))
             }
             DamageType::True => {
                 // True damage ignores resistances
@@ -99,8 +252,8 @@ fn calculate_damage(
             }
             DamageType::Chaos => {
                 // Chaos damage is 50% physical, 50% magic
-                let physical = base_damage * 0.5 * (100.0 / (100.0 + stats.armor));
-                let magic = base_damage * 0.5 * (100.0 / (100.0 + stats.magic_resist));
+                let physical = base_damage * 0.5 * (100.0 / (100.0 + armor));
+                let magic = base_damage * 0.5 * (100.0 / (100.0 + magic_resist));
                 physical + magic
             }
         }
@@ -120,36 +273,64 @@ fn apply_shield_damage(shield: &mut Shield, damage: f32) -> f32 {
     }
 }
 
-/// Apply damage over time effects
-pub fn apply_damage_modifiers(mut query: Query<(&mut Health, &StatusEffect)>, time: Res<Time>) {
-    for (mut health, status) in query.iter_mut() {
-        match &status.effect_type {
-            StatusEffectType::Poison(damage_per_second) => {
-                health.current -= damage_per_second * time.delta_seconds();
-            }
-            StatusEffectType::Burn(damage_per_second) => {
-                health.current -= damage_per_second * time.delta_seconds();
-            }
-            StatusEffectType::Regeneration(heal_per_second) => {
-                health.current =
-                    (health.current + heal_per_second * time.delta_seconds()).min(health.maximum);
+/// Apply damage and healing over time.
+///
+/// Damage ticks go through `DamageEvent` so shields absorb them and the
+/// effect's source gets the credit; effects without a source count as
+/// self-inflicted.
+pub fn apply_damage_modifiers(
+    mut query: Query<(Entity, &mut Health, &StatusEffects)>,
+    mut damage_events: MessageWriter<DamageEvent>,
+    mut heal_events: MessageWriter<Healed>,
+    time: Res<Time>,
+) {
+    let dt = time.delta_seconds();
+
+    for (entity, mut health, effects) in query.iter_mut() {
+        for status in effects.iter() {
+            let stacks = status.stacks.max(1) as f32;
+            match &status.effect_type {
+                StatusEffectType::Poison(damage_per_second)
+                | StatusEffectType::Burn(damage_per_second) => {
+                    damage_events.write(DamageEvent {
+                        attacker: status.source.unwrap_or(entity),
+                        target: entity,
+                        amount: damage_per_second * stacks * dt,
+                        damage_type: DamageType::True,
+                        penetration: 0.0,
+                        is_critical: false,
+                    });
+                }
+                StatusEffectType::Regeneration(heal_per_second) => {
+                    let restored =
+                        (heal_per_second * stacks * dt).min(health.maximum - health.current);
+                    if restored > 0.0 && !health.is_dead() {
+                        health.current += restored;
+                        heal_events.write(Healed {
+                            healer: status.source,
+                            target: entity,
+                            amount: restored,
+                        });
+                    }
+                }
+                _ => {}
             }
-            _ => {}
         }
     }
 }
 
-/// Check for deaths and mark entities
+/// Check for deaths from anything other than a hit, e.g. damage over time
 pub fn check_for_deaths(
     mut commands: Commands,
     query: Query<(Entity, &Health), Without<Dead>>,
     mut death_events: MessageWriter<DeathEvent>,
+    time: Res<Time>,
 ) {
     for (entity, health) in query.iter() {
         if health.current <= 0.0 {
             commands.entity(entity).insert(Dead {
                 killer: None,
-                death_time: 0.0,
+                death_time: time.elapsed_seconds(),
             });
 
             death_events.write(DeathEvent {
@@ -160,4 +341,221 @@ pub fn check_for_deaths(
     }
 }
 impl bevy::prelude::Message for DamageEvent {}
+impl bevy::prelude::Message for AreaDamageEvent {}
+impl bevy::prelude::Message for DamageApplied {}
 impl bevy::prelude::Message for DeathEvent {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use game_physics::{AABB, Obstacle, StealthState};
+
+    fn blast_app() -> App {
+        let mut app = App::new();
+        app.init_resource::<GlobalSpatialGrid>()
+            .add_message::<AreaDamageEvent>()
+            .add_message::<DamageEvent>()
+            .add_systems(Update, area_damage_system);
+        app
+    }
+
+    /// Spawn something at `position` and index it in the spatial grid
+    fn spawn_at(app: &mut App, position: Vec3, bundle: impl Bundle) -> Entity {
+        let entity = app
+            .world_mut()
+            .spawn((Transform::from_translation(position), bundle))
+            .id();
+        app.world_mut()
+            .resource_mut::<GlobalSpatialGrid>()
+            .grid
+            .insert(entity, position);
+        entity
+    }
+
+    fn unit(app: &mut App, position: Vec3, team: u32) -> Entity {
+        spawn_at(app, position, (Health::new(100.0), CollisionTeam(team)))
+    }
+
+    fn blast(attacker: Entity, area: AreaDamage, direct_hit: Option<Entity>) -> AreaDamageEvent {
+        AreaDamageEvent {
+            attacker,
+            center: Vec3::ZERO,
+            amount: 100.0,
+            damage_type: DamageType::Physical,
+            penetration: 0.0,
+            is_critical: false,
+            area,
+            direct_hit,
+        }
+    }
+
+    fn area(friendly_fire: bool, line_of_sight: bool) -> AreaDamage {
+        AreaDamage {
+            radius: 8.0,
+            falloff: 0.1,
+            friendly_fire,
+            line_of_sight,
+        }
+    }
+
+    /// Damage each entity took from this frame's blasts, in event order
+    fn blast_hits(app: &mut App, event: AreaDamageEvent) -> Vec<(Entity, f32)> {
+        app.world_mut().write_message(event);
+        app.update();
+        app.world()
+            .resource::<Messages<DamageEvent>>()
+            .iter_current_update_messages()
+            .map(|hit| (hit.target, hit.amount))
+            .collect()
+    }
+
+    fn damage_to(hits: &[(Entity, f32)], entity: Entity) -> Option<f32> {
+        hits.iter()
+            .find(|(target, _)| *target == entity)
+            .map(|(_, amount)| *amount)
+    }
+
+    #[test]
+    fn damage_falls_off_with_distance() {
+        let mut app = blast_app();
+        let attacker = unit(&mut app, Vec3::new(0.0, 0.0, -20.0), 1);
+        let near = unit(&mut app, Vec3::new(2.0, 0.0, 0.0), 2);
+        let far = unit(&mut app, Vec3::new(0.0, 0.0, 6.0), 2);
+        let outside = unit(&mut app, Vec3::new(9.0, 0.0, 0.0), 2);
+
+        let hits = blast_hits(&mut app, blast(attacker, area(false, false), None));
+        assert!((damage_to(&hits, near).unwrap() - 80.0).abs() < 1e-4);
+        assert!((damage_to(&hits, far).unwrap() - 40.0).abs() < 1e-4);
+        assert_eq!(damage_to(&hits, outside), None);
+        assert_eq!(damage_to(&hits, attacker), None);
+    }
+
+    #[test]
+    fn friendly_fire_decides_whether_allies_are_hit() {
+        let mut app = blast_app();
+        let attacker = unit(&mut app, Vec3::new(1.0, 0.0, 0.0), 1);
+        let ally = unit(&mut app, Vec3::new(-1.0, 0.0, 0.0), 1);
+        let enemy = unit(&mut app, Vec3::new(0.0, 0.0, 1.0), 2);
+
+        let hits_without = blast_hits(&mut app, blast(attacker, area(false, false), None));
+        assert_eq!(damage_to(&hits_without, ally), None);
+        assert!(damage_to(&hits_without, enemy).is_some());
+
+        let hits_with = blast_hits(&mut app, blast(attacker, area(true, false), None));
+        assert!(damage_to(&hits_with, ally).is_some());
+        assert!(damage_to(&hits_with, enemy).is_some());
+        // Never the attacker itself
+        assert_eq!(damage_to(&hits_with, attacker), None);
+    }
+
+    #[test]
+    fn walls_shelter_units_when_line_of_sight_is_required() {
+        let mut app = blast_app();
+        let attacker = unit(&mut app, Vec3::new(0.0, 0.0, -20.0), 1);
+        spawn_at(
+            &mut app,
+            Vec3::new(3.0, 0.0, 0.0),
+            (AABB::new(Vec3::new(0.5, 5.0, 5.0)), Obstacle),
+        );
+        let sheltered = unit(&mut app, Vec3::new(5.0, 0.0, 0.0), 2);
+        let exposed = unit(&mut app, Vec3::new(-5.0, 0.0, 0.0), 2);
+
+        let hits_blocked = blast_hits(&mut app, blast(attacker, area(false, true), None));
+        assert_eq!(damage_to(&hits_blocked, sheltered), None);
+        assert!(damage_to(&hits_blocked, exposed).is_some());
+
+        let hits_open = blast_hits(&mut app, blast(attacker, area(false, false), None));
+        assert!(damage_to(&hits_open, sheltered).is_some());
+    }
+
+    #[test]
+    fn stealthed_units_are_caught_in_the_blast() {
+        let mut app = blast_app();
+        let attacker = unit(&mut app, Vec3::new(0.0, 0.0, -20.0), 1);
+        let cloaked = spawn_at(
+            &mut app,
+            Vec3::new(1.0, 0.0, 0.0),
+            (
+                Health::new(100.0),
+                CollisionTeam(2),
+                StealthState {
+                    cloaked: true,
+                    ..default()
+                },
+            ),
+        );
+
+        let hits = blast_hits(&mut app, blast(attacker, area(false, false), None));
+        assert!(damage_to(&hits, cloaked).is_some());
+    }
+
+    #[test]
+    fn each_victim_is_hit_once_and_the_direct_hit_takes_full_damage() {
+        let mut app = blast_app();
+        let attacker = unit(&mut app, Vec3::new(0.0, 0.0, -20.0), 1);
+        let struck = unit(&mut app, Vec3::new(4.0, 0.0, 0.0), 2);
+        let bystander = unit(&mut app, Vec3::new(0.0, 0.0, 4.0), 2);
+        // Struck directly even though its centre is outside the radius
+        let giant = unit(&mut app, Vec3::new(12.0, 0.0, 0.0), 2);
+
+        let hits = blast_hits(&mut app, blast(attacker, area(false, false), Some(struck)));
+        assert_eq!(hits.len(), 2);
+        assert_eq!(damage_to(&hits, struck), Some(100.0));
+        assert!((damage_to(&hits, bystander).unwrap() - 60.0).abs() < 1e-4);
+        assert_eq!(damage_to(&hits, giant), None);
+
+        // A direct hit outside the blast still lands, once and in full
+        let hits = blast_hits(&mut app, blast(attacker, area(false, false), Some(giant)));
+        assert_eq!(damage_to(&hits, giant), Some(100.0));
+        assert_eq!(
+            hits.iter().filter(|(target, _)| *target == giant).count(),
+            1
+        );
+    }
+
+    #[test]
+    fn matrix_scales_hits_before_armor() {
+        let mut app = App::new();
+        app.init_resource::<Time>()
+            .init_resource::<DamageMatrix>()
+            .add_message::<DamageEvent>()
+            .add_message::<DamageApplied>()
+            .add_message::<DeathEvent>()
+            .add_systems(Update, process_damage_events);
+        // 100 armor halves physical damage
+        let armored = || {
+            (
+                Health::new(1000.0),
+                Stats::new().with(StatKind::Armor, 100.0),
+            )
+        };
+        let attacker = app.world_mut().spawn_empty().id();
+        let temple = app
+            .world_mut()
+            .spawn((armored(), UnitType::new(UnitClassification::Building)))
+            .id();
+        let untyped = app.world_mut().spawn(armored()).id();
+
+        for target in [temple, untyped] {
+            app.world_mut().write_message(DamageEvent {
+                attacker,
+                target,
+                amount: 100.0,
+                damage_type: DamageType::Siege,
+                penetration: 0.0,
+                is_critical: false,
+            });
+        }
+        app.update();
+
+        let dealt: Vec<(Entity, f32)> = app
+            .world()
+            .resource::<Messages<DamageApplied>>()
+            .iter_current_update_messages()
+            .map(|applied| (applied.target, applied.amount))
+            .collect();
+        // Siege doubles against buildings, then armor halves it
+        assert_eq!(dealt, vec![(temple, 100.0), (untyped, 50.0)]);
+        assert_eq!(app.world().get::<Health>(temple).unwrap().current, 900.0);
+    }
+}
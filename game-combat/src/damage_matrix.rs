@@ -0,0 +1,114 @@
+// Balance table of damage multipliers by damage type and target armor class
+use crate::components::*;
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Balance file bundled with the game
+pub const DEFAULT_DAMAGE_MATRIX: &str = include_str!("../balance/damage_matrix.ron");
+
+/// What a unit counts as for the damage matrix: its classification plus
+/// its mechanical, biological and ethereal flags
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum ArmorClass {
+    Infantry,
+    Vehicle,
+    Monster,
+    Hero,
+    Building,
+    Mechanical,
+    Biological,
+    Ethereal,
+}
+
+impl ArmorClass {
+    /// Every armor class a unit type belongs to
+    pub fn of(unit_type: &UnitType) -> Vec<ArmorClass> {
+        let mut classes = vec![match unit_type.classification {
+            UnitClassification::Infantry => ArmorClass::Infantry,
+            UnitClassification::Vehicle => ArmorClass::Vehicle,
+            UnitClassification::Monster => ArmorClass::Monster,
+            UnitClassification::Hero => ArmorClass::Hero,
+            UnitClassification::Building => ArmorClass::Building,
+        }];
+        if unit_type.is_mechanical {
+            classes.push(ArmorClass::Mechanical);
+        }
+        if unit_type.is_biological {
+            classes.push(ArmorClass::Biological);
+        }
+        if unit_type.is_ethereal {
+            classes.push(ArmorClass::Ethereal);
+        }
+        classes
+    }
+}
+
+/// Damage multipliers keyed by damage type and target armor class, applied
+/// before armor and magic resist. Missing entries leave damage unchanged.
+#[derive(Resource, Clone, Debug, Serialize, Deserialize)]
+pub struct DamageMatrix {
+    pub multipliers: HashMap<DamageType, HashMap<ArmorClass, f32>>,
+}
+
+impl Default for DamageMatrix {
+    fn default() -> Self {
+        Self::from_ron(DEFAULT_DAMAGE_MATRIX).expect("bundled damage matrix is valid")
+    }
+}
+
+impl DamageMatrix {
+    /// Parse a damage matrix from RON balance data
+    pub fn from_ron(source: &str) -> Result<Self, ron::error::SpannedError> {
+        ron::from_str(source)
+    }
+
+    /// Multiplier for one damage type against one armor class
+    pub fn get(&self, damage_type: DamageType, class: ArmorClass) -> f32 {
+        self.multipliers
+            .get(&damage_type)
+            .and_then(|classes| classes.get(&class))
+            .copied()
+            .unwrap_or(1.0)
+    }
+
+    /// Combined multiplier against a unit; units without a type take normal damage
+    pub fn multiplier(&self, damage_type: DamageType, unit_type: Option<&UnitType>) -> f32 {
+        unit_type.map_or(1.0, |unit_type| {
+            ArmorClass::of(unit_type)
+                .into_iter()
+                .map(|class| self.get(damage_type, class))
+                .product()
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bundled_matrix_scales_by_class_and_flags() {
+        let matrix = DamageMatrix::default();
+        let wraith = UnitType {
+            classification: UnitClassification::Monster,
+            is_flying: false,
+            is_mechanical: false,
+            is_biological: false,
+            is_ethereal: true,
+        };
+        let temple = UnitType {
+            classification: UnitClassification::Building,
+            is_flying: false,
+            is_mechanical: false,
+            is_biological: false,
+            is_ethereal: false,
+        };
+
+        assert!(matrix.multiplier(DamageType::Chaos, Some(&wraith)) > 2.0);
+        assert!(matrix.multiplier(DamageType::Physical, Some(&wraith)) < 1.0);
+        assert_eq!(matrix.multiplier(DamageType::Siege, Some(&temple)), 2.0);
+        assert_eq!(matrix.multiplier(DamageType::True, Some(&temple)), 1.0);
+        assert_eq!(matrix.multiplier(DamageType::Siege, None), 1.0);
+    }
+}
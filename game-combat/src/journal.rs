@@ -0,0 +1,615 @@
+// Match-wide combat journal, exporters and post-match report
+use crate::components::*;
+use crate::damage::{DamageApplied, DeathEvent};
+use crate::status::StatusEffectApplied;
+use bevy::prelude::*;
+use game_physics::{AbilityResolved, CollisionTeam, Healed};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::io::{self, Write};
+
+pub struct CombatJournalPlugin;
+
+impl Plugin for CombatJournalPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<CombatJournal>()
+            .add_message::<DamageApplied>()
+            .add_message::<DeathEvent>()
+            .add_message::<Healed>()
+            .add_message::<StatusEffectApplied>()
+            .add_message::<AbilityResolved>()
+            .add_systems(Update, combat_journal_system)
+            .add_systems(Last, match_report_on_exit_system);
+    }
+}
+
+/// What a journal entry records
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum JournalEventKind {
+    Damage,
+    Heal,
+    Kill,
+    Status,
+    Ability,
+}
+
+/// An entity as it was when the event happened, so the record outlives it
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct JournalParticipant {
+    pub entity: u64,
+    pub team: Option<u32>,
+    pub unit_type: String,
+}
+
+/// One journaled combat event.
+///
+/// `amount` is damage dealt, health restored, status duration or number of
+/// ability targets; `detail` names the damage type, status or ability.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct JournalEntry {
+    pub tick: u64,
+    pub time: f32,
+    pub kind: JournalEventKind,
+    pub source: Option<JournalParticipant>,
+    pub target: Option<JournalParticipant>,
+    pub amount: f32,
+    pub detail: String,
+}
+
+/// Every damage, heal, kill, status application and ability use of the match
+#[derive(Resource, Default, Clone, Debug)]
+pub struct CombatJournal {
+    /// Frames recorded so far
+    pub tick: u64,
+    pub entries: Vec<JournalEntry>,
+}
+
+impl CombatJournal {
+    pub fn clear(&mut self) {
+        self.tick = 0;
+        self.entries.clear();
+    }
+
+    /// Write one JSON object per entry
+    pub fn write_json_lines(&self, mut writer: impl Write) -> io::Result<()> {
+        for entry in &self.entries {
+            serde_json::to_writer(&mut writer, entry)?;
+            writeln!(writer)?;
+        }
+        Ok(())
+    }
+
+    /// Write the entries as CSV with a header row
+    pub fn write_csv(&self, mut writer: impl Write) -> io::Result<()> {
+        writeln!(
+            writer,
+            "tick,time,kind,source,source_team,source_type,target,target_team,target_type,amount,detail"
+        )?;
+        for entry in &self.entries {
+            write!(writer, "{},{:.3},{:?}", entry.tick, entry.time, entry.kind)?;
+            for participant in [&entry.source, &entry.target] {
+                match participant {
+                    Some(participant) => write!(
+                        writer,
+                        ",{},{},{}",
+                        participant.entity,
+                        participant
+                            .team
+                            .map(|team| team.to_string())
+                            .unwrap_or_default(),
+                        csv_field(&participant.unit_type)
+                    )?,
+                    None => write!(writer, ",,,")?,
+                }
+            }
+            writeln!(writer, ",{:.3},{}", entry.amount, csv_field(&entry.detail))?;
+        }
+        Ok(())
+    }
+
+    /// Totals per team and per unit type over the whole match
+    pub fn summary(&self) -> MatchSummary {
+        let duration = match (self.entries.first(), self.entries.last()) {
+            (Some(first), Some(last)) => last.time - first.time,
+            _ => 0.0,
+        };
+        let mut summary = MatchSummary {
+            duration,
+            ..default()
+        };
+
+        for entry in &self.entries {
+            if let Some(source) = &entry.source {
+                for totals in summary.totals_for(source) {
+                    match entry.kind {
+                        JournalEventKind::Damage => totals.damage_dealt += entry.amount,
+                        JournalEventKind::Heal => totals.healing_done += entry.amount,
+                        JournalEventKind::Kill => totals.kills += 1,
+                        JournalEventKind::Status => totals.statuses_applied += 1,
+                        JournalEventKind::Ability => totals.abilities_used += 1,
+                    }
+                }
+            }
+            if let Some(target) = &entry.target {
+                for totals in summary.totals_for(target) {
+                    match entry.kind {
+                        JournalEventKind::Damage => totals.damage_taken += entry.amount,
+                        JournalEventKind::Kill => totals.deaths += 1,
+                        _ => {}
+                    }
+                }
+            }
+        }
+
+        for totals in summary
+            .by_team
+            .values_mut()
+            .chain(summary.by_unit_type.values_mut())
+        {
+            totals.finish(duration);
+        }
+        summary
+    }
+}
+
+/// Quote a CSV field when it needs it
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+/// Combat totals for a team or unit type
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct CombatTotals {
+    pub damage_dealt: f32,
+    pub damage_taken: f32,
+    pub healing_done: f32,
+    pub kills: u32,
+    pub deaths: u32,
+    pub statuses_applied: u32,
+    pub abilities_used: u32,
+    /// Damage dealt per second of match time
+    pub dps: f32,
+    /// Damage dealt per point of damage taken
+    pub efficiency: f32,
+}
+
+impl CombatTotals {
+    fn finish(&mut self, duration: f32) {
+        self.dps = if duration > 0.0 {
+            self.damage_dealt / duration
+        } else {
+            self.damage_dealt
+        };
+        self.efficiency = if self.damage_taken > 0.0 {
+            self.damage_dealt / self.damage_taken
+        } else {
+            self.damage_dealt
+        };
+    }
+}
+
+/// Post-match report built from the combat journal
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct MatchSummary {
+    /// Seconds between the first and last journaled event
+    pub duration: f32,
+    pub by_team: BTreeMap<u32, CombatTotals>,
+    pub by_unit_type: BTreeMap<String, CombatTotals>,
+}
+
+impl MatchSummary {
+    fn totals_for(&mut self, participant: &JournalParticipant) -> Vec<&mut CombatTotals> {
+        let mut totals = Vec::with_capacity(2);
+        if let Some(team) = participant.team {
+            totals.push(self.by_team.entry(team).or_default());
+        }
+        totals.push(
+            self.by_unit_type
+                .entry(participant.unit_type.clone())
+                .or_default(),
+        );
+        totals
+    }
+}
+
+impl fmt::Display for MatchSummary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Match summary ({:.1}s)", self.duration)?;
+        let rows = self
+            .by_team
+            .iter()
+            .map(|(team, totals)| (format!("team {team}"), totals))
+            .chain(
+                self.by_unit_type
+                    .iter()
+                    .map(|(unit_type, totals)| (unit_type.clone(), totals)),
+            );
+        writeln!(
+            f,
+            "{:<20} {:>10} {:>10} {:>10} {:>6} {:>6} {:>8} {:>10}",
+            "", "dealt", "taken", "healed", "kills", "deaths", "dps", "efficiency"
+        )?;
+        for (label, totals) in rows {
+            writeln!(
+                f,
+                "{:<20} {:>10.0} {:>10.0} {:>10.0} {:>6} {:>6} {:>8.1} {:>10.2}",
+                label,
+                totals.damage_dealt,
+                totals.damage_taken,
+                totals.healing_done,
+                totals.kills,
+                totals.deaths,
+                totals.dps,
+                totals.efficiency
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Capture an entity's team and unit type for the journal
+fn participant(
+    entity: Entity,
+    query: &Query<(Option<&CollisionTeam>, Option<&Name>, Option<&UnitType>)>,
+) -> JournalParticipant {
+    let (team, name, unit_type) = query.get(entity).unwrap_or_default();
+    let unit_type = match (name, unit_type) {
+        (Some(name), _) => name.as_str().to_string(),
+        (None, Some(unit_type)) => format!("{:?}", unit_type.classification),
+        (None, None) => "unknown".to_string(),
+    };
+    JournalParticipant {
+        entity: entity.to_bits(),
+        team: team.map(|team| team.0),
+        unit_type,
+    }
+}
+
+/// System to record this frame's combat events in the journal
+#[allow(clippy::too_many_arguments)]
+pub fn combat_journal_system(
+    mut journal: ResMut<CombatJournal>,
+    mut damage_events: MessageReader<DamageApplied>,
+    mut heal_events: MessageReader<Healed>,
+    mut death_events: MessageReader<DeathEvent>,
+    mut status_events: MessageReader<StatusEffectApplied>,
+    mut ability_events: MessageReader<AbilityResolved>,
+    participant_query: Query<(Option<&CollisionTeam>, Option<&Name>, Option<&UnitType>)>,
+    time: Res<Time>,
+) {
+    journal.tick += 1;
+    let tick = journal.tick;
+    let now = time.elapsed_seconds();
+    let entry =
+        |kind, source: Option<Entity>, target: Option<Entity>, amount, detail| JournalEntry {
+            tick,
+            time: now,
+            kind,
+            source: source.map(|source| participant(source, &participant_query)),
+            target: target.map(|target| participant(target, &participant_query)),
+            amount,
+            detail,
+        };
+
+    let mut entries = Vec::new();
+    for event in damage_events.read() {
+        entries.push(entry(
+            JournalEventKind::Damage,
+            Some(event.attacker),
+            Some(event.target),
+            event.amount,
+            format!("{:?}", event.damage_type),
+        ));
+    }
+    for event in heal_events.read() {
+        entries.push(entry(
+            JournalEventKind::Heal,
+            event.healer,
+            Some(event.target),
+            event.amount,
+            String::new(),
+        ));
+    }
+    for event in death_events.read() {
+        entries.push(entry(
+            JournalEventKind::Kill,
+            event.killer,
+            Some(event.entity),
+            1.0,
+            String::new(),
+        ));
+    }
+    for event in status_events.read() {
+        entries.push(entry(
+            JournalEventKind::Status,
+            event.effect.source,
+            Some(event.target),
+            event.effect.duration,
+            format!("{:?}", event.effect.effect_type),
+        ));
+    }
+    for event in ability_events.read() {
+        // Single-target abilities name their target
+        let target = match event.targets.as_slice() {
+            [target] => Some(*target),
+            _ => None,
+        };
+        entries.push(entry(
+            JournalEventKind::Ability,
+            Some(event.caster),
+            target,
+            event.targets.len() as f32,
+            event.ability.clone(),
+        ));
+    }
+
+    journal.entries.extend(entries);
+}
+
+/// Log the post-match summary when the game closes
+pub fn match_report_on_exit_system(
+    mut exit_events: MessageReader<AppExit>,
+    journal: Res<CombatJournal>,
+) {
+    if exit_events.read().next().is_some() && !journal.entries.is_empty() {
+        info!("{}", journal.summary());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::damage::{
+        DamageEvent, apply_damage_modifiers, check_for_deaths, process_damage_events,
+    };
+    use crate::damage_matrix::DamageMatrix;
+    use crate::status::StatusEffects;
+    use game_physics::Health;
+    use std::time::Duration;
+
+    fn unit(entity: u64, team: u32, unit_type: &str) -> Option<JournalParticipant> {
+        Some(JournalParticipant {
+            entity,
+            team: Some(team),
+            unit_type: unit_type.to_string(),
+        })
+    }
+
+    fn entry(
+        tick: u64,
+        time: f32,
+        kind: JournalEventKind,
+        source: Option<JournalParticipant>,
+        target: Option<JournalParticipant>,
+        amount: f32,
+        detail: &str,
+    ) -> JournalEntry {
+        JournalEntry {
+            tick,
+            time,
+            kind,
+            source,
+            target,
+            amount,
+            detail: detail.to_string(),
+        }
+    }
+
+    /// A short skirmish: an elder and an acolyte of team 1 kill a deep one
+    fn skirmish() -> CombatJournal {
+        let elder = || unit(1, 1, "Cultist, Elder");
+        let deep_one = || unit(2, 2, "deep_one");
+        let acolyte = || unit(3, 1, "acolyte");
+        CombatJournal {
+            tick: 5,
+            entries: vec![
+                entry(
+                    1,
+                    0.0,
+                    JournalEventKind::Damage,
+                    elder(),
+                    deep_one(),
+                    30.0,
+                    "Physical",
+                ),
+                entry(
+                    2,
+                    2.0,
+                    JournalEventKind::Damage,
+                    deep_one(),
+                    elder(),
+                    10.0,
+                    "Magic",
+                ),
+                entry(3, 3.0, JournalEventKind::Heal, acolyte(), elder(), 5.0, ""),
+                entry(
+                    4,
+                    5.0,
+                    JournalEventKind::Damage,
+                    acolyte(),
+                    deep_one(),
+                    20.0,
+                    "Physical",
+                ),
+                entry(
+                    5,
+                    10.0,
+                    JournalEventKind::Kill,
+                    elder(),
+                    deep_one(),
+                    1.0,
+                    "",
+                ),
+            ],
+        }
+    }
+
+    fn approx(a: f32, b: f32) -> bool {
+        (a - b).abs() < 1e-4
+    }
+
+    #[test]
+    fn csv_has_a_header_and_quotes_names_with_commas() {
+        let mut csv = Vec::new();
+        skirmish().write_csv(&mut csv).unwrap();
+        let csv = String::from_utf8(csv).unwrap();
+        let lines: Vec<&str> = csv.lines().collect();
+
+        assert_eq!(lines.len(), 6);
+        assert_eq!(
+            lines[0],
+            "tick,time,kind,source,source_team,source_type,target,target_team,target_type,amount,detail"
+        );
+        assert_eq!(
+            lines[1],
+            "1,0.000,Damage,1,1,\"Cultist, Elder\",2,2,deep_one,30.000,Physical"
+        );
+        assert_eq!(
+            lines[3],
+            "3,3.000,Heal,3,1,acolyte,1,1,\"Cultist, Elder\",5.000,"
+        );
+    }
+
+    #[test]
+    fn json_lines_round_trip_through_serde() {
+        let journal = skirmish();
+        let mut jsonl = Vec::new();
+        journal.write_json_lines(&mut jsonl).unwrap();
+        let jsonl = String::from_utf8(jsonl).unwrap();
+
+        let parsed: Vec<JournalEntry> = jsonl
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(parsed, journal.entries);
+    }
+
+    #[test]
+    fn summary_totals_teams_and_unit_types() {
+        let summary = skirmish().summary();
+        assert!(approx(summary.duration, 10.0));
+
+        let team_one = &summary.by_team[&1];
+        assert!(approx(team_one.damage_dealt, 50.0));
+        assert!(approx(team_one.damage_taken, 10.0));
+        assert!(approx(team_one.healing_done, 5.0));
+        assert_eq!((team_one.kills, team_one.deaths), (1, 0));
+        assert!(approx(team_one.dps, 5.0));
+        assert!(approx(team_one.efficiency, 5.0));
+
+        let team_two = &summary.by_team[&2];
+        assert!(approx(team_two.damage_dealt, 10.0));
+        assert!(approx(team_two.damage_taken, 50.0));
+        assert_eq!((team_two.kills, team_two.deaths), (0, 1));
+        assert!(approx(team_two.dps, 1.0));
+        assert!(approx(team_two.efficiency, 0.2));
+
+        // Team 1 splits into its two unit types
+        assert_eq!(summary.by_unit_type.len(), 3);
+        let elder = &summary.by_unit_type["Cultist, Elder"];
+        assert!(approx(elder.damage_dealt, 30.0));
+        assert_eq!(elder.kills, 1);
+        assert!(approx(elder.dps, 3.0));
+        assert!(approx(elder.efficiency, 3.0));
+        let acolyte = &summary.by_unit_type["acolyte"];
+        assert!(approx(acolyte.damage_dealt, 20.0));
+        assert!(approx(acolyte.healing_done, 5.0));
+        assert!(approx(acolyte.dps, 2.0));
+        // Nothing taken: efficiency falls back to damage dealt
+        assert!(approx(acolyte.efficiency, 20.0));
+        assert!(approx(summary.by_unit_type["deep_one"].damage_taken, 50.0));
+    }
+
+    #[test]
+    fn damage_and_healing_over_time_are_journaled() {
+        let mut app = App::new();
+        app.init_resource::<Time>()
+            .init_resource::<CombatJournal>()
+            .init_resource::<DamageMatrix>()
+            .add_message::<DamageEvent>()
+            .add_message::<DamageApplied>()
+            .add_message::<DeathEvent>()
+            .add_message::<Healed>()
+            .add_message::<StatusEffectApplied>()
+            .add_message::<AbilityResolved>()
+            .add_systems(
+                Update,
+                (
+                    apply_damage_modifiers,
+                    process_damage_events,
+                    check_for_deaths,
+                    combat_journal_system,
+                )
+                    .chain(),
+            );
+        let over_time = |effect_type, source| {
+            let mut effects = StatusEffects::default();
+            effects.apply(StatusEffect::new(effect_type, 5.0, Some(source)));
+            effects
+        };
+        let poisoner = app.world_mut().spawn(CollisionTeam(1)).id();
+        let victim = app
+            .world_mut()
+            .spawn((
+                CollisionTeam(2),
+                Health::new(10.0),
+                over_time(StatusEffectType::Poison(20.0), poisoner),
+            ))
+            .id();
+        let wounded = app
+            .world_mut()
+            .spawn((
+                CollisionTeam(1),
+                Health {
+                    current: 5.0,
+                    maximum: 10.0,
+                },
+                over_time(StatusEffectType::Regeneration(4.0), poisoner),
+            ))
+            .id();
+
+        for _ in 0..3 {
+            app.world_mut()
+                .resource_mut::<Time>()
+                .advance_by(Duration::from_secs_f32(0.25));
+            app.update();
+        }
+
+        let journal = app.world().resource::<CombatJournal>();
+        let records = |kind, target: Entity| -> Vec<(Option<u64>, f32)> {
+            journal
+                .entries
+                .iter()
+                .filter(|entry| {
+                    entry.kind == kind
+                        && entry.target.as_ref().map(|target| target.entity)
+                            == Some(target.to_bits())
+                })
+                .map(|entry| {
+                    (
+                        entry.source.as_ref().map(|source| source.entity),
+                        entry.amount,
+                    )
+                })
+                .collect()
+        };
+        let by_poisoner = Some(poisoner.to_bits());
+        assert_eq!(
+            records(JournalEventKind::Damage, victim),
+            vec![(by_poisoner, 5.0), (by_poisoner, 5.0)]
+        );
+        assert_eq!(
+            records(JournalEventKind::Kill, victim),
+            vec![(by_poisoner, 1.0)]
+        );
+        assert_eq!(
+            records(JournalEventKind::Heal, wounded),
+            vec![(by_poisoner, 1.0); 3]
+        );
+    }
+}
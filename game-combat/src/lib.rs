@@ -3,23 +3,35 @@
 
 use bevy::prelude::*;
 
+pub mod abilities;
 pub mod components;
 pub mod damage;
+pub mod damage_matrix;
 pub mod effects;
+pub mod journal;
 pub mod physics_integration;
 pub mod plugin;
+pub mod projectiles;
+pub mod sanity;
 pub mod states;
+pub mod status;
 pub mod systems;
 pub mod targeting;
 pub mod visuals;
 pub mod xp;
 
 // Re-export main types
+pub use abilities::*;
 pub use components::*;
 pub use damage::*;
+pub use damage_matrix::*;
 pub use effects::*;
+pub use journal::*;
 pub use plugin::CombatPlugin;
+pub use projectiles::*;
+pub use sanity::*;
 pub use states::*;
+pub use status::*;
 pub use systems::*;
 pub use targeting::*;
 pub use visuals::*;
@@ -36,6 +48,7 @@ impl Plugin for GameCombatPlugin {
             .add_plugins(DamagePlugin)
             .add_plugins(EffectsPlugin)
             .add_plugins(XPPlugin)
+            .add_plugins(CombatJournalPlugin)
             .add_plugins(CombatVisualsPlugin);
     }
 }
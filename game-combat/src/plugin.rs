@@ -3,14 +3,36 @@ use bevy::prelude::*;
 pub struct CombatPlugin;
 impl Plugin for CombatPlugin {
     fn build(&self, app: &mut App) {
+        // Attacking breaks stealth
+        app.add_message::<game_physics::RevealStealth>();
+        // Madness changes allegiance and targeting
+        app.add_message::<game_physics::MadnessChanged>();
+        app.add_message::<crate::status::ApplyStatusEffect>()
+            .add_message::<crate::status::DispelStatusEffects>()
+            .add_message::<crate::status::StatusEffectApplied>()
+            .add_message::<crate::status::StatusEffectExpired>()
+            .add_message::<crate::status::StatusEffectDispelled>();
+        // Abilities resolved by the physics layer deal damage and apply statuses here
+        app.add_message::<game_physics::AbilityResolved>();
         app.add_systems(
             Update,
             (
+                crate::abilities::ability_combat_effect_system,
+                // Status effects settle before anyone acts this frame
+                crate::status::apply_status_effects_system,
+                crate::status::dispel_status_effects_system,
+                crate::status::status_effect_system,
+                crate::status::status_effect_modifier_system,
+                crate::status::crowd_control_system,
+                crate::sanity::eldritch_effect_system,
+                crate::sanity::witnessed_death_system,
+                crate::sanity::madness_allegiance_system,
+                crate::systems::weapon_targeting_system,
                 crate::systems::combat_execution_system,
                 crate::systems::update_attack_timers,
-                crate::systems::status_effect_system,
                 crate::systems::shield_regeneration_system,
-                crate::systems::projectile_system,
+                crate::projectiles::projectile_movement_system,
+                crate::projectiles::projectile_system,
                 crate::systems::cleanup_dead_entities,
                 crate::systems::combat_log_system,
             )
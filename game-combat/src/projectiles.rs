@@ -0,0 +1,448 @@
+// Projectile flight and hits: straight, ballistic and homing shots
+use crate::components::*;
+use crate::damage::{AreaDamageEvent, DamageEvent};
+use crate::states::Health;
+use bevy::prelude::*;
+use game_physics::{
+    ColliderExtents, CollisionTeam, ContinuousCollision, GlobalSpatialGrid, SweepTargetQuery,
+    sweep_sphere,
+};
+
+/// Downward acceleration on ballistic projectiles
+pub const PROJECTILE_GRAVITY: f32 = 20.0;
+
+/// How sharply homing projectiles turn, in radians per second
+pub const HOMING_TURN_RATE: f32 = 4.0;
+
+/// Projectiles live this many times their flight time to max range
+pub const PROJECTILE_LIFETIME_FACTOR: f32 = 2.0;
+
+impl Projectile {
+    /// Projectile fired by a weapon, or `None` for weapons that hit instantly.
+    ///
+    /// Siege weapons lob shells, magic homes in and everything else flies
+    /// straight, all at the weapon's `projectile_speed`. The projectile
+    /// carries the weapon's damage type and armor penetration, and whether
+    /// the shot was a critical strike.
+    pub fn from_weapon(
+        weapon: &Weapon,
+        owner: Entity,
+        team: Option<u32>,
+        damage: f32,
+        is_critical: bool,
+    ) -> Option<Self> {
+        let speed = weapon.projectile_speed.filter(|speed| *speed > 0.0)?;
+        let motion = match weapon.weapon_type {
+            WeaponType::Siege => ProjectileMotion::Ballistic {
+                gravity: PROJECTILE_GRAVITY,
+                impact_height: 0.0,
+            },
+            WeaponType::Magic => ProjectileMotion::Homing {
+                target: None,
+                turn_rate: HOMING_TURN_RATE,
+            },
+            WeaponType::Melee | WeaponType::Ranged => ProjectileMotion::Straight,
+        };
+        let area_damage = weapon.area_of_effect.map(|radius| AreaDamage {
+            radius,
+            // Half damage at the edge of the blast
+            falloff: 0.5 / radius.max(f32::EPSILON),
+            friendly_fire: false,
+            line_of_sight: true,
+        });
+        let lifetime = weapon.range / speed * PROJECTILE_LIFETIME_FACTOR;
+
+        Some(Self {
+            owner,
+            team,
+            damage,
+            damage_type: weapon.damage_type,
+            penetration: weapon.penetration,
+            is_critical,
+            speed,
+            motion,
+            velocity: Vec3::ZERO,
+            lifetime,
+            remaining_lifetime: lifetime,
+            pierce_count: weapon.pierce,
+            area_damage,
+            hit_entities: Vec::new(),
+        })
+    }
+
+    /// Aim from `from` at a target, setting the launch velocity for the motion
+    /// model. Ballistic shots are lobbed to land on `target_position`.
+    pub fn aimed(mut self, from: Vec3, target: Entity, target_position: Vec3) -> Self {
+        let offset = target_position - from;
+        match &mut self.motion {
+            ProjectileMotion::Straight => {
+                self.velocity = offset.normalize_or(Vec3::Z) * self.speed;
+            }
+            ProjectileMotion::Ballistic {
+                gravity,
+                impact_height,
+            } => {
+                // Constant horizontal speed; vertical speed chosen to come
+                // down on the target when the horizontal distance is covered
+                let horizontal = Vec3::new(offset.x, 0.0, offset.z);
+                let flight_time = (horizontal.length() / self.speed).max(0.1);
+                let vertical =
+                    (offset.y + 0.5 * *gravity * flight_time * flight_time) / flight_time;
+                self.velocity = horizontal / flight_time + Vec3::Y * vertical;
+                *impact_height = target_position.y;
+                self.lifetime = self.lifetime.max(flight_time * PROJECTILE_LIFETIME_FACTOR);
+                self.remaining_lifetime = self.lifetime;
+            }
+            ProjectileMotion::Homing {
+                target: homing_target,
+                ..
+            } => {
+                *homing_target = Some(target);
+                self.velocity = offset.normalize_or(Vec3::Z) * self.speed;
+            }
+        }
+        self
+    }
+}
+
+/// Spawn a projectile at `position`
+pub fn spawn_projectile(commands: &mut Commands, projectile: Projectile, position: Vec3) -> Entity {
+    let transform = Transform::from_translation(position)
+        .looking_to(projectile.velocity.normalize_or(Vec3::Z), Vec3::Y);
+    commands
+        .spawn((Name::new("Projectile"), projectile, transform))
+        .id()
+}
+
+/// Move projectiles along their straight, ballistic or homing paths
+pub fn projectile_movement_system(
+    mut query: Query<(&mut Projectile, &mut Transform)>,
+    target_query: Query<&Transform, Without<Projectile>>,
+    time: Res<Time>,
+) {
+    let dt = time.delta_seconds();
+
+    for (mut projectile, mut transform) in query.iter_mut() {
+        match projectile.motion {
+            ProjectileMotion::Straight => {}
+            ProjectileMotion::Ballistic { gravity, .. } => {
+                projectile.velocity.y -= gravity * dt;
+            }
+            ProjectileMotion::Homing { target, turn_rate } => {
+                // Without a target a homing shot keeps flying straight
+                if let Some(target_transform) =
+                    target.and_then(|target| target_query.get(target).ok())
+                {
+                    let heading = projectile.velocity.normalize_or(Vec3::Z);
+                    let desired = (target_transform.translation - transform.translation)
+                        .normalize_or(heading);
+                    let angle = heading.angle_between(desired);
+                    let turn = if angle > 0.0 {
+                        (turn_rate * dt / angle).min(1.0)
+                    } else {
+                        1.0
+                    };
+                    let rotation =
+                        Quat::IDENTITY.slerp(Quat::from_rotation_arc(heading, desired), turn);
+                    projectile.velocity = rotation * heading * projectile.speed;
+                }
+            }
+        }
+
+        transform.translation += projectile.velocity * dt;
+        if projectile.velocity.length_squared() > 0.0 {
+            let forward = projectile.velocity.normalize();
+            transform.look_to(forward, Vec3::Y);
+        }
+    }
+}
+
+/// Projectile system: sweeps each projectile's path since the last frame
+/// against indexed colliders and damages hits in order along the path.
+///
+/// Projectiles pass through their owner's team and never hit the same victim
+/// twice. Ballistic shells also detonate when they come down.
+#[allow(clippy::too_many_arguments)]
+pub fn projectile_system(
+    mut commands: Commands,
+    mut query: Query<(Entity, &mut Projectile, &Transform, &ContinuousCollision)>,
+    target_query: SweepTargetQuery<Without<Projectile>>,
+    damageable_query: Query<(), With<Health>>,
+    team_query: Query<&CollisionTeam>,
+    spatial_grid: Res<GlobalSpatialGrid>,
+    extents: Option<Res<ColliderExtents>>,
+    time: Res<Time>,
+    mut damage_events: MessageWriter<DamageEvent>,
+    mut area_damage_events: MessageWriter<AreaDamageEvent>,
+) {
+    for (entity, mut projectile, transform, ccd) in query.iter_mut() {
+        projectile.remaining_lifetime -= time.delta_seconds();
+
+        if projectile.remaining_lifetime <= 0.0 {
+            commands.entity(entity).despawn();
+            continue;
+        }
+
+        let owner = projectile.owner;
+        let owner_team = projectile.team;
+        let (start, end) = ccd.swept_segment(transform.translation);
+
+        let hits = sweep_sphere(
+            &spatial_grid,
+            extents.as_deref(),
+            start,
+            end,
+            ccd.radius,
+            &target_query,
+            |target| {
+                target != owner
+                    && !projectile.hit_entities.contains(&target)
+                    && damageable_query.contains(target)
+                    && (owner_team.is_none()
+                        || team_query.get(target).ok().map(|team| team.0) != owner_team)
+            },
+        );
+
+        let mut spent = false;
+        for hit in hits {
+            projectile.hit_entities.push(hit.entity);
+
+            // Exploding projectiles hurt everything around the impact instead
+            if let Some(area) = &projectile.area_damage {
+                area_damage_events.write(AreaDamageEvent {
+                    attacker: projectile.owner,
+                    center: hit.point,
+                    amount: projectile.damage,
+                    damage_type: projectile.damage_type,
+                    penetration: projectile.penetration,
+                    is_critical: projectile.is_critical,
+                    area: area.clone(),
+                    direct_hit: Some(hit.entity),
+                });
+            } else {
+                damage_events.write(DamageEvent {
+                    attacker: projectile.owner,
+                    target: hit.entity,
+                    amount: projectile.damage,
+                    damage_type: projectile.damage_type,
+                    penetration: projectile.penetration,
+                    is_critical: projectile.is_critical,
+                });
+            }
+
+            // Destroy projectile unless it pierces
+            if projectile.pierce_count == 0 {
+                spent = true;
+                break;
+            }
+            projectile.pierce_count -= 1;
+        }
+
+        // Shells that reach the ground without hitting anyone still go off
+        if !spent
+            && let ProjectileMotion::Ballistic { impact_height, .. } = projectile.motion
+            && projectile.velocity.y < 0.0
+            && transform.translation.y <= impact_height
+        {
+            if let Some(area) = &projectile.area_damage {
+                area_damage_events.write(AreaDamageEvent {
+                    attacker: projectile.owner,
+                    center: transform.translation,
+                    amount: projectile.damage,
+                    damage_type: projectile.damage_type,
+                    penetration: projectile.penetration,
+                    is_critical: projectile.is_critical,
+                    area: area.clone(),
+                    direct_hit: None,
+                });
+            }
+            spent = true;
+        }
+
+        if spent {
+            commands.entity(entity).despawn();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use game_physics::collision::continuous_collision_tracking_system;
+    use game_physics::components::Sphere as PhysicsSphere;
+    use std::time::Duration;
+
+    /// Direct and area hits landed this test
+    #[derive(Resource, Default)]
+    struct Hits {
+        direct: Vec<DamageEvent>,
+        area: Vec<AreaDamageEvent>,
+    }
+
+    fn collect_hits(
+        mut hits: ResMut<Hits>,
+        mut damage_events: MessageReader<DamageEvent>,
+        mut area_damage_events: MessageReader<AreaDamageEvent>,
+    ) {
+        hits.direct.extend(damage_events.read().cloned());
+        hits.area.extend(area_damage_events.read().cloned());
+    }
+
+    /// Moving target for homing shots
+    #[derive(Component)]
+    struct Drift(Vec3);
+
+    fn drift_system(
+        mut query: Query<(Entity, &Drift, &mut Transform)>,
+        mut spatial_grid: ResMut<GlobalSpatialGrid>,
+        time: Res<Time>,
+    ) {
+        for (entity, drift, mut transform) in query.iter_mut() {
+            transform.translation += drift.0 * time.delta_seconds();
+            spatial_grid.grid.update(entity, transform.translation);
+        }
+    }
+
+    fn projectile_app() -> App {
+        let mut app = App::new();
+        app.init_resource::<Time>()
+            .init_resource::<GlobalSpatialGrid>()
+            .init_resource::<Hits>()
+            .add_message::<DamageEvent>()
+            .add_message::<AreaDamageEvent>()
+            .add_systems(
+                Update,
+                (
+                    drift_system,
+                    projectile_movement_system,
+                    projectile_system,
+                    continuous_collision_tracking_system,
+                    collect_hits,
+                )
+                    .chain(),
+            );
+        app
+    }
+
+    fn step(app: &mut App, seconds: f32) {
+        app.world_mut()
+            .resource_mut::<Time>()
+            .advance_by(Duration::from_secs_f32(seconds));
+        app.update();
+    }
+
+    /// Spawn a damageable unit at `position` and index it in the spatial grid
+    fn unit(app: &mut App, position: Vec3, team: u32, bundle: impl Bundle) -> Entity {
+        let entity = app
+            .world_mut()
+            .spawn((
+                Transform::from_translation(position),
+                Health::new(100.0),
+                CollisionTeam(team),
+                bundle,
+            ))
+            .id();
+        app.world_mut()
+            .resource_mut::<GlobalSpatialGrid>()
+            .grid
+            .insert(entity, position);
+        entity
+    }
+
+    fn fire(app: &mut App, projectile: Projectile, position: Vec3) -> Entity {
+        app.world_mut()
+            .spawn((projectile, Transform::from_translation(position)))
+            .id()
+    }
+
+    #[test]
+    fn piercing_projectiles_hit_each_enemy_once_and_pass_through_allies() {
+        let mut app = projectile_app();
+        let owner = unit(&mut app, Vec3::ZERO, 1, ());
+        let near = unit(&mut app, Vec3::new(0.0, 0.0, 5.0), 2, ());
+        let ally = unit(&mut app, Vec3::new(0.0, 0.0, 8.0), 1, ());
+        // Big enough that the projectile overlaps it for several frames
+        let big = unit(
+            &mut app,
+            Vec3::new(0.0, 0.0, 12.0),
+            2,
+            PhysicsSphere::new(3.0),
+        );
+        let far = unit(&mut app, Vec3::new(0.0, 0.0, 20.0), 2, ());
+
+        let weapon = Weapon::new(WeaponType::Ranged, 10.0, 1.0, 30.0).with_pierce(5);
+        let projectile = Projectile::from_weapon(&weapon, owner, Some(1), 10.0, true)
+            .unwrap()
+            .aimed(Vec3::ZERO, far, Vec3::new(0.0, 0.0, 20.0));
+        assert_eq!(projectile.pierce_count, 5);
+        let shot = fire(&mut app, projectile, Vec3::ZERO);
+
+        for _ in 0..20 {
+            step(&mut app, 0.05);
+        }
+
+        let hits = &app.world().resource::<Hits>().direct;
+        let victims: Vec<Entity> = hits.iter().map(|hit| hit.target).collect();
+        assert_eq!(victims, vec![near, big, far]);
+        assert!(hits.iter().all(|hit| hit.is_critical));
+        assert!(!victims.contains(&ally) && !victims.contains(&owner));
+        assert_eq!(
+            app.world().get::<Projectile>(shot).unwrap().hit_entities,
+            vec![near, big, far]
+        );
+    }
+
+    #[test]
+    fn ballistic_shells_land_on_their_target_point() {
+        let mut app = projectile_app();
+        let owner = unit(&mut app, Vec3::ZERO, 1, ());
+        let target_point = Vec3::new(20.0, 0.0, 5.0);
+
+        let weapon = Weapon::new(WeaponType::Siege, 50.0, 3.0, 30.0).with_area(3.0);
+        let projectile = Projectile::from_weapon(&weapon, owner, Some(1), 50.0, false)
+            .unwrap()
+            .aimed(Vec3::ZERO, owner, target_point);
+        let shot = fire(&mut app, projectile, Vec3::ZERO);
+
+        for _ in 0..300 {
+            step(&mut app, 0.01);
+            if !app.world().resource::<Hits>().area.is_empty() {
+                break;
+            }
+        }
+
+        let blasts = &app.world().resource::<Hits>().area;
+        assert_eq!(blasts.len(), 1);
+        assert_eq!(blasts[0].direct_hit, None);
+        assert!(blasts[0].center.distance(target_point) < 0.5);
+        assert!(app.world().get_entity(shot).is_err());
+    }
+
+    #[test]
+    fn homing_projectiles_converge_on_moving_targets() {
+        let mut app = projectile_app();
+        let owner = unit(&mut app, Vec3::ZERO, 1, ());
+        let start = Vec3::new(0.0, 0.0, 15.0);
+        let target = unit(&mut app, start, 2, Drift(Vec3::new(8.0, 0.0, 0.0)));
+
+        let weapon = Weapon::new(WeaponType::Magic, 20.0, 1.0, 30.0);
+        let projectile = Projectile::from_weapon(&weapon, owner, Some(1), 20.0, false)
+            .unwrap()
+            .aimed(Vec3::ZERO, target, start);
+        fire(&mut app, projectile, Vec3::ZERO);
+
+        for _ in 0..150 {
+            step(&mut app, 0.02);
+            if !app.world().resource::<Hits>().direct.is_empty() {
+                break;
+            }
+        }
+
+        let hits = &app.world().resource::<Hits>().direct;
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].target, target);
+        // The target had moved well off the original line of fire
+        let target_position = app.world().get::<Transform>(target).unwrap().translation;
+        assert!(target_position.x > 4.0);
+    }
+}
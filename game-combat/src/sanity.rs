@@ -0,0 +1,129 @@
+// Sanity drain from eldritch effects and witnessed deaths, and madness in combat
+use crate::components::*;
+use crate::damage::DeathEvent;
+use crate::status::StatusEffects;
+use crate::targeting::{Targetable, TargetingSystem};
+use bevy::prelude::*;
+use game_physics::{
+    CollisionTeam, GlobalSpatialGrid, MadnessBehaviour, MadnessChanged, Sanity, SanitySource,
+};
+
+/// Sanity lost per second to `VoidTouch`
+pub const VOID_TOUCH_DRAIN: f32 = 3.0;
+
+/// Sanity lost per second to `DeepCurse`
+pub const DEEP_CURSE_DRAIN: f32 = 5.0;
+
+/// How far away a death can be seen and still shake a unit
+pub const WITNESS_RADIUS: f32 = 12.0;
+
+/// Sanity lost watching an ally die
+pub const ALLY_DEATH_SANITY_LOSS: f32 = 10.0;
+
+/// Sanity lost watching anyone else die
+pub const DEATH_SANITY_LOSS: f32 = 4.0;
+
+/// Where a Lovecraftian status effect drains sanity from, and how much per second
+pub fn eldritch_drain(effect: &StatusEffect) -> Option<(SanitySource, f32)> {
+    let stacks = effect.stacks.max(1) as f32;
+    match effect.effect_type {
+        StatusEffectType::Madness(per_second) => {
+            Some((SanitySource::Eldritch, per_second * stacks))
+        }
+        StatusEffectType::Corruption(per_second) => {
+            Some((SanitySource::Corruption, per_second * stacks))
+        }
+        StatusEffectType::VoidTouch => Some((SanitySource::Eldritch, VOID_TOUCH_DRAIN)),
+        StatusEffectType::DeepCurse => Some((SanitySource::Eldritch, DEEP_CURSE_DRAIN)),
+        _ => None,
+    }
+}
+
+/// System to drain sanity from units afflicted by eldritch status effects
+pub fn eldritch_effect_system(
+    mut query: Query<(&StatusEffects, &mut Sanity)>,
+    team_query: Query<&CollisionTeam>,
+    time: Res<Time>,
+) {
+    let dt = time.delta_seconds();
+
+    for (effects, mut sanity) in query.iter_mut() {
+        for effect in effects.iter() {
+            let Some((source, per_second)) = eldritch_drain(effect) else {
+                continue;
+            };
+            let tormentor = effect
+                .source
+                .and_then(|source| team_query.get(source).ok())
+                .map(|team| team.0);
+            sanity.drain(source, per_second * dt, tormentor);
+        }
+    }
+}
+
+/// System to shake the sanity of everyone who sees a unit die.
+///
+/// Allies of the fallen take it harder and blame the killer's team.
+pub fn witnessed_death_system(
+    mut death_events: MessageReader<DeathEvent>,
+    transform_query: Query<&Transform>,
+    team_query: Query<&CollisionTeam>,
+    mut witness_query: Query<(&Transform, &mut Sanity, Option<&CollisionTeam>)>,
+    spatial_grid: Res<GlobalSpatialGrid>,
+) {
+    for event in death_events.read() {
+        let Ok(position) = transform_query.get(event.entity).map(|t| t.translation) else {
+            continue;
+        };
+        let fallen_team = team_query.get(event.entity).ok().map(|team| team.0);
+        let killer_team = event
+            .killer
+            .and_then(|killer| team_query.get(killer).ok())
+            .map(|team| team.0);
+
+        for witness in spatial_grid.grid.query_radius(position, WITNESS_RADIUS) {
+            if witness == event.entity {
+                continue;
+            }
+            let Ok((transform, mut sanity, team)) = witness_query.get_mut(witness) else {
+                continue;
+            };
+            if transform.translation.distance(position) > WITNESS_RADIUS {
+                continue;
+            }
+
+            let ally = fallen_team.is_some() && fallen_team == team.map(|team| team.0);
+            if ally {
+                sanity.drain(SanitySource::Death, ALLY_DEATH_SANITY_LOSS, killer_team);
+            } else {
+                sanity.drain(SanitySource::Death, DEATH_SANITY_LOSS, None);
+            }
+        }
+    }
+}
+
+/// System to move defectors between teams and make mad units rethink targets
+pub fn madness_allegiance_system(
+    mut madness_events: MessageReader<MadnessChanged>,
+    mut query: Query<(Option<&mut Targetable>, Option<&mut TargetingSystem>)>,
+) {
+    for event in madness_events.read() {
+        let Ok((targetable, targeting)) = query.get_mut(event.entity) else {
+            continue;
+        };
+
+        let team = match (event.current, event.previous) {
+            (Some(MadnessBehaviour::Defection { to, .. }), _) => Some(to),
+            (_, Some(MadnessBehaviour::Defection { from, .. })) => Some(from),
+            _ => None,
+        };
+        if let (Some(team), Some(mut targetable)) = (team, targetable) {
+            targetable.team_id = team;
+        }
+
+        if let Some(mut targeting) = targeting {
+            targeting.current_target = None;
+            targeting.target_lock_time = 0.0;
+        }
+    }
+}
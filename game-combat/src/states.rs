@@ -34,13 +34,5 @@ impl AttackTimer {
         self.timer.tick(delta);
     }
 }
-#[derive(Component)]
-pub struct Health {
-    pub current: f32,
-    pub maximum: f32,
-}
-impl Health {
-    pub fn percentage(&self) -> f32 {
-        self.current / self.maximum
-    }
-}
+/// Shared with units and AI so damage and health bars use the same pool
+pub use game_physics::Health;
@@ -0,0 +1,746 @@
+// Status effect container, stacking rules and dispels
+use crate::components::*;
+use bevy::prelude::*;
+use game_physics::{CrowdControl, ModifierOp, ModifierSource, StatKind, Stats};
+use serde::{Deserialize, Serialize};
+use std::mem::discriminant;
+
+/// How a new effect combines with effects of the same type already present
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum StackingPolicy {
+    /// Keep one instance, keeping the longer of the two durations and the
+    /// stronger of the two magnitudes
+    Refresh,
+    /// Keep one instance, add a stack up to `max_stacks`, restart its duration
+    /// and keep the stronger magnitude
+    Intensity { max_stacks: u32 },
+    /// Every application is its own instance with its own duration
+    Independent,
+    /// Keep only the strongest instance; equal strength refreshes it
+    StrongestWins,
+}
+
+/// Groups of effects that dispels remove together
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum DispelCategory {
+    Buff,
+    Debuff,
+    Magic,
+    Poison,
+    Curse,
+    CrowdControl,
+}
+
+impl StatusEffectType {
+    pub fn stacking_policy(&self) -> StackingPolicy {
+        match self {
+            StatusEffectType::AttackSpeed(_)
+            | StatusEffectType::MovementSpeed(_)
+            | StatusEffectType::DamageBoost(_)
+            | StatusEffectType::ArmorBoost(_)
+            | StatusEffectType::Slow(_) => StackingPolicy::StrongestWins,
+            StatusEffectType::Regeneration(_) => StackingPolicy::Independent,
+            StatusEffectType::Poison(_) | StatusEffectType::Corruption(_) => {
+                StackingPolicy::Intensity { max_stacks: 5 }
+            }
+            StatusEffectType::Madness(_) => StackingPolicy::Intensity { max_stacks: 10 },
+            StatusEffectType::Stun
+            | StatusEffectType::Silence
+            | StatusEffectType::Blind
+            | StatusEffectType::Burn(_)
+            | StatusEffectType::Freeze
+            | StatusEffectType::VoidTouch
+            | StatusEffectType::DeepCurse => StackingPolicy::Refresh,
+        }
+    }
+
+    pub fn dispel_categories(&self) -> &'static [DispelCategory] {
+        use DispelCategory::*;
+        match self {
+            StatusEffectType::AttackSpeed(_)
+            | StatusEffectType::MovementSpeed(_)
+            | StatusEffectType::DamageBoost(_)
+            | StatusEffectType::ArmorBoost(_)
+            | StatusEffectType::Regeneration(_) => &[Buff, Magic],
+            StatusEffectType::Slow(_) | StatusEffectType::Burn(_) => &[Debuff, Magic],
+            StatusEffectType::Stun | StatusEffectType::Freeze => &[Debuff, CrowdControl],
+            StatusEffectType::Silence | StatusEffectType::Blind => &[Debuff, CrowdControl, Magic],
+            StatusEffectType::Poison(_) => &[Debuff, Poison],
+            StatusEffectType::Madness(_)
+            | StatusEffectType::Corruption(_)
+            | StatusEffectType::VoidTouch
+            | StatusEffectType::DeepCurse => &[Debuff, Curse],
+        }
+    }
+
+    pub fn is_debuff(&self) -> bool {
+        self.dispel_categories().contains(&DispelCategory::Debuff)
+    }
+
+    /// Strength used by `StrongestWins`; effects without a value count as 1
+    pub fn magnitude(&self) -> f32 {
+        match self {
+            StatusEffectType::AttackSpeed(value)
+            | StatusEffectType::MovementSpeed(value)
+            | StatusEffectType::DamageBoost(value)
+            | StatusEffectType::ArmorBoost(value)
+            | StatusEffectType::Regeneration(value)
+            | StatusEffectType::Slow(value)
+            | StatusEffectType::Poison(value)
+            | StatusEffectType::Burn(value)
+            | StatusEffectType::Madness(value)
+            | StatusEffectType::Corruption(value) => *value,
+            _ => 1.0,
+        }
+    }
+
+    /// Same effect type, ignoring its value
+    pub fn same_kind(&self, other: &StatusEffectType) -> bool {
+        discriminant(self) == discriminant(other)
+    }
+}
+
+/// What happened when an effect was applied
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ApplyOutcome {
+    /// A new instance was added
+    Added,
+    /// An existing instance had its duration restarted
+    Refreshed,
+    /// An existing instance gained a stack (new stack count)
+    Stacked(u32),
+    /// A weaker instance was replaced by a stronger one
+    Replaced,
+    /// A stronger instance is already active
+    Ignored,
+}
+
+/// Every status effect currently on an entity
+#[derive(Component, Clone, Debug, Default)]
+pub struct StatusEffects {
+    effects: Vec<StatusEffect>,
+}
+
+impl StatusEffects {
+    pub fn iter(&self) -> impl Iterator<Item = &StatusEffect> {
+        self.effects.iter()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.effects.is_empty()
+    }
+
+    /// Whether any effect of the same type as `effect_type` is active
+    pub fn has(&self, effect_type: &StatusEffectType) -> bool {
+        self.effects
+            .iter()
+            .any(|effect| effect.effect_type.same_kind(effect_type))
+    }
+
+    /// Add an effect following its type's stacking policy
+    pub fn apply(&mut self, effect: StatusEffect) -> ApplyOutcome {
+        let policy = effect.effect_type.stacking_policy();
+        let existing = self
+            .effects
+            .iter_mut()
+            .find(|active| active.effect_type.same_kind(&effect.effect_type));
+
+        let Some(existing) = existing.filter(|_| policy != StackingPolicy::Independent) else {
+            self.effects.push(effect);
+            return ApplyOutcome::Added;
+        };
+
+        match policy {
+            StackingPolicy::Refresh => {
+                keep_stronger(existing, &effect);
+                existing.duration = effect.duration;
+                existing.remaining = existing.remaining.max(effect.duration);
+                existing.source = effect.source;
+                ApplyOutcome::Refreshed
+            }
+            StackingPolicy::Intensity { max_stacks } => {
+                keep_stronger(existing, &effect);
+                existing.stacks = (existing.stacks + effect.stacks).min(max_stacks);
+                existing.duration = effect.duration;
+                existing.remaining = effect.duration;
+                existing.source = effect.source;
+                ApplyOutcome::Stacked(existing.stacks)
+            }
+            StackingPolicy::StrongestWins => {
+                let current = existing.effect_type.magnitude();
+                let incoming = effect.effect_type.magnitude();
+                if incoming > current {
+                    *existing = effect;
+                    ApplyOutcome::Replaced
+                } else if incoming == current {
+                    existing.remaining = existing.remaining.max(effect.duration);
+                    ApplyOutcome::Refreshed
+                } else {
+                    ApplyOutcome::Ignored
+                }
+            }
+            StackingPolicy::Independent => unreachable!("independent effects are always added"),
+        }
+    }
+
+    /// Count down durations and take out expired effects
+    pub fn tick(&mut self, delta: f32) -> Vec<StatusEffect> {
+        for effect in &mut self.effects {
+            effect.remaining -= delta;
+        }
+        let (expired, active) = self
+            .effects
+            .drain(..)
+            .partition(|effect| effect.remaining <= 0.0);
+        self.effects = active;
+        expired
+    }
+
+    /// Remove up to `max_effects` effects in `category`, oldest first
+    pub fn dispel(
+        &mut self,
+        category: DispelCategory,
+        max_effects: Option<usize>,
+    ) -> Vec<StatusEffect> {
+        let mut budget = max_effects.unwrap_or(usize::MAX);
+        let mut dispelled = Vec::new();
+        self.effects.retain(|effect| {
+            if budget > 0 && effect.effect_type.dispel_categories().contains(&category) {
+                budget -= 1;
+                dispelled.push(effect.clone());
+                false
+            } else {
+                true
+            }
+        });
+        dispelled
+    }
+}
+
+/// Take the incoming effect's magnitude if it is stronger
+fn keep_stronger(existing: &mut StatusEffect, incoming: &StatusEffect) {
+    if incoming.effect_type.magnitude() > existing.effect_type.magnitude() {
+        existing.effect_type = incoming.effect_type.clone();
+    }
+}
+
+// ==============================================================================
+// MESSAGES
+// ==============================================================================
+
+/// Request to put a status effect on an entity
+#[derive(Event, Clone, Debug)]
+pub struct ApplyStatusEffect {
+    pub target: Entity,
+    pub effect: StatusEffect,
+}
+
+/// Request to strip effects of a category from an entity
+#[derive(Event, Clone, Debug)]
+pub struct DispelStatusEffects {
+    pub target: Entity,
+    pub category: DispelCategory,
+    pub source: Option<Entity>,
+    /// Remove at most this many effects; `None` removes them all
+    pub max_effects: Option<usize>,
+}
+
+/// An effect landed (or was refreshed/stacked) on an entity
+#[derive(Event, Clone, Debug)]
+pub struct StatusEffectApplied {
+    pub target: Entity,
+    /// The effect as it now stands on the target
+    pub effect: StatusEffect,
+    pub outcome: ApplyOutcome,
+}
+
+/// An effect ran out
+#[derive(Event, Clone, Debug)]
+pub struct StatusEffectExpired {
+    pub target: Entity,
+    pub effect: StatusEffect,
+}
+
+/// An effect was removed by a dispel
+#[derive(Event, Clone, Debug)]
+pub struct StatusEffectDispelled {
+    pub target: Entity,
+    pub effect: StatusEffect,
+    pub dispeller: Option<Entity>,
+}
+
+// ==============================================================================
+// SYSTEMS
+// ==============================================================================
+
+/// System to apply requested status effects, creating containers as needed
+pub fn apply_status_effects_system(
+    mut commands: Commands,
+    mut requests: MessageReader<ApplyStatusEffect>,
+    mut query: Query<&mut StatusEffects>,
+    mut applied_events: MessageWriter<StatusEffectApplied>,
+) {
+    // Containers created this frame, inserted once all requests are read
+    let mut new_containers: Vec<(Entity, StatusEffects)> = Vec::new();
+
+    for request in requests.read() {
+        let effect_type = request.effect.effect_type.clone();
+        let (outcome, current) = if let Ok(mut effects) = query.get_mut(request.target) {
+            let outcome = effects.apply(request.effect.clone());
+            (outcome, find_latest(&effects, &effect_type))
+        } else {
+            let index = match new_containers
+                .iter()
+                .position(|(entity, _)| *entity == request.target)
+            {
+                Some(index) => index,
+                None => {
+                    new_containers.push((request.target, StatusEffects::default()));
+                    new_containers.len() - 1
+                }
+            };
+            let effects = &mut new_containers[index].1;
+            let outcome = effects.apply(request.effect.clone());
+            (outcome, find_latest(effects, &effect_type))
+        };
+
+        if outcome == ApplyOutcome::Ignored {
+            continue;
+        }
+        if let Some(effect) = current {
+            applied_events.write(StatusEffectApplied {
+                target: request.target,
+                effect,
+                outcome,
+            });
+        }
+    }
+
+    for (entity, effects) in new_containers {
+        commands.entity(entity).try_insert(effects);
+    }
+}
+
+fn find_latest(effects: &StatusEffects, effect_type: &StatusEffectType) -> Option<StatusEffect> {
+    effects
+        .iter()
+        .filter(|effect| effect.effect_type.same_kind(effect_type))
+        .last()
+        .cloned()
+}
+
+/// System to handle status effect durations.
+///
+/// Ticking alone doesn't mark `StatusEffects` changed, so stats and crowd
+/// control are only rebuilt when an effect expires.
+pub fn status_effect_system(
+    mut query: Query<(Entity, &mut StatusEffects)>,
+    mut expired_events: MessageWriter<StatusEffectExpired>,
+    time: Res<Time>,
+) {
+    let dt = time.delta_seconds();
+
+    for (entity, mut effects) in query.iter_mut() {
+        if effects.is_empty() {
+            continue;
+        }
+
+        let expired = effects.bypass_change_detection().tick(dt);
+        if !expired.is_empty() {
+            effects.set_changed();
+        }
+        for effect in expired {
+            expired_events.write(StatusEffectExpired {
+                target: entity,
+                effect,
+            });
+        }
+    }
+}
+
+/// System to process dispel requests
+pub fn dispel_status_effects_system(
+    mut requests: MessageReader<DispelStatusEffects>,
+    mut query: Query<&mut StatusEffects>,
+    mut dispelled_events: MessageWriter<StatusEffectDispelled>,
+) {
+    for request in requests.read() {
+        let Ok(mut effects) = query.get_mut(request.target) else {
+            continue;
+        };
+
+        for effect in effects.dispel(request.category, request.max_effects) {
+            dispelled_events.write(StatusEffectDispelled {
+                target: request.target,
+                effect,
+                dispeller: request.source,
+            });
+        }
+    }
+}
+
+/// Stat modifiers granted by a status effect
+pub fn status_effect_modifiers(status: &StatusEffect) -> Vec<(StatKind, ModifierOp)> {
+    let stacks = status.stacks.max(1) as f32;
+    match &status.effect_type {
+        StatusEffectType::AttackSpeed(bonus) => vec![(
+            StatKind::AttackSpeed,
+            ModifierOp::Multiply(1.0 + bonus * stacks),
+        )],
+        StatusEffectType::MovementSpeed(bonus) => vec![(
+            StatKind::MovementSpeed,
+            ModifierOp::Multiply(1.0 + bonus * stacks),
+        )],
+        StatusEffectType::DamageBoost(bonus) => vec![(
+            StatKind::AttackDamage,
+            ModifierOp::Multiply(1.0 + bonus * stacks),
+        )],
+        StatusEffectType::ArmorBoost(amount) => {
+            vec![(StatKind::Armor, ModifierOp::Add(amount * stacks))]
+        }
+        StatusEffectType::Slow(amount) => vec![(
+            StatKind::MovementSpeed,
+            ModifierOp::Multiply((1.0 - amount * stacks).max(0.0)),
+        )],
+        _ => Vec::new(),
+    }
+}
+
+/// System to feed all active status effects into the stat pipeline
+pub fn status_effect_modifier_system(
+    mut query: Query<(&StatusEffects, &mut Stats), Changed<StatusEffects>>,
+    mut removed: RemovedComponents<StatusEffects>,
+    mut stats_query: Query<&mut Stats, Without<StatusEffects>>,
+) {
+    for (effects, mut stats) in query.iter_mut() {
+        stats.replace_source(
+            ModifierSource::StatusEffect,
+            effects.iter().flat_map(status_effect_modifiers),
+            None,
+        );
+    }
+
+    for entity in removed.read() {
+        if let Ok(mut stats) = stats_query.get_mut(entity) {
+            stats.remove_source(ModifierSource::StatusEffect);
+        }
+    }
+}
+
+/// Crowd control imposed by a set of status effects
+pub fn crowd_control_from(effects: &StatusEffects) -> CrowdControl {
+    let mut control = CrowdControl::default();
+    for effect in effects.iter() {
+        match effect.effect_type {
+            StatusEffectType::Stun | StatusEffectType::Freeze => control.stunned = true,
+            StatusEffectType::Silence => control.silenced = true,
+            StatusEffectType::Blind => control.blinded = true,
+            _ => {}
+        }
+    }
+    control
+}
+
+/// System to keep crowd control flags in line with status effects
+pub fn crowd_control_system(
+    mut commands: Commands,
+    query: Query<(Entity, &StatusEffects, Option<&CrowdControl>), Changed<StatusEffects>>,
+    mut removed: RemovedComponents<StatusEffects>,
+    mut control_query: Query<&mut CrowdControl, Without<StatusEffects>>,
+) {
+    for (entity, effects, current) in query.iter() {
+        let control = crowd_control_from(effects);
+        if current != Some(&control) {
+            commands.entity(entity).try_insert(control);
+        }
+    }
+
+    for entity in removed.read() {
+        if let Ok(mut control) = control_query.get_mut(entity) {
+            *control = CrowdControl::default();
+        }
+    }
+}
+
+impl bevy::prelude::Message for ApplyStatusEffect {}
+impl bevy::prelude::Message for DispelStatusEffects {}
+impl bevy::prelude::Message for StatusEffectApplied {}
+impl bevy::prelude::Message for StatusEffectExpired {}
+impl bevy::prelude::Message for StatusEffectDispelled {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::damage::DamageEvent;
+    use crate::states::CombatState;
+    use crate::systems::combat_execution_system;
+    use crate::targeting::TargetingSystem;
+    use game_physics::movement::pathfinding_movement_system;
+    use game_physics::{MovementController, RevealStealth};
+    use std::time::Duration;
+
+    #[derive(Resource, Default)]
+    struct Hits(usize);
+
+    fn count_hits(mut hits: ResMut<Hits>, mut damage_events: MessageReader<DamageEvent>) {
+        hits.0 += damage_events.read().count();
+    }
+
+    fn step(app: &mut App, seconds: f32) {
+        app.world_mut()
+            .resource_mut::<Time>()
+            .advance_by(Duration::from_secs_f32(seconds));
+        app.update();
+    }
+
+    #[test]
+    fn stunned_unit_neither_moves_nor_attacks() {
+        let mut app = App::new();
+        app.init_resource::<Time>()
+            .init_resource::<Hits>()
+            .add_message::<ApplyStatusEffect>()
+            .add_message::<StatusEffectApplied>()
+            .add_message::<StatusEffectExpired>()
+            .add_message::<DamageEvent>()
+            .add_message::<RevealStealth>()
+            .add_systems(
+                Update,
+                (
+                    apply_status_effects_system,
+                    status_effect_system,
+                    status_effect_modifier_system,
+                    crowd_control_system,
+                    combat_execution_system,
+                    pathfinding_movement_system,
+                    count_hits,
+                )
+                    .chain(),
+            );
+
+        let target = app
+            .world_mut()
+            .spawn(Transform::from_xyz(2.0, 0.0, 0.0))
+            .id();
+        let attacker = app
+            .world_mut()
+            .spawn((
+                Transform::default(),
+                MovementController {
+                    target_position: Some(Vec3::new(0.0, 0.0, 10.0)),
+                    ..default()
+                },
+                Stats::new()
+                    .with(StatKind::AttackDamage, 10.0)
+                    .with(StatKind::AttackSpeed, 10.0),
+                AttackCooldown::new(10.0),
+                CombatState::Attacking(target),
+                TargetingSystem {
+                    range: 5.0,
+                    current_target: Some(target),
+                    ..default()
+                },
+            ))
+            .id();
+
+        app.world_mut().write_message(ApplyStatusEffect {
+            target: attacker,
+            effect: StatusEffect::new(StatusEffectType::Stun, 1.0, None),
+        });
+        for _ in 0..5 {
+            step(&mut app, 0.1);
+        }
+
+        let position = app.world().get::<Transform>(attacker).unwrap().translation;
+        assert_eq!(position, Vec3::ZERO);
+        assert_eq!(app.world().resource::<Hits>().0, 0);
+        assert!(app.world().get::<CrowdControl>(attacker).unwrap().stunned);
+
+        // Once the stun wears off the unit carries on with both
+        for _ in 0..10 {
+            step(&mut app, 0.1);
+        }
+        assert!(!app.world().get::<CrowdControl>(attacker).unwrap().stunned);
+        assert!(
+            app.world()
+                .get::<Transform>(attacker)
+                .unwrap()
+                .translation
+                .z
+                > 0.0
+        );
+        assert!(app.world().resource::<Hits>().0 > 0);
+    }
+    /// Frames in which a unit's status effects changed
+    #[derive(Resource, Default)]
+    struct EffectChanges(usize);
+
+    fn count_effect_changes(
+        mut changes: ResMut<EffectChanges>,
+        query: Query<(), Changed<StatusEffects>>,
+    ) {
+        if !query.is_empty() {
+            changes.0 += 1;
+        }
+    }
+
+    #[test]
+    fn ticking_durations_only_marks_effects_changed_on_expiry() {
+        let mut app = App::new();
+        app.init_resource::<Time>()
+            .init_resource::<EffectChanges>()
+            .add_message::<ApplyStatusEffect>()
+            .add_message::<StatusEffectApplied>()
+            .add_message::<StatusEffectExpired>()
+            .add_systems(
+                Update,
+                (
+                    apply_status_effects_system,
+                    status_effect_system,
+                    count_effect_changes,
+                )
+                    .chain(),
+            );
+        let unit = app.world_mut().spawn(Stats::new()).id();
+
+        app.world_mut().write_message(ApplyStatusEffect {
+            target: unit,
+            effect: StatusEffect::new(StatusEffectType::DamageBoost(0.5), 1.0, None),
+        });
+        app.update();
+        for _ in 0..5 {
+            step(&mut app, 0.1);
+        }
+        // Applied once; the following ticks go unnoticed
+        assert_eq!(app.world().resource::<EffectChanges>().0, 1);
+
+        for _ in 0..6 {
+            step(&mut app, 0.1);
+        }
+        assert!(app.world().get::<StatusEffects>(unit).unwrap().is_empty());
+        assert_eq!(app.world().resource::<EffectChanges>().0, 2);
+    }
+
+    fn effect(effect_type: StatusEffectType, duration: f32) -> StatusEffect {
+        StatusEffect::new(effect_type, duration, None)
+    }
+
+    #[test]
+    fn intensity_stacks_up_to_the_cap() {
+        let mut effects = StatusEffects::default();
+        assert_eq!(
+            effects.apply(effect(StatusEffectType::Poison(2.0), 4.0)),
+            ApplyOutcome::Added
+        );
+        for stacks in 2..=5 {
+            assert_eq!(
+                effects.apply(effect(StatusEffectType::Poison(2.0), 4.0)),
+                ApplyOutcome::Stacked(stacks)
+            );
+        }
+        assert_eq!(
+            effects.apply(effect(StatusEffectType::Poison(1.0), 4.0)),
+            ApplyOutcome::Stacked(5)
+        );
+
+        let poison = effects.iter().next().unwrap();
+        assert_eq!(effects.iter().count(), 1);
+        assert_eq!(poison.stacks, 5);
+        // A weaker application adds to the stack but doesn't weaken it
+        assert_eq!(poison.effect_type.magnitude(), 2.0);
+
+        effects.apply(effect(StatusEffectType::Poison(3.0), 4.0));
+        assert_eq!(effects.iter().next().unwrap().effect_type.magnitude(), 3.0);
+    }
+
+    #[test]
+    fn refresh_resets_duration_and_keeps_the_stronger_magnitude() {
+        let mut effects = StatusEffects::default();
+        effects.apply(effect(StatusEffectType::Burn(5.0), 3.0));
+        effects.tick(2.0);
+        assert_eq!(effects.iter().next().unwrap().remaining, 1.0);
+
+        assert_eq!(
+            effects.apply(effect(StatusEffectType::Burn(2.0), 3.0)),
+            ApplyOutcome::Refreshed
+        );
+        let burn = effects.iter().next().unwrap();
+        assert_eq!(burn.remaining, 3.0);
+        assert_eq!(burn.effect_type.magnitude(), 5.0);
+
+        effects.apply(effect(StatusEffectType::Burn(8.0), 3.0));
+        assert_eq!(effects.iter().next().unwrap().effect_type.magnitude(), 8.0);
+        assert_eq!(effects.iter().count(), 1);
+    }
+
+    #[test]
+    fn strongest_wins_replaces_weaker_and_ignores_weaker() {
+        let mut effects = StatusEffects::default();
+        effects.apply(effect(StatusEffectType::Slow(0.2), 5.0));
+
+        assert_eq!(
+            effects.apply(effect(StatusEffectType::Slow(0.5), 2.0)),
+            ApplyOutcome::Replaced
+        );
+        let slow = effects.iter().next().unwrap();
+        assert_eq!(slow.effect_type.magnitude(), 0.5);
+        assert_eq!(slow.remaining, 2.0);
+
+        assert_eq!(
+            effects.apply(effect(StatusEffectType::Slow(0.3), 10.0)),
+            ApplyOutcome::Ignored
+        );
+        let slow = effects.iter().next().unwrap();
+        assert_eq!(slow.effect_type.magnitude(), 0.5);
+        assert_eq!(slow.remaining, 2.0);
+        assert_eq!(effects.iter().count(), 1);
+    }
+
+    #[test]
+    fn independent_instances_expire_separately() {
+        let mut effects = StatusEffects::default();
+        effects.apply(effect(StatusEffectType::Regeneration(4.0), 1.0));
+        assert_eq!(
+            effects.apply(effect(StatusEffectType::Regeneration(4.0), 3.0)),
+            ApplyOutcome::Added
+        );
+        assert_eq!(effects.iter().count(), 2);
+
+        let expired = effects.tick(1.5);
+        assert_eq!(expired.len(), 1);
+        assert_eq!(expired[0].duration, 1.0);
+        assert_eq!(effects.iter().count(), 1);
+
+        assert_eq!(effects.tick(2.0).len(), 1);
+        assert!(effects.is_empty());
+    }
+
+    #[test]
+    fn dispel_respects_category_and_count() {
+        let mut effects = StatusEffects::default();
+        effects.apply(effect(StatusEffectType::Slow(0.3), 5.0));
+        effects.apply(effect(StatusEffectType::Poison(2.0), 5.0));
+        effects.apply(effect(StatusEffectType::Stun, 5.0));
+        effects.apply(effect(StatusEffectType::DamageBoost(10.0), 5.0));
+
+        // Only the first matching effect goes when the count is limited
+        let dispelled = effects.dispel(DispelCategory::Debuff, Some(1));
+        assert_eq!(dispelled.len(), 1);
+        assert!(
+            dispelled[0]
+                .effect_type
+                .same_kind(&StatusEffectType::Slow(0.0))
+        );
+
+        let dispelled = effects.dispel(DispelCategory::Poison, None);
+        assert_eq!(dispelled.len(), 1);
+        assert!(effects.has(&StatusEffectType::Stun));
+
+        let dispelled = effects.dispel(DispelCategory::Debuff, None);
+        assert_eq!(dispelled.len(), 1);
+        assert!(!effects.has(&StatusEffectType::Stun));
+        assert!(
+            effects.has(&StatusEffectType::DamageBoost(0.0)),
+            "buffs stay"
+        );
+    }
+}
@@ -1,61 +1,217 @@
 // Main combat systems that orchestrate the combat flow
 use crate::components::*;
 use crate::damage::*;
+use crate::projectiles::*;
 use crate::states::*;
 use crate::targeting::*;
 use bevy::prelude::*;
+use game_physics::{Airborne, CollisionTeam, CrowdControl, RevealStealth, StatKind, Stats};
 
-/// Main combat execution system
+/// How long attacking keeps a stealthed unit revealed
+pub const ATTACK_REVEAL_DURATION: f32 = 2.0;
+
+/// Chance for a blinded attacker's attack to miss
+pub const BLIND_MISS_CHANCE: f32 = 0.5;
+
+/// Roll for a critical hit on an attack's damage
+fn roll_damage(stats: &Stats, damage: f32) -> (f32, bool) {
+    let is_critical = rand::random::<f32>() < stats.get(StatKind::CriticalChance);
+    if is_critical {
+        (damage * stats.get(StatKind::CriticalDamage), true)
+    } else {
+        (damage, false)
+    }
+}
+
+/// Main combat execution system.
+///
+/// Units with `Weapons` fire every weapon that is off cooldown and whose
+/// range and air/ground rules fit the target: ranged weapons spawn
+/// projectiles, melee hits land instantly. Units without weapons attack with
+/// their `AttackDamage` stat at `TargetingSystem::range`.
+#[allow(clippy::type_complexity)]
 pub fn combat_execution_system(
+    mut commands: Commands,
     mut query: Query<(
         Entity,
         &CombatState,
         &TargetingSystem,
-        &CombatStats,
-        &mut AttackCooldown,
+        &Stats,
+        Option<&mut AttackCooldown>,
+        Option<&mut Weapons>,
         &Transform,
+        Option<&CrowdControl>,
+        Option<&CollisionTeam>,
     )>,
-    target_query: Query<&Transform>,
+    target_query: Query<(&Transform, Option<&UnitType>, Has<Airborne>)>,
     mut damage_events: MessageWriter<DamageEvent>,
+    mut reveal_events: MessageWriter<RevealStealth>,
     time: Res<Time>,
 ) {
-    for (entity, state, targeting, stats, mut cooldown, transform) in query.iter_mut() {
+    let dt = time.delta_seconds();
+
+    for (entity, state, targeting, stats, cooldown, weapons, transform, control, team) in
+        query.iter_mut()
+    {
+        // Stunned and frozen units can't attack or wind up their next attack
+        if control.is_some_and(|control| !control.can_attack()) {
+            continue;
+        }
+        // Blinded attackers may swing at nothing
+        let blinded = control.is_some_and(|control| control.blinded);
+
+        if let Some(mut weapons) = weapons {
+            // Cooldowns tick every frame; keep `Changed<Weapons>` for loadout
+            // changes so targeting isn't recomputed for every armed unit
+            let weapons = weapons.bypass_change_detection();
+
+            // Attack speed modifiers make every weapon cycle faster
+            let attack_speed = stats.ratio(StatKind::AttackSpeed);
+            for weapon in weapons.weapons.iter_mut() {
+                weapon.cooldown_remaining =
+                    (weapon.cooldown_remaining - dt * attack_speed).max(0.0);
+            }
+
+            let CombatState::Attacking(_target) = state else {
+                continue;
+            };
+            let Some(current_target) = targeting.current_target else {
+                continue;
+            };
+            let Ok((target_transform, target_type, target_airborne)) =
+                target_query.get(current_target)
+            else {
+                continue;
+            };
+            let distance = transform.translation.distance(target_transform.translation);
+            let target_airborne = is_airborne(target_type, target_airborne);
+
+            let mut attacked = false;
+            for weapon in weapons.weapons.iter_mut() {
+                if !weapon.is_ready() || !weapon.can_hit(distance, target_airborne) {
+                    continue;
+                }
+                weapon.cooldown_remaining = weapon.cooldown;
+                attacked = true;
+
+                if blinded && rand::random::<f32>() < BLIND_MISS_CHANCE {
+                    continue;
+                }
+
+                // Damage bonuses scale every weapon alike
+                let damage = weapon.damage * stats.ratio(StatKind::AttackDamage);
+                let (damage, is_critical) = roll_damage(stats, damage);
+
+                match Projectile::from_weapon(
+                    weapon,
+                    entity,
+                    team.map(|team| team.0),
+                    damage,
+                    is_critical,
+                ) {
+                    Some(projectile) => {
+                        let projectile = projectile.aimed(
+                            transform.translation,
+                            current_target,
+                            target_transform.translation,
+                        );
+                        spawn_projectile(&mut commands, projectile, transform.translation);
+                    }
+                    None => {
+                        damage_events.write(DamageEvent {
+                            attacker: entity,
+                            target: current_target,
+                            amount: damage,
+                            damage_type: weapon.damage_type,
+                            penetration: weapon.penetration,
+                            is_critical,
+                        });
+                    }
+                }
+            }
+
+            // Attacking gives away a stealthed attacker's position
+            if attacked {
+                reveal_events.write(RevealStealth {
+                    entity,
+                    duration: ATTACK_REVEAL_DURATION,
+                });
+            }
+            continue;
+        }
+
+        let Some(mut cooldown) = cooldown else {
+            continue;
+        };
+
         // Only attack if we're in the attacking state
         if let CombatState::Attacking(_target) = state
-            && cooldown.tick(time.delta_seconds())
+            && cooldown.tick(dt)
         {
             // Check if target is still valid and in range
             if let Some(current_target) = targeting.current_target
-                && let Ok(target_transform) = target_query.get(current_target)
+                && let Ok((target_transform, _, _)) = target_query.get(current_target)
             {
                 let distance = transform.translation.distance(target_transform.translation);
 
                 if distance <= targeting.range {
-                    // Calculate damage
-                    let is_critical = rand::random::<f32>() < stats.critical_chance;
-                    let damage = if is_critical {
-                        stats.damage * stats.critical_damage
-                    } else {
-                        stats.damage
-                    };
-
-                    // Send damage event
-                    damage_events.write(DamageEvent {
-                        attacker: entity,
-                        target: current_target,
-                        amount: damage,
-                        damage_type: DamageType::Physical,
-                        is_critical,
+                    let missed = blinded && rand::random::<f32>() < BLIND_MISS_CHANCE;
+
+                    if !missed {
+                        let (damage, is_critical) =
+                            roll_damage(stats, stats.get(StatKind::AttackDamage));
+
+                        // Send damage event
+                        damage_events.write(DamageEvent {
+                            attacker: entity,
+                            target: current_target,
+                            amount: damage,
+                            damage_type: DamageType::Physical,
+                            penetration: 0.0,
+                            is_critical,
+                        });
+                    }
+
+                    // Attacking gives away a stealthed attacker's position
+                    reveal_events.write(RevealStealth {
+                        entity,
+                        duration: ATTACK_REVEAL_DURATION,
                     });
 
                     // Reset cooldown
-                    cooldown.reset(stats.attack_speed);
+                    cooldown.reset(stats.get(StatKind::AttackSpeed));
                 }
             }
         }
     }
 }
 
+/// System to keep target acquisition in line with what a unit's weapons can reach
+pub fn weapon_targeting_system(
+    mut query: Query<(&Weapons, &mut TargetingSystem), Changed<Weapons>>,
+) {
+    for (weapons, mut targeting) in query.iter_mut() {
+        if weapons.weapons.is_empty() {
+            continue;
+        }
+        let range = weapons.max_range();
+        let can_target_air = weapons.weapons.iter().any(|weapon| weapon.can_target_air);
+        let can_target_ground = weapons
+            .weapons
+            .iter()
+            .any(|weapon| weapon.can_target_ground);
+
+        if targeting.range != range
+            || targeting.can_target_air != can_target_air
+            || targeting.can_target_ground != can_target_ground
+        {
+            targeting.range = range;
+            targeting.can_target_air = can_target_air;
+            targeting.can_target_ground = can_target_ground;
+        }
+    }
+}
+
 /// System to update attack timers
 pub fn update_attack_timers(mut query: Query<&mut AttackTimer>, time: Res<Time>) {
     for mut timer in query.iter_mut() {
@@ -63,21 +219,6 @@ pub fn update_attack_timers(mut query: Query<&mut AttackTimer>, time: Res<Time>)
     }
 }
 
-/// System to handle status effect durations
-pub fn status_effect_system(
-    mut commands: Commands,
-    mut query: Query<(Entity, &mut StatusEffect)>,
-    time: Res<Time>,
-) {
-    for (entity, mut status) in query.iter_mut() {
-        status.remaining -= time.delta_seconds();
-
-        if status.remaining <= 0.0 {
-            commands.entity(entity).remove::<StatusEffect>();
-        }
-    }
-}
-
 /// System to handle shield regeneration
 pub fn shield_regeneration_system(mut query: Query<&mut Shield>, time: Res<Time>) {
     for mut shield in query.iter_mut() {
@@ -120,64 +261,229 @@ pub fn combat_log_system(
     }
 }
 
-/// System to handle projectiles
-pub fn projectile_system(
+/// System to clean up dead entities
+pub fn cleanup_dead_entities(
     mut commands: Commands,
-    mut query: Query<(Entity, &mut Projectile, &Transform)>,
-    target_query: Query<(Entity, &Transform)>,
+    query: Query<(Entity, &Dead)>,
     time: Res<Time>,
-    mut damage_events: MessageWriter<DamageEvent>,
 ) {
-    for (entity, mut projectile, transform) in query.iter_mut() {
-        projectile.remaining_lifetime -= time.delta_seconds();
-
-        if projectile.remaining_lifetime <= 0.0 {
+    for (entity, dead) in query.iter() {
+        // Wait a bit before despawning to allow death animations
+        if time.elapsed_seconds() - dead.death_time > 2.0 {
             commands.entity(entity).despawn();
-            continue;
         }
+    }
+}
 
-        // Check for collisions (simplified - would use Rapier in production)
-        for (target_entity, target_transform) in target_query.iter() {
-            let distance = transform.translation.distance(target_transform.translation);
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
 
-            if distance < 1.0 {
-                // Hit detection radius
-                damage_events.write(DamageEvent {
-                    attacker: projectile.owner,
-                    target: target_entity,
-                    amount: projectile.damage,
-                    damage_type: projectile.damage_type.clone(),
-                    is_critical: false,
-                });
+    /// Direct hits landed this test
+    #[derive(Resource, Default)]
+    struct Hits(Vec<DamageEvent>);
 
-                // Handle area damage
-                if let Some(_area) = &projectile.area_damage {
-                    // TODO: Implement area damage calculation
-                }
+    fn collect_hits(mut hits: ResMut<Hits>, mut damage_events: MessageReader<DamageEvent>) {
+        hits.0.extend(damage_events.read().cloned());
+    }
 
-                // Destroy projectile unless it pierces
-                if projectile.pierce_count == 0 {
-                    commands.entity(entity).despawn();
-                } else {
-                    projectile.pierce_count -= 1;
-                }
+    fn weapons_app() -> App {
+        let mut app = App::new();
+        app.init_resource::<Time>()
+            .init_resource::<Hits>()
+            .add_message::<DamageEvent>()
+            .add_message::<RevealStealth>()
+            .add_systems(
+                Update,
+                (
+                    weapon_targeting_system,
+                    combat_execution_system,
+                    collect_hits,
+                )
+                    .chain(),
+            );
+        app
+    }
 
-                break;
-            }
-        }
+    fn step(app: &mut App, seconds: f32) {
+        app.world_mut()
+            .resource_mut::<Time>()
+            .advance_by(Duration::from_secs_f32(seconds));
+        app.update();
     }
-}
 
-/// System to clean up dead entities
-pub fn cleanup_dead_entities(
-    mut commands: Commands,
-    query: Query<(Entity, &Dead)>,
-    time: Res<Time>,
-) {
-    for (entity, dead) in query.iter() {
-        // Wait a bit before despawning to allow death animations
-        if time.elapsed_seconds() - dead.death_time > 2.0 {
-            commands.entity(entity).despawn();
+    fn target(app: &mut App, distance: f32, bundle: impl Bundle) -> Entity {
+        app.world_mut()
+            .spawn((Transform::from_xyz(distance, 0.0, 0.0), bundle))
+            .id()
+    }
+
+    /// Unit at the origin attacking `target` with `weapons`
+    fn armed(app: &mut App, target: Entity, weapons: impl IntoIterator<Item = Weapon>) -> Entity {
+        app.world_mut()
+            .spawn((
+                Transform::default(),
+                Stats::new()
+                    .with(StatKind::AttackDamage, 10.0)
+                    .with(StatKind::AttackSpeed, 1.0),
+                Weapons::new(weapons),
+                CombatState::Attacking(target),
+                TargetingSystem {
+                    current_target: Some(target),
+                    ..default()
+                },
+            ))
+            .id()
+    }
+
+    fn hits_by(app: &App, attacker: Entity) -> Vec<f32> {
+        app.world()
+            .resource::<Hits>()
+            .0
+            .iter()
+            .filter(|hit| hit.attacker == attacker)
+            .map(|hit| hit.amount)
+            .collect()
+    }
+
+    fn projectiles_by(app: &mut App, owner: Entity) -> usize {
+        app.world_mut()
+            .query::<&Projectile>()
+            .iter(app.world())
+            .filter(|projectile| projectile.owner == owner)
+            .count()
+    }
+
+    #[test]
+    fn weapons_fire_only_between_min_and_max_range() {
+        let mut app = weapons_app();
+        let mortar = || Weapon::new(WeaponType::Ranged, 10.0, 1.0, 20.0).with_min_range(5.0);
+        let too_close = target(&mut app, 3.0, ());
+        let in_range = target(&mut app, 10.0, ());
+        let too_far = target(&mut app, 25.0, ());
+        let close_attacker = armed(&mut app, too_close, [mortar()]);
+        let attacker = armed(&mut app, in_range, [mortar()]);
+        let far_attacker = armed(&mut app, too_far, [mortar()]);
+
+        step(&mut app, 0.1);
+
+        assert_eq!(projectiles_by(&mut app, close_attacker), 0);
+        assert_eq!(projectiles_by(&mut app, attacker), 1);
+        assert_eq!(projectiles_by(&mut app, far_attacker), 0);
+    }
+
+    #[test]
+    fn each_weapon_keeps_its_own_cooldown() {
+        let mut app = weapons_app();
+        let enemy = target(&mut app, 2.0, ());
+        let attacker = armed(
+            &mut app,
+            enemy,
+            [
+                Weapon::new(WeaponType::Melee, 10.0, 1.0, 3.0),
+                Weapon::new(WeaponType::Melee, 3.0, 0.25, 3.0),
+            ],
+        );
+
+        for _ in 0..10 {
+            step(&mut app, 0.1);
         }
+
+        let hits = hits_by(&app, attacker);
+        assert_eq!(hits.iter().filter(|amount| **amount == 10.0).count(), 1);
+        assert_eq!(hits.iter().filter(|amount| **amount == 3.0).count(), 4);
+    }
+
+    #[test]
+    fn weapons_pick_air_or_ground_targets() {
+        let mut app = weapons_app();
+        let loadout = || {
+            [
+                Weapon::new(WeaponType::Melee, 10.0, 1.0, 3.0).targeting(false, true),
+                Weapon::new(WeaponType::Ranged, 5.0, 1.0, 3.0).targeting(true, false),
+            ]
+        };
+        let walker = target(&mut app, 2.0, ());
+        let flyer = target(&mut app, 2.0, Airborne::default());
+        let ground_attacker = armed(&mut app, walker, loadout());
+        let air_attacker = armed(&mut app, flyer, loadout());
+
+        step(&mut app, 0.1);
+
+        // The ground weapon strikes the walker, the anti-air one the flyer
+        assert_eq!(hits_by(&app, ground_attacker), vec![10.0]);
+        assert_eq!(projectiles_by(&mut app, ground_attacker), 0);
+        assert!(hits_by(&app, air_attacker).is_empty());
+        assert_eq!(projectiles_by(&mut app, air_attacker), 1);
+    }
+
+    #[test]
+    fn melee_hits_instantly_and_ranged_weapons_fire_projectiles() {
+        let mut app = weapons_app();
+        let near = target(&mut app, 2.0, ());
+        let far = target(&mut app, 10.0, ());
+        let swordsman = armed(
+            &mut app,
+            near,
+            [Weapon::new(WeaponType::Melee, 10.0, 1.0, 3.0)],
+        );
+        let archer = armed(
+            &mut app,
+            far,
+            [Weapon::new(WeaponType::Ranged, 8.0, 1.0, 15.0)],
+        );
+
+        step(&mut app, 0.1);
+
+        assert_eq!(hits_by(&app, swordsman), vec![10.0]);
+        assert_eq!(projectiles_by(&mut app, swordsman), 0);
+        assert!(hits_by(&app, archer).is_empty());
+        assert_eq!(projectiles_by(&mut app, archer), 1);
+        let projectile = app
+            .world_mut()
+            .query::<&Projectile>()
+            .single(app.world())
+            .unwrap();
+        assert_eq!(projectile.damage, 8.0);
+    }
+
+    #[test]
+    fn cooldowns_do_not_retrigger_weapon_targeting() {
+        let mut app = weapons_app();
+        let enemy = target(&mut app, 2.0, ());
+        let attacker = armed(
+            &mut app,
+            enemy,
+            [Weapon::new(WeaponType::Melee, 10.0, 1.0, 3.0)],
+        );
+
+        step(&mut app, 0.1);
+        assert_eq!(
+            app.world().get::<TargetingSystem>(attacker).unwrap().range,
+            3.0
+        );
+
+        // Targeting is only synced when the loadout changes, not as weapons cool down
+        app.world_mut()
+            .get_mut::<TargetingSystem>(attacker)
+            .unwrap()
+            .range = 1.0;
+        step(&mut app, 0.1);
+        assert_eq!(
+            app.world().get::<TargetingSystem>(attacker).unwrap().range,
+            1.0
+        );
+
+        app.world_mut()
+            .get_mut::<Weapons>(attacker)
+            .unwrap()
+            .weapons
+            .push(Weapon::new(WeaponType::Ranged, 5.0, 1.0, 12.0));
+        step(&mut app, 0.1);
+        assert_eq!(
+            app.world().get::<TargetingSystem>(attacker).unwrap().range,
+            12.0
+        );
     }
 }
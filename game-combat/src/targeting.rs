@@ -1,4 +1,8 @@
-use game_physics::Velocity;
+use crate::components::UnitType;
+use game_physics::{
+    Airborne, CollisionMask, GlobalSpatialGrid, Madness, MadnessBehaviour, PhysicsRaycast,
+    StealthState, is_hidden_from, sight_blocking_layers,
+};
 // Targeting system using Rapier3D for physics-based line of sight and range checks
 use bevy::prelude::*;
 // use bevy_rapier3d::prelude::*;
@@ -23,6 +27,17 @@ pub struct TargetingSystem {
     pub can_target_ground: bool,
 }
 
+impl TargetingSystem {
+    /// Whether this targeting system is allowed to engage air or ground
+    pub fn can_target(&self, target_airborne: bool) -> bool {
+        if target_airborne {
+            self.can_target_air
+        } else {
+            self.can_target_ground
+        }
+    }
+}
+
 impl Default for TargetingSystem {
     fn default() -> Self {
         Self {
@@ -58,6 +73,7 @@ pub enum TargetLostReason {
     LineOfSightBlocked,
     TargetDestroyed,
     ManualDisengage,
+    Stealthed,
 }
 
 pub struct TargetingPlugin;
@@ -66,6 +82,7 @@ impl Plugin for TargetingPlugin {
     fn build(&self, app: &mut App) {
         app.add_message::<TargetAcquiredEvent>()
             .add_message::<TargetLostEvent>()
+            .init_resource::<GlobalSpatialGrid>()
             .add_systems(
                 Update,
                 (
@@ -78,29 +95,81 @@ impl Plugin for TargetingPlugin {
     }
 }
 
-/// System that handles target acquisition
+/// Whether a unit is in a frenzy and attacks allies as readily as enemies
+pub fn is_frenzied(madness: Option<&Madness>) -> bool {
+    madness.is_some_and(|madness| madness.behaviour == MadnessBehaviour::Frenzy)
+}
+
+/// Whether an entity is currently in the air, either as a flying unit type
+/// or because it is on the flight movement layer
+pub fn is_airborne(unit_type: Option<&UnitType>, has_airborne: bool) -> bool {
+    has_airborne || unit_type.is_some_and(|unit_type| unit_type.is_flying)
+}
+
+/// System that handles target acquisition.
+///
+/// Frenzied units pick a random target in reach, allies included.
+#[allow(clippy::type_complexity)]
 pub fn target_acquisition_system(
-    mut targeting_query: Query<(Entity, &mut TargetingSystem, &Transform, &Targetable)>,
-    targetable_query: Query<(Entity, &Transform, &Targetable), Without<TargetingSystem>>,
-    // TODO: Add proper Rapier integration when available
+    mut targeting_query: Query<(
+        Entity,
+        &mut TargetingSystem,
+        &Transform,
+        &Targetable,
+        Option<&UnitType>,
+        Has<Airborne>,
+        Option<&Madness>,
+    )>,
+    targetable_query: Query<
+        (
+            Entity,
+            &Transform,
+            &Targetable,
+            Option<&UnitType>,
+            Has<Airborne>,
+            Option<&StealthState>,
+        ),
+        Without<TargetingSystem>,
+    >,
+    spatial_grid: Res<GlobalSpatialGrid>,
+    raycast: PhysicsRaycast,
     mut target_acquired_events: MessageWriter<TargetAcquiredEvent>,
 ) {
-    for (entity, mut targeting, transform, my_team) in targeting_query.iter_mut() {
+    for (entity, mut targeting, transform, my_team, my_type, my_airborne, madness) in
+        targeting_query.iter_mut()
+    {
         // Skip if we already have a valid target
         if targeting.current_target.is_some() {
             continue;
         }
 
+        let frenzied = is_frenzied(madness);
+        let viewer_airborne = is_airborne(my_type, my_airborne);
+
         let mut best_target: Option<(Entity, f32, f32)> = None; // (entity, distance, score)
 
-        for (target_entity, target_transform, target_team) in targetable_query.iter() {
-            // Don't target same team
-            if target_team.team_id == my_team.team_id {
+        // Only consider targetables indexed within weapon range
+        let nearby = spatial_grid
+            .grid
+            .query_radius(transform.translation, targeting.range);
+        for (target_entity, target_transform, target_team, target_type, target_airborne, stealth) in
+            nearby
+                .into_iter()
+                .filter_map(|e| targetable_query.get(e).ok())
+        {
+            // Don't target same team unless madness has taken over
+            if target_team.team_id == my_team.team_id && !frenzied {
+                continue;
+            }
+
+            // Check if target is visible and not hidden by stealth
+            if !target_team.is_visible || is_hidden_from(stealth, my_team.team_id) {
                 continue;
             }
 
-            // Check if target is visible
-            if !target_team.is_visible {
+            // Check air/ground restrictions
+            let target_airborne = is_airborne(target_type, target_airborne);
+            if !targeting.can_target(target_airborne) {
                 continue;
             }
 
@@ -116,13 +185,24 @@ pub fn target_acquisition_system(
                 continue;
             }
 
-            // TODO: Add line of sight check with Rapier when available
-            // For now, assume line of sight is clear
+            // Check line of sight (cliffs don't block air units)
+            if !raycast.line_of_sight(
+                transform.translation,
+                target_transform.translation,
+                sight_blocking_layers(viewer_airborne, target_airborne),
+                &[entity, target_entity],
+            ) {
+                continue;
+            }
 
             // Calculate target score (prefer closer, higher priority targets)
             let distance_score = 1.0 - (distance / targeting.range);
             let priority_score = target_team.priority;
-            let total_score = distance_score + priority_score;
+            let total_score = if frenzied {
+                rand::random::<f32>()
+            } else {
+                distance_score + priority_score
+            };
 
             if best_target.is_none() || total_score > best_target.as_ref().unwrap().2 {
                 best_target = Some((target_entity, distance, total_score));
@@ -145,18 +225,26 @@ pub fn target_acquisition_system(
 
 /// System that validates current targets are still valid
 pub fn target_validation_system(
-    mut targeting_query: Query<(Entity, &mut TargetingSystem, &Transform, &Targetable)>,
-    targetable_query: Query<(&Transform, &Targetable)>,
+    mut targeting_query: Query<(
+        Entity,
+        &mut TargetingSystem,
+        &Transform,
+        &Targetable,
+        Option<&Madness>,
+    )>,
+    targetable_query: Query<(&Transform, &Targetable, Option<&StealthState>)>,
     mut target_lost_events: MessageWriter<TargetLostEvent>,
     time: Res<Time>,
 ) {
-    for (entity, mut targeting, transform, my_team) in targeting_query.iter_mut() {
+    for (entity, mut targeting, transform, my_team, madness) in targeting_query.iter_mut() {
         if let Some(target_entity) = targeting.current_target {
             let mut lose_target = false;
             let mut reason = TargetLostReason::TargetDestroyed;
 
             // Check if target still exists and is valid
-            if let Ok((target_transform, target_team)) = targetable_query.get(target_entity) {
+            if let Ok((target_transform, target_team, stealth)) =
+                targetable_query.get(target_entity)
+            {
                 let distance = transform.translation.distance(target_transform.translation);
 
                 // Check range
@@ -167,7 +255,7 @@ pub fn target_validation_system(
                 }
 
                 // Check if still enemy
-                if target_team.team_id == my_team.team_id {
+                if target_team.team_id == my_team.team_id && !is_frenzied(madness) {
                     lose_target = true;
                     reason = TargetLostReason::ManualDisengage;
                 }
@@ -178,6 +266,12 @@ pub fn target_validation_system(
                     reason = TargetLostReason::LineOfSightBlocked;
                 }
 
+                // Check if it slipped into stealth
+                if is_hidden_from(stealth, my_team.team_id) {
+                    lose_target = true;
+                    reason = TargetLostReason::Stealthed;
+                }
+
                 // Update lock time
                 if !lose_target {
                     targeting.target_lock_time += time.delta_seconds();
@@ -202,17 +296,45 @@ pub fn target_validation_system(
     }
 }
 
-/// System that performs detailed line of sight checks
+/// Layers that block line of sight between two ground units (see `sight_blocking_layers`)
+pub const SIGHT_BLOCKING_LAYERS: u32 = CollisionMask::BUILDINGS | CollisionMask::TERRAIN;
+
+/// System that drops targets hidden behind buildings or terrain
+#[allow(clippy::type_complexity)]
 pub fn line_of_sight_system(
-    targeting_query: Query<(Entity, &TargetingSystem, &Transform)>,
-    transform_query: Query<&Transform>,
+    mut targeting_query: Query<(
+        Entity,
+        &mut TargetingSystem,
+        &Transform,
+        Option<&UnitType>,
+        Has<Airborne>,
+    )>,
+    transform_query: Query<(&Transform, Option<&UnitType>, Has<Airborne>)>,
+    raycast: PhysicsRaycast,
+    mut target_lost_events: MessageWriter<TargetLostEvent>,
 ) {
-    for (_entity, targeting, _transform) in targeting_query.iter() {
+    for (entity, mut targeting, transform, my_type, my_airborne) in targeting_query.iter_mut() {
         if let Some(target_entity) = targeting.current_target
-            && let Ok(_target_transform) = transform_query.get(target_entity)
+            && let Ok((target_transform, target_type, target_airborne)) =
+                transform_query.get(target_entity)
+            && !raycast.line_of_sight(
+                transform.translation,
+                target_transform.translation,
+                sight_blocking_layers(
+                    is_airborne(my_type, my_airborne),
+                    is_airborne(target_type, target_airborne),
+                ),
+                &[entity, target_entity],
+            )
         {
-            // TODO: Add detailed raycast with Rapier when available
-            // For now, assume line of sight is always clear
+            target_lost_events.write(TargetLostEvent {
+                entity,
+                previous_target: target_entity,
+                reason: TargetLostReason::LineOfSightBlocked,
+            });
+
+            targeting.current_target = None;
+            targeting.target_lock_time = 0.0;
         }
     }
 }
@@ -225,40 +347,5 @@ fn is_in_field_of_view(observer: &Transform, target: &Transform, field_of_view:
     angle <= field_of_view / 2.0
 }
 
-/// Component for projectiles that tracks their target
-#[derive(Component)]
-pub struct HomingProjectile {
-    pub target: Entity,
-    pub turn_speed: f32,
-    pub acceleration: f32,
-}
-
-/// System for homing projectiles
-pub fn homing_projectile_system(
-    mut projectile_query: Query<(&mut Transform, &mut Velocity, &HomingProjectile)>,
-    target_query: Query<&Transform, Without<HomingProjectile>>,
-    time: Res<Time>,
-) {
-    for (mut transform, mut velocity, homing) in projectile_query.iter_mut() {
-        if let Ok(target_transform) = target_query.get(homing.target) {
-            // Calculate direction to target
-            let to_target = (target_transform.translation - transform.translation).normalize();
-
-            // Smoothly rotate towards target
-            let current_dir = transform.rotation * Vec3::Z;
-            let new_dir = current_dir.lerp(to_target, homing.turn_speed * time.delta_seconds());
-            transform.look_to(new_dir, Vec3::Y);
-
-            // Accelerate towards target
-            velocity.linear += new_dir * homing.acceleration * time.delta_seconds();
-
-            // Cap max speed
-            let max_speed = 50.0;
-            if velocity.linear.length() > max_speed {
-                velocity.linear = velocity.linear.normalize() * max_speed;
-            }
-        }
-    }
-}
 impl bevy::prelude::Message for TargetAcquiredEvent {}
 impl bevy::prelude::Message for TargetLostEvent {}
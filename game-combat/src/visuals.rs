@@ -1,6 +1,9 @@
 // Visual effects system for combat
 use crate::components::*;
 use crate::damage::{DamageEvent, DeathEvent};
+use crate::status::{
+    ApplyOutcome, StatusEffectApplied, StatusEffectDispelled, StatusEffectExpired, StatusEffects,
+};
 use bevy::pbr::StandardMaterial;
 use bevy::prelude::*;
 use bevy::render::alpha::AlphaMode;
@@ -88,6 +91,7 @@ impl Plugin for CombatVisualsPlugin {
                     update_projectile_trails,
                     update_shield_effects,
                     animate_buff_indicators,
+                    status_indicator_system,
                     cleanup_expired_effects,
                 ),
             );
@@ -128,7 +132,7 @@ pub fn spawn_damage_numbers(
                 Name::new("VisualDamageNumber"),
                 VisualDamageNumber {
                     amount: event.amount,
-                    damage_type: event.damage_type.clone(),
+                    damage_type: event.damage_type,
                     lifetime: 1.5,
                     velocity: Vec3::new(
                         rand::random::<f32>() * 2.0 - 1.0,
@@ -424,6 +428,72 @@ pub fn animate_buff_indicators(
     }
 }
 
+/// Keep one indicator per active kind of status effect above each entity
+#[allow(clippy::too_many_arguments)]
+pub fn status_indicator_system(
+    mut commands: Commands,
+    mut applied_events: MessageReader<StatusEffectApplied>,
+    mut expired_events: MessageReader<StatusEffectExpired>,
+    mut dispelled_events: MessageReader<StatusEffectDispelled>,
+    effects_query: Query<&StatusEffects>,
+    indicators: Query<(Entity, &ChildOf, &BuffVisualIndicator)>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    let mut spawned: Vec<(Entity, StatusEffectType)> = Vec::new();
+
+    for event in applied_events.read() {
+        if event.outcome != ApplyOutcome::Added {
+            continue;
+        }
+
+        let effect_type = &event.effect.effect_type;
+        let already_shown = indicators.iter().any(|(_, parent, indicator)| {
+            parent.parent() == event.target && indicator.effect_type.same_kind(effect_type)
+        }) || spawned
+            .iter()
+            .any(|(target, shown)| *target == event.target && shown.same_kind(effect_type));
+        if already_shown {
+            continue;
+        }
+
+        if commands.get_entity(event.target).is_err() {
+            continue;
+        }
+        let indicator = create_buff_indicator(
+            &mut commands,
+            &mut meshes,
+            &mut materials,
+            effect_type.clone(),
+        );
+        commands.entity(event.target).add_child(indicator);
+        spawned.push((event.target, effect_type.clone()));
+    }
+
+    let ended = expired_events
+        .read()
+        .map(|event| (event.target, &event.effect.effect_type))
+        .chain(
+            dispelled_events
+                .read()
+                .map(|event| (event.target, &event.effect.effect_type)),
+        );
+    for (target, effect_type) in ended {
+        let still_active = effects_query
+            .get(target)
+            .is_ok_and(|effects| effects.has(effect_type));
+        if still_active {
+            continue;
+        }
+
+        for (indicator_entity, parent, indicator) in indicators.iter() {
+            if parent.parent() == target && indicator.effect_type.same_kind(effect_type) {
+                commands.entity(indicator_entity).despawn();
+            }
+        }
+    }
+}
+
 /// Clean up expired visual effects
 pub fn cleanup_expired_effects(
     _commands: Commands,
@@ -445,7 +515,7 @@ fn spawn_impact_particles(
     let particle_type = match damage_type {
         DamageType::Physical => ParticleType::Blood,
         DamageType::Magic => ParticleType::Void,
-        DamageType::Chaos => ParticleType::Explosion,
+        DamageType::Chaos | DamageType::Siege => ParticleType::Explosion,
         _ => ParticleType::Blood,
     };
 
@@ -618,6 +688,7 @@ fn get_damage_color(damage_type: &DamageType, is_critical: bool) -> Color {
             DamageType::Magic => Color::srgb(0.5, 0.0, 1.0),
             DamageType::True => Color::srgb(1.0, 0.0, 0.0),
             DamageType::Chaos => Color::srgb(0.8, 0.0, 0.8),
+            DamageType::Siege => Color::srgb(1.0, 0.6, 0.2),
         }
     }
 }
@@ -1,6 +1,34 @@
 // XP and progression system
+use crate::components::{CombatLog, Dead};
+use crate::damage::{DamageApplied, DeathEvent};
 use bevy::prelude::*;
+use game_physics::{CollisionTeam, GlobalSpatialGrid, ModifierOp, ModifierSource, StatKind, Stats};
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+// Veterancy promotes from experience in the physics layer
+pub use game_physics::{Experience, Promoted, VeteranStatus, VeteranTier};
+
+/// Seconds a hit keeps counting towards kill and assist credit
+pub const ASSIST_WINDOW: f32 = 10.0;
+
+/// XP every kill is worth before the victim's stats and level
+pub const KILL_XP_BASE: f32 = 20.0;
+
+/// Extra share of a victim's XP value per level above the first
+pub const XP_LEVEL_SCALING: f32 = 0.25;
+
+/// Fraction of a victim's XP value each assisting attacker earns
+pub const ASSIST_XP_FRACTION: f32 = 0.5;
+
+/// Allies of the killer this close to the victim share in the XP
+pub const XP_SHARE_RADIUS: f32 = 15.0;
+
+/// Fraction of a victim's XP value each nearby ally earns
+pub const SHARED_XP_FRACTION: f32 = 0.25;
+
+/// Health and damage bonus per level above the first
+pub const LEVEL_STAT_BONUS: f32 = 0.05;
 
 pub struct XPPlugin;
 
@@ -8,49 +36,84 @@ impl Plugin for XPPlugin {
     fn build(&self, app: &mut App) {
         app.add_message::<XPGainEvent>()
             .add_message::<LevelUpEvent>()
+            .add_message::<DamageApplied>()
+            .add_message::<DeathEvent>()
             .add_systems(
                 Update,
-                (process_xp_events, check_level_ups, apply_level_bonuses).chain(),
+                (
+                    damage_contribution_system,
+                    kill_xp_system,
+                    process_xp_events,
+                    check_level_ups,
+                    apply_level_bonuses,
+                )
+                    .chain(),
             );
     }
 }
 
-#[derive(Component, Clone, Debug, Serialize, Deserialize)]
-pub struct Experience {
-    pub current: u32,
-    pub total: u32,
-    pub level: u32,
-    pub next_level_xp: u32,
+/// One attacker's recent damage to a unit
+#[derive(Clone, Debug)]
+pub struct DamageContribution {
+    pub attacker: Entity,
+    pub damage: f32,
+    pub last_hit: f32,
 }
 
-impl Default for Experience {
-    fn default() -> Self {
-        Self {
-            current: 0,
-            total: 0,
-            level: 1,
-            next_level_xp: 100,
-        }
-    }
+/// Recent attackers of a unit, for kill and assist credit
+#[derive(Component, Clone, Debug, Default)]
+pub struct DamageContributors {
+    pub contributions: Vec<DamageContribution>,
 }
 
-impl Experience {
-    pub fn add_xp(&mut self, amount: u32) {
-        self.current += amount;
-        self.total += amount;
+impl DamageContributors {
+    pub fn record(&mut self, attacker: Entity, damage: f32, now: f32) {
+        // Damage from before the assist window no longer counts
+        self.contributions
+            .retain(|contribution| now - contribution.last_hit <= ASSIST_WINDOW);
+        match self
+            .contributions
+            .iter_mut()
+            .find(|contribution| contribution.attacker == attacker)
+        {
+            Some(contribution) => {
+                contribution.damage += damage;
+                contribution.last_hit = now;
+            }
+            None => self.contributions.push(DamageContribution {
+                attacker,
+                damage,
+                last_hit: now,
+            }),
+        }
     }
 
-    pub fn can_level_up(&self) -> bool {
-        self.current >= self.next_level_xp
+    /// Attackers who hit within the assist window
+    pub fn recent(&self, now: f32) -> impl Iterator<Item = &DamageContribution> {
+        self.contributions
+            .iter()
+            .filter(move |contribution| now - contribution.last_hit <= ASSIST_WINDOW)
     }
 
-    pub fn level_up(&mut self) {
-        self.current -= self.next_level_xp;
-        self.level += 1;
-        self.next_level_xp = calculate_next_level_xp(self.level);
+    /// Recent attacker who dealt the most damage
+    pub fn top(&self, now: f32) -> Option<Entity> {
+        self.recent(now)
+            .max_by(|a, b| a.damage.total_cmp(&b.damage))
+            .map(|contribution| contribution.attacker)
     }
 }
 
+/// XP a unit is worth when killed: tougher, harder-hitting and higher-level
+/// units are worth more
+pub fn xp_value(stats: Option<&Stats>, experience: Option<&Experience>) -> f32 {
+    let base = KILL_XP_BASE
+        + stats.map_or(0.0, |stats| {
+            stats.get(StatKind::MaxHealth) * 0.2 + stats.get(StatKind::AttackDamage) * 2.0
+        });
+    let level = experience.map_or(1, |experience| experience.level);
+    base * (1.0 + (level.saturating_sub(1)) as f32 * XP_LEVEL_SCALING)
+}
+
 #[derive(Event, Clone, Debug)]
 pub struct XPGainEvent {
     pub entity: Entity,
@@ -62,6 +125,8 @@ pub struct XPGainEvent {
 pub enum XPSource {
     Kill(Entity),
     Assist(Entity),
+    /// An ally's kill nearby
+    Shared(Entity),
     Damage(f32),
     Objective,
     Quest,
@@ -73,49 +138,148 @@ pub struct LevelUpEvent {
     pub new_level: u32,
 }
 
-#[derive(Component, Clone, Debug)]
-pub struct VeteranStatus {
-    pub tier: VeteranTier,
-    pub kill_count: u32,
-    pub total_damage: f32,
-}
+/// System to remember who damaged each unit recently
+pub fn damage_contribution_system(
+    mut commands: Commands,
+    mut applied_events: MessageReader<DamageApplied>,
+    mut query: Query<&mut DamageContributors>,
+    time: Res<Time>,
+) {
+    let now = time.elapsed_seconds();
+    // Units hit for the first time get their contributors inserted once
+    let mut new_contributors: HashMap<Entity, DamageContributors> = HashMap::new();
 
-#[derive(Clone, Debug, Serialize, Deserialize)]
-pub enum VeteranTier {
-    Recruit,
-    Regular,
-    Veteran,
-    Elite,
-    Champion,
-    Legendary,
-}
+    for event in applied_events.read() {
+        if event.attacker == event.target {
+            continue;
+        }
+        if let Ok(mut contributors) = query.get_mut(event.target) {
+            contributors.record(event.attacker, event.amount, now);
+        } else {
+            new_contributors.entry(event.target).or_default().record(
+                event.attacker,
+                event.amount,
+                now,
+            );
+        }
+    }
 
-impl VeteranTier {
-    pub fn stat_multiplier(&self) -> f32 {
-        match self {
-            VeteranTier::Recruit => 1.0,
-            VeteranTier::Regular => 1.1,
-            VeteranTier::Veteran => 1.25,
-            VeteranTier::Elite => 1.4,
-            VeteranTier::Champion => 1.6,
-            VeteranTier::Legendary => 2.0,
+    for (entity, contributors) in new_contributors {
+        if let Ok(mut entity_commands) = commands.get_entity(entity) {
+            entity_commands.insert(contributors);
         }
     }
 }
 
-fn calculate_next_level_xp(level: u32) -> u32 {
-    // Exponential growth formula
-    100 + (level * level * 50)
+/// System to award kill, assist and nearby-ally XP when a unit dies.
+///
+/// Deaths without a killing blow (e.g. poison) credit the top recent attacker.
+/// The victim's own team never earns XP, even from friendly fire or frenzy.
+#[allow(clippy::type_complexity)]
+pub fn kill_xp_system(
+    mut death_events: MessageReader<DeathEvent>,
+    victim_query: Query<(
+        Option<&DamageContributors>,
+        Option<&Stats>,
+        Option<&Experience>,
+        Option<&Transform>,
+    )>,
+    team_query: Query<&CollisionTeam>,
+    ally_query: Query<(&CollisionTeam, Has<Dead>), With<Experience>>,
+    mut log_query: Query<&mut CombatLog>,
+    spatial_grid: Res<GlobalSpatialGrid>,
+    mut xp_events: MessageWriter<XPGainEvent>,
+    time: Res<Time>,
+) {
+    let now = time.elapsed_seconds();
+
+    for event in death_events.read() {
+        let victim = event.entity;
+        let Ok((contributors, stats, experience, transform)) = victim_query.get(victim) else {
+            continue;
+        };
+        let Some(killer) = event
+            .killer
+            .or_else(|| contributors.and_then(|contributors| contributors.top(now)))
+        else {
+            continue;
+        };
+        if killer == victim {
+            continue;
+        }
+        let victim_team = team_query.get(victim).ok().map(|team| team.0);
+        let on_victims_team = |entity: Entity| {
+            victim_team.is_some() && team_query.get(entity).ok().map(|team| team.0) == victim_team
+        };
+
+        let value = xp_value(stats, experience);
+        if !on_victims_team(killer) {
+            xp_events.write(XPGainEvent {
+                entity: killer,
+                amount: value.round() as u32,
+                source: XPSource::Kill(victim),
+            });
+        }
+
+        let mut credited = vec![killer];
+        for contribution in contributors
+            .into_iter()
+            .flat_map(|contributors| contributors.recent(now))
+        {
+            if credited.contains(&contribution.attacker)
+                || contribution.attacker == victim
+                || on_victims_team(contribution.attacker)
+            {
+                continue;
+            }
+            credited.push(contribution.attacker);
+            xp_events.write(XPGainEvent {
+                entity: contribution.attacker,
+                amount: (value * ASSIST_XP_FRACTION).round() as u32,
+                source: XPSource::Assist(victim),
+            });
+            if let Ok(mut log) = log_query.get_mut(contribution.attacker) {
+                log.assists += 1;
+            }
+        }
+
+        // The killer's nearby allies learn from the fight too
+        let (Some(transform), Ok((killer_team, _))) = (transform, ally_query.get(killer)) else {
+            continue;
+        };
+        for ally in spatial_grid
+            .grid
+            .query_radius(transform.translation, XP_SHARE_RADIUS)
+        {
+            if credited.contains(&ally) || ally == victim {
+                continue;
+            }
+            let Ok((team, dead)) = ally_query.get(ally) else {
+                continue;
+            };
+            if dead || team.0 != killer_team.0 || Some(team.0) == victim_team {
+                continue;
+            }
+            credited.push(ally);
+            xp_events.write(XPGainEvent {
+                entity: ally,
+                amount: (value * SHARED_XP_FRACTION).round() as u32,
+                source: XPSource::Shared(victim),
+            });
+        }
+    }
 }
 
 pub fn process_xp_events(
     mut xp_events: MessageReader<XPGainEvent>,
-    mut query: Query<&mut Experience>,
+    mut query: Query<(&mut Experience, Option<&Stats>)>,
     mut level_up_events: MessageWriter<LevelUpEvent>,
 ) {
     for event in xp_events.read() {
-        if let Ok(mut experience) = query.get_mut(event.entity) {
-            experience.add_xp(event.amount);
+        if let Ok((mut experience, stats)) = query.get_mut(event.entity) {
+            // Leaders and veterans learn faster
+            let gain = stats.map_or(1.0, |stats| stats.ratio(StatKind::XpGain));
+            experience.add_xp((event.amount as f32 * gain).round() as u32);
 
             // Check for level up
             while experience.can_level_up() {
@@ -149,14 +313,206 @@ pub fn check_level_ups(mut level_up_events: MessageReader<LevelUpEvent>) {
     }
 }
 
-pub fn apply_level_bonuses(query: Query<(&Experience, &crate::components::CombatStats)>) {
-    for (experience, _stats) in query.iter() {
-        // Apply level-based stat increases
-        let _level_bonus = 1.0 + (experience.level as f32 - 1.0) * 0.05;
-
-        // TODO: Apply level bonuses with base stats tracking
-        // For now, we'll just recalculate based on level
+/// Levels scale health and damage through the stat pipeline
+pub fn apply_level_bonuses(mut query: Query<(&Experience, &mut Stats), Changed<Experience>>) {
+    for (experience, mut stats) in query.iter_mut() {
+        let level_bonus = 1.0 + experience.level.saturating_sub(1) as f32 * LEVEL_STAT_BONUS;
+        let applied = stats
+            .modifiers()
+            .find(|modifier| modifier.source == ModifierSource::Level)
+            .map_or(ModifierOp::Multiply(1.0), |modifier| modifier.op);
+        // Experience changes with every kill; only rebuild stats on level ups
+        if applied == ModifierOp::Multiply(level_bonus) {
+            continue;
+        }
+        stats.replace_source(
+            ModifierSource::Level,
+            [
+                (StatKind::MaxHealth, ModifierOp::Multiply(level_bonus)),
+                (StatKind::AttackDamage, ModifierOp::Multiply(level_bonus)),
+            ],
+            None,
+        );
     }
 }
+
 impl bevy::prelude::Message for XPGainEvent {}
 impl bevy::prelude::Message for LevelUpEvent {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use game_physics::DamageType;
+    use std::time::Duration;
+
+    /// XP handed out this test
+    #[derive(Resource, Default)]
+    struct Gains(Vec<XPGainEvent>);
+
+    fn collect_gains(mut gains: ResMut<Gains>, mut xp_events: MessageReader<XPGainEvent>) {
+        gains.0.extend(xp_events.read().cloned());
+    }
+
+    fn xp_app() -> App {
+        let mut app = App::new();
+        app.init_resource::<Time>()
+            .init_resource::<GlobalSpatialGrid>()
+            .init_resource::<Gains>()
+            .add_message::<DamageApplied>()
+            .add_message::<DeathEvent>()
+            .add_message::<XPGainEvent>()
+            .add_systems(
+                Update,
+                (damage_contribution_system, kill_xp_system, collect_gains).chain(),
+            );
+        app
+    }
+
+    fn step(app: &mut App, seconds: f32) {
+        app.world_mut()
+            .resource_mut::<Time>()
+            .advance_by(Duration::from_secs_f32(seconds));
+        app.update();
+    }
+
+    /// Spawn a unit at `position` and index it in the spatial grid
+    fn unit(app: &mut App, position: Vec3, team: u32) -> Entity {
+        let entity = app
+            .world_mut()
+            .spawn((
+                Transform::from_translation(position),
+                CollisionTeam(team),
+                Experience::default(),
+                CombatLog::default(),
+            ))
+            .id();
+        app.world_mut()
+            .resource_mut::<GlobalSpatialGrid>()
+            .grid
+            .insert(entity, position);
+        entity
+    }
+
+    fn hit(app: &mut App, attacker: Entity, target: Entity) {
+        app.world_mut().write_message(DamageApplied {
+            attacker,
+            target,
+            amount: 10.0,
+            absorbed: 0.0,
+            damage_type: DamageType::Physical,
+            is_critical: false,
+        });
+        step(app, 0.1);
+    }
+
+    fn die(app: &mut App, entity: Entity, killer: Option<Entity>) {
+        app.world_mut().write_message(DeathEvent { entity, killer });
+        step(app, 0.1);
+    }
+
+    /// XP each entity gained, with its source
+    fn gains_of(app: &App, entity: Entity) -> Vec<(u32, XPSource)> {
+        app.world()
+            .resource::<Gains>()
+            .0
+            .iter()
+            .filter(|gain| gain.entity == entity)
+            .map(|gain| (gain.amount, gain.source.clone()))
+            .collect()
+    }
+
+    #[test]
+    fn killer_earns_kill_xp() {
+        let mut app = xp_app();
+        let killer = unit(&mut app, Vec3::ZERO, 1);
+        let victim = unit(&mut app, Vec3::new(2.0, 0.0, 0.0), 2);
+        let value = xp_value(None, Some(&Experience::default())).round() as u32;
+
+        hit(&mut app, killer, victim);
+        die(&mut app, victim, Some(killer));
+
+        let gains = gains_of(&app, killer);
+        assert_eq!(gains.len(), 1);
+        assert!(
+            matches!(gains[0], (amount, XPSource::Kill(target)) if amount == value && target == victim)
+        );
+        assert!(gains_of(&app, victim).is_empty());
+    }
+
+    #[test]
+    fn recent_attackers_earn_assists() {
+        let mut app = xp_app();
+        let killer = unit(&mut app, Vec3::ZERO, 1);
+        // Far enough from the victim not to share in the XP as well
+        let helper = unit(&mut app, Vec3::new(50.0, 0.0, 0.0), 1);
+        let victim = unit(&mut app, Vec3::new(2.0, 0.0, 0.0), 2);
+
+        hit(&mut app, helper, victim);
+        step(&mut app, ASSIST_WINDOW - 1.0);
+        hit(&mut app, killer, victim);
+        die(&mut app, victim, Some(killer));
+
+        let gains = gains_of(&app, helper);
+        assert_eq!(gains.len(), 1);
+        assert!(matches!(gains[0].1, XPSource::Assist(target) if target == victim));
+        assert_eq!(app.world().get::<CombatLog>(helper).unwrap().assists, 1);
+        // The killing blow isn't also an assist
+        assert_eq!(gains_of(&app, killer).len(), 1);
+        assert_eq!(app.world().get::<CombatLog>(killer).unwrap().assists, 0);
+    }
+
+    #[test]
+    fn hits_older_than_the_assist_window_earn_nothing() {
+        let mut app = xp_app();
+        let killer = unit(&mut app, Vec3::ZERO, 1);
+        let helper = unit(&mut app, Vec3::new(50.0, 0.0, 0.0), 1);
+        let victim = unit(&mut app, Vec3::new(2.0, 0.0, 0.0), 2);
+
+        hit(&mut app, helper, victim);
+        step(&mut app, ASSIST_WINDOW + 1.0);
+        hit(&mut app, killer, victim);
+        die(&mut app, victim, Some(killer));
+
+        assert!(gains_of(&app, helper).is_empty());
+        assert_eq!(app.world().get::<CombatLog>(helper).unwrap().assists, 0);
+    }
+
+    #[test]
+    fn nearby_allies_share_the_kill() {
+        let mut app = xp_app();
+        let killer = unit(&mut app, Vec3::ZERO, 1);
+        let victim = unit(&mut app, Vec3::new(2.0, 0.0, 0.0), 2);
+        let near_ally = unit(&mut app, Vec3::new(2.0, 0.0, XP_SHARE_RADIUS - 3.0), 1);
+        let far_ally = unit(&mut app, Vec3::new(2.0, 0.0, XP_SHARE_RADIUS + 5.0), 1);
+        let bystander = unit(&mut app, Vec3::new(4.0, 0.0, 0.0), 3);
+
+        die(&mut app, victim, Some(killer));
+
+        let gains = gains_of(&app, near_ally);
+        assert_eq!(gains.len(), 1);
+        assert!(matches!(gains[0].1, XPSource::Shared(target) if target == victim));
+        assert!(gains_of(&app, far_ally).is_empty());
+        assert!(gains_of(&app, bystander).is_empty());
+    }
+
+    #[test]
+    fn the_victims_team_never_earns_xp() {
+        let mut app = xp_app();
+        // A frenzied or careless teammate lands the killing blow
+        let traitor = unit(&mut app, Vec3::ZERO, 2);
+        let victim = unit(&mut app, Vec3::new(2.0, 0.0, 0.0), 2);
+        let teammate = unit(&mut app, Vec3::new(4.0, 0.0, 0.0), 2);
+        let enemy = unit(&mut app, Vec3::new(50.0, 0.0, 0.0), 1);
+
+        hit(&mut app, teammate, victim);
+        hit(&mut app, enemy, victim);
+        hit(&mut app, traitor, victim);
+        die(&mut app, victim, Some(traitor));
+
+        assert!(gains_of(&app, traitor).is_empty());
+        assert!(gains_of(&app, teammate).is_empty());
+        assert_eq!(app.world().get::<CombatLog>(teammate).unwrap().assists, 0);
+        // Enemies who helped still get their assist
+        assert_eq!(gains_of(&app, enemy).len(), 1);
+    }
+}
@@ -0,0 +1,868 @@
+use crate::components::CollisionTeam;
+use crate::crowd_control::{CrowdControl, can_cast};
+use crate::sanity::Sanity;
+use crate::spatial::GlobalSpatialGrid;
+use crate::stats::{DamageType, Healed, Health};
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::Path;
+
+// ==============================================================================
+// ABILITY DEFINITIONS
+// ==============================================================================
+
+/// How an ability picks where it lands
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum AbilityTargeting {
+    /// Affects only the caster
+    SelfCast,
+    /// Affects a single unit within range
+    Unit,
+    /// Lands on a point within range without affecting units directly
+    Point,
+    /// Affects units within `radius` of a point; range 0 centres it on the caster
+    Area { radius: f32 },
+    /// Affects units in front of the caster, up to the ability's range
+    Cone { angle_degrees: f32 },
+}
+
+/// Which units an ability affects, relative to the caster's team
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum AbilityAffects {
+    Allies,
+    Enemies,
+    All,
+}
+
+/// Resources spent when an ability is activated
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct AbilityCost {
+    pub health: f32,
+    pub sanity: f32,
+}
+
+/// How a displacement effect moves units
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum Displacement {
+    /// Push affected units this far away from the target point
+    Push(f32),
+    /// Pull affected units this far towards the target point
+    Pull(f32),
+    /// Move the caster to the target point
+    Blink,
+}
+
+/// One building block of an ability; effects are applied in order
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum AbilityEffect {
+    Damage {
+        amount: f32,
+        damage_type: DamageType,
+    },
+    /// Blast around the target point; `falloff` is the fraction of full damage
+    /// lost per unit of distance from it
+    AreaDamage {
+        amount: f32,
+        damage_type: DamageType,
+        radius: f32,
+        falloff: f32,
+        friendly_fire: bool,
+        line_of_sight: bool,
+    },
+    /// Restore a flat amount plus a fraction of maximum health
+    Heal {
+        amount: f32,
+        fraction: f32,
+    },
+    /// A status effect, resolved by the combat crate
+    Status {
+        kind: StatusKind,
+        magnitude: f32,
+        duration: f32,
+    },
+    /// Spawn units from a unit template around the target point
+    Summon {
+        unit_type: String,
+        count: u32,
+    },
+    Displace(Displacement),
+}
+
+/// Status effects an ability can apply; `magnitude` is ignored by the ones
+/// without a strength
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum StatusKind {
+    AttackSpeed,
+    MovementSpeed,
+    DamageBoost,
+    ArmorBoost,
+    Regeneration,
+    Slow,
+    Stun,
+    Silence,
+    Blind,
+    Poison,
+    Burn,
+    Freeze,
+    Madness,
+    Corruption,
+    VoidTouch,
+    DeepCurse,
+}
+
+/// Data definition of an active ability
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct AbilityDefinition {
+    pub id: String,
+    pub name: String,
+    pub cost: AbilityCost,
+    /// Seconds before the ability can be used again, counted from activation
+    pub cooldown: f32,
+    /// Seconds between activation and the effects landing
+    pub cast_time: f32,
+    pub range: f32,
+    pub targeting: AbilityTargeting,
+    pub affects: AbilityAffects,
+    pub effects: Vec<AbilityEffect>,
+}
+
+/// Balance file bundled with the game
+pub const DEFAULT_ABILITIES: &str = include_str!("../balance/abilities.ron");
+
+/// Every ability definition in the game, keyed by id
+#[derive(Resource, Clone, Debug)]
+pub struct AbilityLibrary {
+    pub definitions: HashMap<String, AbilityDefinition>,
+}
+
+impl AbilityLibrary {
+    pub fn get(&self, id: &str) -> Option<&AbilityDefinition> {
+        self.definitions.get(id)
+    }
+
+    pub fn insert(&mut self, definition: AbilityDefinition) {
+        self.definitions.insert(definition.id.clone(), definition);
+    }
+
+    /// Parse a list of ability definitions from RON balance data
+    pub fn from_ron(source: &str) -> Result<Self, ron::error::SpannedError> {
+        let definitions: Vec<AbilityDefinition> = ron::from_str(source)?;
+        Ok(Self {
+            definitions: definitions
+                .into_iter()
+                .map(|definition| (definition.id.clone(), definition))
+                .collect(),
+        })
+    }
+
+    /// Load ability definitions from a RON balance file
+    pub fn load(path: impl AsRef<Path>) -> std::io::Result<Self> {
+        let source = std::fs::read_to_string(path)?;
+        Self::from_ron(&source)
+            .map_err(|error| std::io::Error::new(std::io::ErrorKind::InvalidData, error))
+    }
+}
+
+impl Default for AbilityLibrary {
+    fn default() -> Self {
+        Self::from_ron(DEFAULT_ABILITIES).expect("bundled ability definitions are valid")
+    }
+}
+
+// ==============================================================================
+// CASTERS
+// ==============================================================================
+
+#[derive(Clone, Debug)]
+pub struct AbilitySlot {
+    pub ability: String,
+    pub cooldown_remaining: f32,
+}
+
+/// An activated ability waiting for its cast time to pass
+#[derive(Clone, Debug)]
+pub struct AbilityCasting {
+    pub ability: String,
+    pub target: AbilityTarget,
+    pub remaining: f32,
+}
+
+/// Abilities an entity can use, their cooldowns and any cast in progress
+#[derive(Component, Clone, Debug, Default)]
+pub struct Abilities {
+    pub slots: Vec<AbilitySlot>,
+    pub casting: Option<AbilityCasting>,
+}
+
+impl Abilities {
+    pub fn new<'a>(abilities: impl IntoIterator<Item = &'a str>) -> Self {
+        Self {
+            slots: abilities
+                .into_iter()
+                .map(|ability| AbilitySlot {
+                    ability: ability.to_string(),
+                    cooldown_remaining: 0.0,
+                })
+                .collect(),
+            casting: None,
+        }
+    }
+
+    pub fn slot(&self, ability: &str) -> Option<&AbilitySlot> {
+        self.slots.iter().find(|slot| slot.ability == ability)
+    }
+
+    /// Whether the ability is known, off cooldown and nothing else is being cast
+    pub fn is_ready(&self, ability: &str) -> bool {
+        self.casting.is_none()
+            && self
+                .slot(ability)
+                .is_some_and(|slot| slot.cooldown_remaining <= 0.0)
+    }
+}
+
+// ==============================================================================
+// MESSAGES
+// ==============================================================================
+
+/// What an ability is aimed at
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum AbilityTarget {
+    /// The caster itself, or its position and facing
+    Caster,
+    Unit(Entity),
+    Point(Vec3),
+}
+
+/// Request to activate an ability; player input and AI both send this
+#[derive(Event, Clone, Debug)]
+pub struct UseAbility {
+    pub caster: Entity,
+    pub ability: String,
+    pub target: AbilityTarget,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AbilityFailure {
+    UnknownAbility,
+    OnCooldown,
+    AlreadyCasting,
+    /// Stunned or silenced
+    CannotCast,
+    InsufficientHealth,
+    InsufficientSanity,
+    InvalidTarget,
+    OutOfRange,
+    /// Stunned or silenced before the cast finished
+    Interrupted,
+}
+
+/// An ability could not be activated or its cast was interrupted
+#[derive(Event, Clone, Debug)]
+pub struct AbilityFailed {
+    pub caster: Entity,
+    pub ability: String,
+    pub reason: AbilityFailure,
+}
+
+/// A cast finished; each crate applies the effects it owns to `targets`
+#[derive(Event, Clone, Debug)]
+pub struct AbilityResolved {
+    pub caster: Entity,
+    pub ability: String,
+    /// Where the ability landed
+    pub point: Vec3,
+    /// Units affected, empty for point abilities
+    pub targets: Vec<Entity>,
+    pub effects: Vec<AbilityEffect>,
+}
+
+// ==============================================================================
+// SYSTEMS
+// ==============================================================================
+
+/// Whether `other` is affected by an ability from a caster on `caster_team`
+fn affects(affects: AbilityAffects, caster_team: Option<u32>, other: Option<u32>) -> bool {
+    let allied = caster_team.is_some() && caster_team == other;
+    match affects {
+        AbilityAffects::Allies => allied,
+        AbilityAffects::Enemies => !allied,
+        AbilityAffects::All => true,
+    }
+}
+
+/// Point an ability is aimed at, or why the target doesn't work
+fn aim(
+    definition: &AbilityDefinition,
+    caster: Entity,
+    caster_position: Vec3,
+    target: AbilityTarget,
+    units: &Query<(&Transform, &Health, Option<&CollisionTeam>)>,
+) -> Result<Vec3, AbilityFailure> {
+    let caster_team = units
+        .get(caster)
+        .ok()
+        .and_then(|(_, _, team)| team.map(|t| t.0));
+
+    let point = match (&definition.targeting, target) {
+        (AbilityTargeting::SelfCast, _) => return Ok(caster_position),
+        (AbilityTargeting::Unit, AbilityTarget::Unit(entity)) => {
+            let Ok((transform, health, team)) = units.get(entity) else {
+                return Err(AbilityFailure::InvalidTarget);
+            };
+            if health.is_dead() || !affects(definition.affects, caster_team, team.map(|t| t.0)) {
+                return Err(AbilityFailure::InvalidTarget);
+            }
+            transform.translation
+        }
+        (AbilityTargeting::Unit, _) => return Err(AbilityFailure::InvalidTarget),
+        (_, AbilityTarget::Unit(entity)) => units
+            .get(entity)
+            .map(|(transform, _, _)| transform.translation)
+            .map_err(|_| AbilityFailure::InvalidTarget)?,
+        (_, AbilityTarget::Point(point)) => point,
+        (AbilityTargeting::Point, AbilityTarget::Caster) => {
+            return Err(AbilityFailure::InvalidTarget);
+        }
+        (_, AbilityTarget::Caster) => caster_position,
+    };
+
+    // Self-centred areas ignore wherever they were aimed
+    if definition.range <= 0.0 && matches!(definition.targeting, AbilityTargeting::Area { .. }) {
+        return Ok(caster_position);
+    }
+    if matches!(definition.targeting, AbilityTargeting::Cone { .. }) {
+        return Ok(point);
+    }
+    if caster_position.distance(point) > definition.range {
+        return Err(AbilityFailure::OutOfRange);
+    }
+    Ok(point)
+}
+
+/// System to validate ability requests, pay their costs and start casting
+#[allow(clippy::type_complexity)]
+pub fn ability_activation_system(
+    library: Res<AbilityLibrary>,
+    mut requests: MessageReader<UseAbility>,
+    mut casters: Query<(
+        &Transform,
+        &mut Abilities,
+        Option<&CrowdControl>,
+        Option<&mut Sanity>,
+    )>,
+    mut units: ParamSet<(
+        Query<(&Transform, &Health, Option<&CollisionTeam>)>,
+        Query<&mut Health>,
+    )>,
+    mut failed_events: MessageWriter<AbilityFailed>,
+) {
+    for request in requests.read() {
+        let Ok((transform, mut abilities, control, sanity)) = casters.get_mut(request.caster)
+        else {
+            continue;
+        };
+        let fail = |reason| AbilityFailed {
+            caster: request.caster,
+            ability: request.ability.clone(),
+            reason,
+        };
+
+        let (Some(definition), Some(slot)) = (
+            library.get(&request.ability),
+            abilities.slot(&request.ability),
+        ) else {
+            failed_events.write(fail(AbilityFailure::UnknownAbility));
+            continue;
+        };
+        if abilities.casting.is_some() {
+            failed_events.write(fail(AbilityFailure::AlreadyCasting));
+            continue;
+        }
+        if slot.cooldown_remaining > 0.0 {
+            failed_events.write(fail(AbilityFailure::OnCooldown));
+            continue;
+        }
+        if !can_cast(control) {
+            failed_events.write(fail(AbilityFailure::CannotCast));
+            continue;
+        }
+
+        // Health costs can't be paid with the last of it
+        let cost = &definition.cost;
+        let health = units
+            .p0()
+            .get(request.caster)
+            .ok()
+            .map(|(_, h, _)| h.current);
+        if cost.health > 0.0 && health.is_none_or(|health| health <= cost.health) {
+            failed_events.write(fail(AbilityFailure::InsufficientHealth));
+            continue;
+        }
+        if cost.sanity > 0.0 && sanity.as_ref().is_none_or(|s| s.current < cost.sanity) {
+            failed_events.write(fail(AbilityFailure::InsufficientSanity));
+            continue;
+        }
+
+        if let Err(reason) = aim(
+            definition,
+            request.caster,
+            transform.translation,
+            request.target,
+            &units.p0(),
+        ) {
+            failed_events.write(fail(reason));
+            continue;
+        }
+
+        if let Ok(mut health) = units.p1().get_mut(request.caster) {
+            health.current -= cost.health;
+        }
+        if let Some(mut sanity) = sanity {
+            sanity.current -= cost.sanity;
+        }
+        if let Some(slot) = abilities
+            .slots
+            .iter_mut()
+            .find(|slot| slot.ability == request.ability)
+        {
+            slot.cooldown_remaining = definition.cooldown;
+        }
+        abilities.casting = Some(AbilityCasting {
+            ability: request.ability.clone(),
+            target: request.target,
+            remaining: definition.cast_time,
+        });
+    }
+}
+
+/// System to tick cooldowns and casts, and resolve casts that finish.
+///
+/// Casters stunned or silenced mid-cast lose the cast; the cooldown is spent.
+#[allow(clippy::type_complexity)]
+pub fn ability_casting_system(
+    time: Res<Time>,
+    library: Res<AbilityLibrary>,
+    spatial_grid: Res<GlobalSpatialGrid>,
+    mut casters: Query<(Entity, &Transform, &mut Abilities, Option<&CrowdControl>)>,
+    units: Query<(&Transform, &Health, Option<&CollisionTeam>)>,
+    mut resolved_events: MessageWriter<AbilityResolved>,
+    mut failed_events: MessageWriter<AbilityFailed>,
+) {
+    let dt = time.delta_seconds();
+
+    for (caster, transform, mut abilities, control) in casters.iter_mut() {
+        for slot in abilities.slots.iter_mut() {
+            slot.cooldown_remaining = (slot.cooldown_remaining - dt).max(0.0);
+        }
+
+        let Some(casting) = abilities.casting.as_mut() else {
+            continue;
+        };
+        if !can_cast(control) {
+            failed_events.write(AbilityFailed {
+                caster,
+                ability: casting.ability.clone(),
+                reason: AbilityFailure::Interrupted,
+            });
+            abilities.casting = None;
+            continue;
+        }
+        casting.remaining -= dt;
+        if casting.remaining > 0.0 {
+            continue;
+        }
+        let Some(casting) = abilities.casting.take() else {
+            continue;
+        };
+        let Some(definition) = library.get(&casting.ability) else {
+            continue;
+        };
+
+        let position = transform.translation;
+        let Ok(point) = aim(definition, caster, position, casting.target, &units) else {
+            // The target died or moved away during the cast
+            failed_events.write(AbilityFailed {
+                caster,
+                ability: casting.ability,
+                reason: AbilityFailure::InvalidTarget,
+            });
+            continue;
+        };
+
+        let caster_team = units
+            .get(caster)
+            .ok()
+            .and_then(|(_, _, team)| team.map(|t| t.0));
+        let affected = |entity: Entity| {
+            units.get(entity).ok().filter(|(_, health, team)| {
+                !health.is_dead() && affects(definition.affects, caster_team, team.map(|t| t.0))
+            })
+        };
+
+        let targets = match &definition.targeting {
+            AbilityTargeting::SelfCast => vec![caster],
+            AbilityTargeting::Unit => match casting.target {
+                AbilityTarget::Unit(entity) => vec![entity],
+                _ => Vec::new(),
+            },
+            AbilityTargeting::Point => Vec::new(),
+            AbilityTargeting::Area { radius } => spatial_grid
+                .grid
+                .query_radius(point, *radius)
+                .into_iter()
+                .filter(|entity| {
+                    affected(*entity).is_some_and(|(transform, _, _)| {
+                        transform.translation.distance(point) <= *radius
+                    })
+                })
+                .collect(),
+            AbilityTargeting::Cone { angle_degrees } => {
+                let facing = match casting.target {
+                    AbilityTarget::Caster => transform.forward().as_vec3(),
+                    _ => point - position,
+                };
+                let facing = Vec3::new(facing.x, 0.0, facing.z).normalize_or(Vec3::NEG_Z);
+                let half_angle = angle_degrees.to_radians() * 0.5;
+                spatial_grid
+                    .grid
+                    .query_radius(position, definition.range)
+                    .into_iter()
+                    .filter(|entity| *entity != caster)
+                    .filter(|entity| {
+                        affected(*entity).is_some_and(|(transform, _, _)| {
+                            let offset = transform.translation - position;
+                            let flat = Vec3::new(offset.x, 0.0, offset.z);
+                            offset.length() <= definition.range
+                                && (flat.length_squared() == 0.0
+                                    || flat.angle_between(facing) <= half_angle)
+                        })
+                    })
+                    .collect()
+            }
+        };
+
+        resolved_events.write(AbilityResolved {
+            caster,
+            ability: casting.ability,
+            point,
+            targets,
+            effects: definition.effects.clone(),
+        });
+    }
+}
+
+/// System to apply the healing and displacement of resolved abilities
+pub fn ability_effect_system(
+    mut resolved_events: MessageReader<AbilityResolved>,
+    mut health_query: Query<&mut Health>,
+    mut transform_query: Query<&mut Transform>,
+    mut healed_events: MessageWriter<Healed>,
+) {
+    for event in resolved_events.read() {
+        for effect in &event.effects {
+            match effect {
+                AbilityEffect::Heal { amount, fraction } => {
+                    for target in &event.targets {
+                        if let Ok(mut health) = health_query.get_mut(*target) {
+                            let before = health.current;
+                            let heal = amount + health.maximum * fraction;
+                            health.heal(heal);
+                            healed_events.write(Healed {
+                                healer: Some(event.caster),
+                                target: *target,
+                                amount: health.current - before,
+                            });
+                        }
+                    }
+                }
+                AbilityEffect::Displace(Displacement::Blink) => {
+                    if let Ok(mut transform) = transform_query.get_mut(event.caster) {
+                        transform.translation.x = event.point.x;
+                        transform.translation.z = event.point.z;
+                    }
+                }
+                AbilityEffect::Displace(Displacement::Push(distance)) => {
+                    displace(&mut transform_query, &event.targets, event.point, *distance);
+                }
+                AbilityEffect::Displace(Displacement::Pull(distance)) => {
+                    displace(
+                        &mut transform_query,
+                        &event.targets,
+                        event.point,
+                        -*distance,
+                    );
+                }
+                _ => {}
+            }
+        }
+    }
+}
+
+/// Move targets horizontally away from (or, when negative, towards) a point.
+///
+/// Pulls stop at the point rather than overshooting it.
+fn displace(
+    transform_query: &mut Query<&mut Transform>,
+    targets: &[Entity],
+    point: Vec3,
+    distance: f32,
+) {
+    for target in targets {
+        let Ok(mut transform) = transform_query.get_mut(*target) else {
+            continue;
+        };
+        let offset = Vec3::new(
+            transform.translation.x - point.x,
+            0.0,
+            transform.translation.z - point.z,
+        );
+        let moved = if distance < 0.0 {
+            offset.normalize_or_zero() * distance.max(-offset.length())
+        } else {
+            offset.normalize_or(Vec3::X) * distance
+        };
+        transform.translation += moved;
+    }
+}
+
+impl bevy::prelude::Message for UseAbility {}
+impl bevy::prelude::Message for AbilityFailed {}
+impl bevy::prelude::Message for AbilityResolved {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn ability_app() -> App {
+        let mut app = App::new();
+        app.init_resource::<Time>()
+            .init_resource::<AbilityLibrary>()
+            .insert_resource(GlobalSpatialGrid::new(10.0))
+            .add_message::<UseAbility>()
+            .add_message::<AbilityFailed>()
+            .add_message::<AbilityResolved>()
+            .add_message::<Healed>()
+            .add_systems(
+                Update,
+                (
+                    ability_activation_system,
+                    ability_casting_system,
+                    ability_effect_system,
+                )
+                    .chain(),
+            );
+        app
+    }
+
+    #[test]
+    fn bundled_abilities_load_from_balance_file() {
+        let library = AbilityLibrary::default();
+        assert_eq!(library.definitions.len(), 7);
+
+        let nova = library.get("blood_nova").expect("blood nova is defined");
+        assert_eq!(nova.cost.health, 20.0);
+        assert_eq!(nova.targeting, AbilityTargeting::Area { radius: 8.0 });
+        assert_eq!(
+            nova.effects[1],
+            AbilityEffect::Displace(Displacement::Push(4.0))
+        );
+        assert_eq!(
+            library.get("call_the_deep").unwrap().effects,
+            [AbilityEffect::Summon {
+                unit_type: "deep_acolyte".to_string(),
+                count: 2,
+            }]
+        );
+
+        assert!(AbilityLibrary::from_ron("[(id: \"broken\")]").is_err());
+    }
+
+    #[test]
+    fn misspelled_status_fails_to_load() {
+        let rally = |status: &str| {
+            format!(
+                "[(id: \"rally\", name: \"Rally\", cost: (health: 0.0, sanity: 0.0), \
+                 cooldown: 30.0, cast_time: 0.0, range: 0.0, targeting: Area(radius: 15.0), \
+                 affects: Allies, effects: [Status(kind: {status}, magnitude: 1.0, duration: 10.0)])]"
+            )
+        };
+
+        let library = AbilityLibrary::from_ron(&rally("DamageBoost")).unwrap();
+        assert_eq!(
+            library.get("rally").unwrap().effects,
+            [AbilityEffect::Status {
+                kind: StatusKind::DamageBoost,
+                magnitude: 1.0,
+                duration: 10.0,
+            }]
+        );
+        assert!(AbilityLibrary::from_ron(&rally("DamageBoots")).is_err());
+    }
+
+    fn step(app: &mut App, seconds: f32) {
+        app.world_mut()
+            .resource_mut::<Time>()
+            .advance_by(Duration::from_secs_f32(seconds));
+        app.update();
+    }
+
+    fn failures(app: &App) -> Vec<AbilityFailure> {
+        let messages = app.world().resource::<Messages<AbilityFailed>>();
+        messages
+            .iter_current_update_messages()
+            .map(|event| event.reason)
+            .collect()
+    }
+
+    #[test]
+    fn cast_time_cooldown_and_costs() {
+        let mut app = ability_app();
+        let caster = app
+            .world_mut()
+            .spawn((
+                Transform::default(),
+                Health::new(100.0),
+                Sanity::new(100.0),
+                CollisionTeam(1),
+                Abilities::new(["void_bolt"]),
+            ))
+            .id();
+        let mut wounded = Health::new(100.0);
+        wounded.current = 50.0;
+        let enemy = app
+            .world_mut()
+            .spawn((
+                Transform::from_xyz(5.0, 0.0, 0.0),
+                wounded,
+                CollisionTeam(2),
+            ))
+            .id();
+        let far_enemy = app
+            .world_mut()
+            .spawn((
+                Transform::from_xyz(50.0, 0.0, 0.0),
+                Health::new(100.0),
+                CollisionTeam(2),
+            ))
+            .id();
+
+        let cast = |app: &mut App, target| {
+            app.world_mut().write_message(UseAbility {
+                caster,
+                ability: "void_bolt".to_string(),
+                target: AbilityTarget::Unit(target),
+            });
+        };
+
+        cast(&mut app, far_enemy);
+        step(&mut app, 0.1);
+        assert_eq!(failures(&app), vec![AbilityFailure::OutOfRange]);
+
+        cast(&mut app, enemy);
+        step(&mut app, 0.1);
+        assert!(failures(&app).is_empty());
+        assert_eq!(app.world().get::<Sanity>(caster).unwrap().current, 95.0);
+        let resolved = app.world().resource::<Messages<AbilityResolved>>();
+        assert_eq!(resolved.iter_current_update_messages().count(), 0);
+
+        // Still on cooldown once the cast lands
+        step(&mut app, 0.5);
+        let resolved = app.world().resource::<Messages<AbilityResolved>>();
+        let event = resolved.iter_current_update_messages().next().unwrap();
+        assert_eq!(event.targets, vec![enemy]);
+        assert!(
+            !app.world()
+                .get::<Abilities>(caster)
+                .unwrap()
+                .is_ready("void_bolt")
+        );
+
+        cast(&mut app, enemy);
+        step(&mut app, 0.1);
+        assert_eq!(failures(&app), vec![AbilityFailure::OnCooldown]);
+
+        app.world_mut().entity_mut(caster).insert(CrowdControl {
+            silenced: true,
+            ..default()
+        });
+        step(&mut app, 10.0);
+        cast(&mut app, enemy);
+        step(&mut app, 0.1);
+        assert_eq!(failures(&app), vec![AbilityFailure::CannotCast]);
+    }
+
+    #[test]
+    fn area_heal_and_displacement() {
+        let mut app = ability_app();
+        let mut library = AbilityLibrary::default();
+        library.insert(AbilityDefinition {
+            id: "tremor".to_string(),
+            name: "Tremor".to_string(),
+            cost: AbilityCost::default(),
+            cooldown: 1.0,
+            cast_time: 0.0,
+            range: 0.0,
+            targeting: AbilityTargeting::Area { radius: 5.0 },
+            affects: AbilityAffects::Enemies,
+            effects: vec![AbilityEffect::Displace(Displacement::Push(3.0))],
+        });
+        app.insert_resource(library);
+
+        let caster = app
+            .world_mut()
+            .spawn((
+                Transform::default(),
+                Health::new(100.0),
+                CollisionTeam(1),
+                Abilities::new(["mend", "tremor"]),
+            ))
+            .id();
+        let mut ally_health = Health::new(100.0);
+        ally_health.current = 10.0;
+        let ally = app
+            .world_mut()
+            .spawn((
+                Transform::from_xyz(2.0, 0.0, 0.0),
+                ally_health,
+                CollisionTeam(1),
+            ))
+            .id();
+        let enemy = app
+            .world_mut()
+            .spawn((
+                Transform::from_xyz(0.0, 0.0, 3.0),
+                Health::new(100.0),
+                CollisionTeam(2),
+            ))
+            .id();
+        for (entity, position) in [
+            (caster, Vec3::ZERO),
+            (ally, Vec3::new(2.0, 0.0, 0.0)),
+            (enemy, Vec3::new(0.0, 0.0, 3.0)),
+        ] {
+            app.world_mut()
+                .resource_mut::<GlobalSpatialGrid>()
+                .grid
+                .insert(entity, position);
+        }
+
+        for ability in ["mend", "tremor"] {
+            app.world_mut().write_message(UseAbility {
+                caster,
+                ability: ability.to_string(),
+                target: AbilityTarget::Caster,
+            });
+            step(&mut app, 0.1);
+        }
+
+        assert_eq!(app.world().get::<Health>(ally).unwrap().current, 35.0);
+        assert_eq!(app.world().get::<Health>(enemy).unwrap().current, 100.0);
+        let pushed = app.world().get::<Transform>(enemy).unwrap().translation;
+        assert!((pushed.z - 6.0).abs() < 1e-4);
+        let ally_position = app.world().get::<Transform>(ally).unwrap().translation;
+        assert_eq!(ally_position, Vec3::new(2.0, 0.0, 0.0));
+    }
+}
@@ -1,6 +1,9 @@
 use crate::components::*;
-use crate::spatial::GlobalSpatialGrid;
+use crate::spatial::{GlobalSpatialGrid, ordered_pair};
+use crate::terrain::TerrainHeightfield;
+use bevy::ecs::system::SystemParam;
 use bevy::prelude::*;
+use std::collections::HashSet;
 // Use our physics Sphere to avoid name conflict
 use crate::components::Sphere as PhysicsSphere;
 
@@ -8,26 +11,48 @@ use crate::components::Sphere as PhysicsSphere;
 // COLLISION DETECTION SYSTEMS
 // ==============================================================================
 
-/// Broad phase collision detection using spatial partitioning
+/// Search radius used to gather broad phase candidates around each collider
+const BROAD_PHASE_SEARCH_RADIUS: f32 = 5.0;
+
+/// Broad phase collision detection using spatial partitioning.
+///
+/// Colliders are kept in the incremental `GlobalSpatialGrid`; only colliders
+/// whose transform changed are re-bucketed, instead of rebuilding the grid.
+/// Pairs rejected by layer/mask or team filtering are never emitted.
+#[allow(clippy::type_complexity)]
 pub fn broad_phase_collision_system(
     mut spatial_grid: ResMut<GlobalSpatialGrid>,
-    query: Query<(Entity, &Transform), With<AABB>>,
+    moved_query: Query<
+        (Entity, &Transform),
+        (Or<(With<AABB>, With<PhysicsSphere>)>, Changed<Transform>),
+    >,
+    query: Query<
+        (Entity, &Transform, Option<&AABB>, Option<&PhysicsSphere>),
+        Or<(With<AABB>, With<PhysicsSphere>)>,
+    >,
+    filter_query: CollisionFilterQuery,
     mut collision_pairs: ResMut<BroadPhaseCollisionPairs>,
 ) {
     collision_pairs.clear();
 
-    // Update spatial grid with current positions
-    spatial_grid.grid.clear();
-    for (entity, transform) in query.iter() {
-        spatial_grid.grid.insert(entity, transform.translation);
+    // Keep colliders indexed even if they have no SpatialData of their own
+    for (entity, transform) in moved_query.iter() {
+        spatial_grid.grid.update(entity, transform.translation);
     }
 
     // Find potential collision pairs using spatial grid
-    for (entity, transform) in query.iter() {
-        let nearby_entities = spatial_grid.grid.query_range(transform.translation, 5.0); // 5 unit search radius
+    for (entity, transform, aabb, sphere) in query.iter() {
+        let extent = aabb
+            .map(|aabb| aabb.half_extents.length())
+            .or(sphere.map(|sphere| sphere.radius + sphere.center_offset.length()))
+            .unwrap_or(0.0);
+        let search_radius = BROAD_PHASE_SEARCH_RADIUS.max(extent * 2.0);
+        let nearby_entities = spatial_grid
+            .grid
+            .query_range(transform.translation, search_radius);
 
         for nearby_entity in nearby_entities {
-            if entity != nearby_entity {
+            if entity != nearby_entity && pair_passes_filter(&filter_query, entity, nearby_entity) {
                 collision_pairs.add_pair(entity, nearby_entity);
             }
         }
@@ -38,9 +63,14 @@ pub fn broad_phase_collision_system(
 pub fn aabb_collision_system(
     collision_pairs: Res<BroadPhaseCollisionPairs>,
     aabb_query: Query<(&Transform, &AABB)>,
+    filter_query: CollisionFilterQuery,
     mut collision_events: MessageWriter<CollisionEvent>,
 ) {
     for &(entity_a, entity_b) in &collision_pairs.pairs {
+        if !pair_passes_filter(&filter_query, entity_a, entity_b) {
+            continue;
+        }
+
         if let (Ok((transform_a, aabb_a)), Ok((transform_b, aabb_b))) =
             (aabb_query.get(entity_a), aabb_query.get(entity_b))
         {
@@ -61,9 +91,14 @@ pub fn aabb_collision_system(
 pub fn sphere_collision_system(
     collision_pairs: Res<BroadPhaseCollisionPairs>,
     sphere_query: Query<(&Transform, &PhysicsSphere)>,
+    filter_query: CollisionFilterQuery,
     mut collision_events: MessageWriter<CollisionEvent>,
 ) {
     for &(entity_a, entity_b) in &collision_pairs.pairs {
+        if !pair_passes_filter(&filter_query, entity_a, entity_b) {
+            continue;
+        }
+
         if let (Ok((transform_a, sphere_a)), Ok((transform_b, sphere_b))) =
             (sphere_query.get(entity_a), sphere_query.get(entity_b))
         {
@@ -76,7 +111,7 @@ pub fn sphere_collision_system(
                     entity_a,
                     entity_b,
                     collision_type: CollisionType::Sphere,
-                    contact_point: center_a + direction.normalize() * sphere_a.radius,
+                    contact_point: center_a + direction.normalize_or_zero() * sphere_a.radius,
                     normal: direction.normalize_or_zero(),
                 });
             }
@@ -85,52 +120,120 @@ pub fn sphere_collision_system(
 }
 
 /// Sensor trigger detection system
+///
+/// Overlaps are tracked across frames so `TriggerEvent` fires once with
+/// `entered: true` when something enters a sensor and once with
+/// `entered: false` when it leaves (or either entity is gone).
 pub fn sensor_system(
     collision_pairs: Res<BroadPhaseCollisionPairs>,
     sensor_query: Query<(&Transform, &PhysicsSphere, &Sensor), With<Sensor>>,
     entity_query: Query<&Transform, Without<Sensor>>,
+    filter_query: CollisionFilterQuery,
+    mut contacts: ResMut<ActiveContacts>,
     mut trigger_events: MessageWriter<TriggerEvent>,
 ) {
-    for &(sensor_entity, other_entity) in &collision_pairs.pairs {
-        // Check if one entity is a sensor
-        if let (Ok((sensor_transform, sensor_sphere, sensor)), Ok(other_transform)) = (
+    let mut current = HashSet::new();
+
+    for &(entity_a, entity_b) in &collision_pairs.pairs {
+        // Either side of the pair may be the sensor
+        let (sensor_entity, other_entity) = if sensor_query.contains(entity_a) {
+            (entity_a, entity_b)
+        } else {
+            (entity_b, entity_a)
+        };
+
+        let (Ok((sensor_transform, sensor_sphere, sensor)), Ok(other_transform)) = (
             sensor_query.get(sensor_entity),
             entity_query.get(other_entity),
-        ) {
-            if sensor.is_active
-                && sensor_sphere.overlaps(
-                    sensor_transform.translation,
-                    &PhysicsSphere::new(0.1),
-                    other_transform.translation,
-                )
-            {
-                trigger_events.write(TriggerEvent {
-                    sensor_entity,
-                    triggered_by: other_entity,
-                    entered: true,
-                });
-            }
+        ) else {
+            continue;
+        };
+
+        if !sensor_passes_filter(&filter_query, sensor_entity, other_entity) {
+            continue;
         }
-        // Check reverse (other entity might be the sensor)
-        else if let (Ok((sensor_transform, sensor_sphere, sensor)), Ok(other_transform)) = (
-            sensor_query.get(other_entity),
-            entity_query.get(sensor_entity),
-        ) {
-            if sensor.is_active
-                && sensor_sphere.overlaps(
-                    sensor_transform.translation,
-                    &PhysicsSphere::new(0.1),
-                    other_transform.translation,
-                )
-            {
-                trigger_events.write(TriggerEvent {
-                    sensor_entity: other_entity,
-                    triggered_by: sensor_entity,
-                    entered: true,
-                });
-            }
+
+        if sensor.is_active
+            && sensor_sphere.overlaps(
+                sensor_transform.translation,
+                &PhysicsSphere::new(0.1),
+                other_transform.translation,
+            )
+        {
+            current.insert((sensor_entity, other_entity));
         }
     }
+
+    for &(sensor_entity, triggered_by) in current.difference(&contacts.triggers) {
+        trigger_events.write(TriggerEvent {
+            sensor_entity,
+            triggered_by,
+            entered: true,
+        });
+    }
+
+    for &(sensor_entity, triggered_by) in contacts.triggers.difference(&current) {
+        trigger_events.write(TriggerEvent {
+            sensor_entity,
+            triggered_by,
+            entered: false,
+        });
+    }
+
+    contacts.triggers = current;
+}
+
+/// Turn per-frame `CollisionEvent` overlaps into persistent contacts.
+///
+/// Each ordered pair produces `CollisionStarted` on the first overlapping
+/// frame, `CollisionStay` while it keeps overlapping and `CollisionEnded`
+/// once it separates, no matter how many narrow phase tests reported it.
+pub fn contact_tracking_system(
+    mut collision_events: MessageReader<CollisionEvent>,
+    mut contacts: ResMut<ActiveContacts>,
+    mut started_events: MessageWriter<CollisionStarted>,
+    mut stay_events: MessageWriter<CollisionStay>,
+    mut ended_events: MessageWriter<CollisionEnded>,
+) {
+    let mut current = HashSet::new();
+
+    for event in collision_events.read() {
+        if event.entity_a == event.entity_b {
+            continue;
+        }
+
+        let pair = ordered_pair(event.entity_a, event.entity_b);
+        if !current.insert(pair) {
+            continue; // Already reported by another narrow phase test
+        }
+
+        // Keep the normal pointing from pair.0 to pair.1
+        let normal = if pair.0 == event.entity_a {
+            event.normal
+        } else {
+            -event.normal
+        };
+
+        let contact = ContactData {
+            entity_a: pair.0,
+            entity_b: pair.1,
+            collision_type: event.collision_type.clone(),
+            contact_point: event.contact_point,
+            normal,
+        };
+
+        if contacts.collisions.contains(&pair) {
+            stay_events.write(CollisionStay(contact));
+        } else {
+            started_events.write(CollisionStarted(contact));
+        }
+    }
+
+    for &(entity_a, entity_b) in contacts.collisions.difference(&current) {
+        ended_events.write(CollisionEnded { entity_a, entity_b });
+    }
+
+    contacts.collisions = current;
 }
 
 /// Collision response system for physics-based collision resolution
@@ -200,40 +303,478 @@ pub fn collision_response_system(
 /// Raycast system for line-of-sight and projectile collision
 pub fn raycast_system(
     mut raycast_events: MessageReader<RaycastEvent>,
-    obstacle_query: Query<(&Transform, &AABB), With<Obstacle>>,
+    raycast: PhysicsRaycast,
     mut raycast_results: MessageWriter<RaycastResultEvent>,
 ) {
     for raycast_event in raycast_events.read() {
-        let mut hit_found = false;
-        let mut closest_hit = RaycastHit {
-            entity: Entity::PLACEHOLDER,
-            distance: f32::INFINITY,
-            point: Vec3::ZERO,
-            normal: Vec3::ZERO,
-        };
+        let ray = RayQuery::new(
+            raycast_event.origin,
+            raycast_event.direction,
+            raycast_event.max_distance,
+        )
+        .with_layers(raycast_event.layer_mask);
+        let hits = raycast.cast(&ray, |_| true);
+
+        raycast_results.write(RaycastResultEvent {
+            ray_id: raycast_event.ray_id,
+            hit: hits.first().cloned(),
+            hits,
+        });
+    }
+}
+
+// ==============================================================================
+// RAYCAST QUERIES
+// ==============================================================================
+
+/// Upper bound for ray length so unbounded rays stay cheap to traverse
+const MAX_RAY_DISTANCE: f32 = 10_000.0;
+
+/// Height above an entity's origin used for line-of-sight rays
+pub const LINE_OF_SIGHT_EYE_HEIGHT: f32 = 1.0;
+
+/// Layers that block sight between two entities. Buildings always block;
+/// terrain (cliffs and ridges) only blocks when neither end is airborne.
+pub fn sight_blocking_layers(viewer_airborne: bool, target_airborne: bool) -> u32 {
+    if viewer_airborne || target_airborne {
+        CollisionMask::BUILDINGS
+    } else {
+        CollisionMask::BUILDINGS | CollisionMask::TERRAIN
+    }
+}
+
+/// A ray to test against colliders and terrain
+#[derive(Clone, Debug)]
+pub struct RayQuery {
+    pub origin: Vec3,
+    pub direction: Vec3,
+    pub max_distance: f32,
+    /// Only colliders whose layer intersects this mask are tested; terrain is
+    /// tested when the mask includes `CollisionMask::TERRAIN`
+    pub layer_mask: u32,
+}
+
+impl RayQuery {
+    pub fn new(origin: Vec3, direction: Vec3, max_distance: f32) -> Self {
+        Self {
+            origin,
+            direction: direction.normalize_or_zero(),
+            max_distance: max_distance.min(MAX_RAY_DISTANCE),
+            layer_mask: CollisionMask::ALL,
+        }
+    }
+
+    /// Ray from `from` that ends exactly at `to`
+    pub fn between(from: Vec3, to: Vec3) -> Self {
+        Self::new(from, to - from, from.distance(to))
+    }
+
+    pub fn with_layers(mut self, layer_mask: u32) -> Self {
+        self.layer_mask = layer_mask;
+        self
+    }
+}
+
+/// Synchronous raycasts against spheres, AABB colliders and the terrain
+/// heightfield, usable directly as a system parameter.
+///
+/// Colliders without a `CollisionMask` count as buildings when marked
+/// `Obstacle` and as units otherwise.
+#[derive(SystemParam)]
+pub struct PhysicsRaycast<'w, 's> {
+    spatial_grid: Res<'w, GlobalSpatialGrid>,
+    heightfield: Option<Res<'w, TerrainHeightfield>>,
+    extents: Option<Res<'w, ColliderExtents>>,
+    colliders: Query<
+        'w,
+        's,
+        (
+            &'static Transform,
+            Option<&'static AABB>,
+            Option<&'static PhysicsSphere>,
+            Option<&'static CollisionMask>,
+            Has<Obstacle>,
+        ),
+    >,
+}
+
+impl PhysicsRaycast<'_, '_> {
+    /// All hits along the ray, sorted by distance. `filter` can reject
+    /// entities (e.g. the caster); terrain hits are never filtered.
+    pub fn cast(&self, ray: &RayQuery, mut filter: impl FnMut(Entity) -> bool) -> Vec<RaycastHit> {
+        let mut hits = Vec::new();
+        if ray.direction == Vec3::ZERO || ray.max_distance <= 0.0 {
+            return hits;
+        }
+
+        let end = ray.origin + ray.direction * ray.max_distance;
 
-        // Check intersection with all obstacles
-        for (transform, aabb) in obstacle_query.iter() {
-            if let Some(hit) = ray_aabb_intersection(
-                raycast_event.origin,
-                raycast_event.direction,
-                transform.translation,
-                aabb,
-            ) {
-                if hit.distance < closest_hit.distance {
-                    closest_hit = hit;
-                    hit_found = true;
+        for entity in self.ray_candidates(ray) {
+            let Ok((transform, aabb, sphere, mask, is_obstacle)) = self.colliders.get(entity)
+            else {
+                continue;
+            };
+
+            let layer = match mask {
+                Some(mask) => mask.layer,
+                None if is_obstacle => CollisionMask::BUILDINGS,
+                None => CollisionMask::UNITS,
+            };
+            if layer & ray.layer_mask == 0 || !filter(entity) {
+                continue;
+            }
+
+            let hit = if let Some(aabb) = aabb {
+                sweep_sphere_aabb(ray.origin, end, 0.0, transform.translation, aabb)
+            } else if let Some(sphere) = sphere {
+                sweep_sphere_sphere(
+                    ray.origin,
+                    end,
+                    0.0,
+                    transform.translation + sphere.center_offset,
+                    sphere.radius,
+                )
+            } else {
+                None
+            };
+
+            if let Some(hit) = hit {
+                hits.push(RaycastHit { entity, ..hit });
+            }
+        }
+
+        if ray.layer_mask & CollisionMask::TERRAIN != 0
+            && let Some(terrain) = &self.heightfield
+            && let Some((distance, point, normal)) =
+                terrain.raycast(ray.origin, ray.direction, ray.max_distance)
+        {
+            hits.push(RaycastHit {
+                entity: terrain.entity.unwrap_or(Entity::PLACEHOLDER),
+                distance,
+                point,
+                normal,
+            });
+        }
+
+        hits.sort_by(|a, b| a.distance.total_cmp(&b.distance));
+        hits
+    }
+
+    /// Closest hit along the ray
+    pub fn cast_first(
+        &self,
+        ray: &RayQuery,
+        filter: impl FnMut(Entity) -> bool,
+    ) -> Option<RaycastHit> {
+        self.cast(ray, filter).into_iter().next()
+    }
+
+    /// Whether anything on `layer_mask` lies between two points. Rays are
+    /// raised by `LINE_OF_SIGHT_EYE_HEIGHT` and `ignore` (usually the
+    /// observer and target) never blocks.
+    pub fn line_of_sight(&self, from: Vec3, to: Vec3, layer_mask: u32, ignore: &[Entity]) -> bool {
+        let eye = Vec3::Y * LINE_OF_SIGHT_EYE_HEIGHT;
+        let ray = RayQuery::between(from + eye, to + eye).with_layers(layer_mask);
+        self.cast_first(&ray, |entity| !ignore.contains(&entity))
+            .is_none()
+    }
+
+    /// Entities in the cells the ray crosses plus enough neighbours to reach
+    /// the largest collider, so colliders whose centre sits off the ray are
+    /// still tested
+    fn ray_candidates(&self, ray: &RayQuery) -> HashSet<Entity> {
+        let grid = &self.spatial_grid.grid;
+        let max_radius = self
+            .extents
+            .as_ref()
+            .map_or(0.0, |extents| extents.max_radius);
+        let reach = ((max_radius / grid.cell_size).ceil() as i32).max(1);
+        let mut candidates = HashSet::new();
+
+        for (cell_x, cell_z) in grid.ray_cells(ray.origin, ray.direction, ray.max_distance) {
+            for dx in -reach..=reach {
+                for dz in -reach..=reach {
+                    candidates.extend(grid.query_cell(cell_x + dx, cell_z + dz));
                 }
             }
         }
 
-        raycast_results.write(RaycastResultEvent {
-            ray_id: raycast_event.ray_id,
-            hit: if hit_found { Some(closest_hit) } else { None },
+        candidates
+    }
+}
+
+/// How far any collider reaches horizontally from its indexed position.
+/// Grows as larger colliders appear and never shrinks.
+#[derive(Resource, Default, Clone, Debug)]
+pub struct ColliderExtents {
+    pub max_radius: f32,
+}
+
+/// Track the largest collider so grid searches can widen to reach it
+#[allow(clippy::type_complexity)]
+pub fn collider_extents_system(
+    mut extents: ResMut<ColliderExtents>,
+    query: Query<
+        (Option<&AABB>, Option<&PhysicsSphere>),
+        Or<(Changed<AABB>, Changed<PhysicsSphere>)>,
+    >,
+) {
+    for (aabb, sphere) in query.iter() {
+        let aabb_radius = aabb.map_or(0.0, |aabb| {
+            aabb.center_offset.xz().length() + aabb.half_extents.xz().length()
         });
+        let sphere_radius = sphere.map_or(0.0, |sphere| {
+            sphere.center_offset.xz().length() + sphere.radius
+        });
+        let radius = aabb_radius.max(sphere_radius);
+        if radius > extents.max_radius {
+            extents.max_radius = radius;
+        }
+    }
+}
+
+// ==============================================================================
+// CONTINUOUS COLLISION
+// ==============================================================================
+
+/// Least extra search distance around a sweep, for colliders registered
+/// before `ColliderExtents` has seen them
+const SWEEP_CANDIDATE_MARGIN: f32 = 2.0;
+
+/// Radius used for indexed entities without an `AABB` or `Sphere`
+pub const DEFAULT_SWEEP_TARGET_RADIUS: f32 = 0.5;
+
+/// Collider shapes a sweep can be tested against
+pub type SweepTargetQuery<'w, 's, F = ()> = Query<
+    'w,
+    's,
+    (
+        &'static Transform,
+        Option<&'static AABB>,
+        Option<&'static PhysicsSphere>,
+    ),
+    F,
+>;
+
+/// Record where every continuous collider ended the frame
+pub fn continuous_collision_tracking_system(
+    mut query: Query<(&Transform, &mut ContinuousCollision)>,
+) {
+    for (transform, mut ccd) in query.iter_mut() {
+        ccd.previous_position = Some(transform.translation);
     }
 }
 
+/// Sweep a sphere from `start` to `end` against indexed colliders.
+///
+/// Candidates come from the spatial grid, searched wide enough to reach the
+/// largest collider in `extents`; `filter` can reject entities (owner, own
+/// team, layers). Hits are sorted by distance along the sweep, with `point`
+/// on the target's surface and `normal` facing the mover.
+pub fn sweep_sphere<F: bevy::ecs::query::QueryFilter>(
+    spatial_grid: &GlobalSpatialGrid,
+    extents: Option<&ColliderExtents>,
+    start: Vec3,
+    end: Vec3,
+    radius: f32,
+    targets: &SweepTargetQuery<F>,
+    mut filter: impl FnMut(Entity) -> bool,
+) -> Vec<RaycastHit> {
+    let half_length = start.distance(end) * 0.5;
+    let margin = extents.map_or(SWEEP_CANDIDATE_MARGIN, |extents| {
+        extents.max_radius.max(SWEEP_CANDIDATE_MARGIN)
+    });
+    let candidates = spatial_grid
+        .grid
+        .query_range((start + end) * 0.5, half_length + radius + margin);
+
+    let mut hits: Vec<RaycastHit> = candidates
+        .into_iter()
+        .filter(|&entity| filter(entity))
+        .filter_map(|entity| {
+            let (transform, aabb, sphere) = targets.get(entity).ok()?;
+            let hit = if let Some(aabb) = aabb {
+                sweep_sphere_aabb(start, end, radius, transform.translation, aabb)
+            } else {
+                let (target_radius, offset) = sphere
+                    .map_or((DEFAULT_SWEEP_TARGET_RADIUS, Vec3::ZERO), |sphere| {
+                        (sphere.radius, sphere.center_offset)
+                    });
+                sweep_sphere_sphere(
+                    start,
+                    end,
+                    radius,
+                    transform.translation + offset,
+                    target_radius,
+                )
+            };
+            hit.map(|hit| RaycastHit { entity, ..hit })
+        })
+        .collect();
+
+    hits.sort_by(|a, b| a.distance.total_cmp(&b.distance));
+    hits
+}
+
+/// Earliest time of impact of a moving sphere against a static sphere
+pub fn sweep_sphere_sphere(
+    start: Vec3,
+    end: Vec3,
+    radius: f32,
+    target_center: Vec3,
+    target_radius: f32,
+) -> Option<RaycastHit> {
+    let combined = radius + target_radius;
+    let delta = end - start;
+    let length = delta.length();
+    let to_start = start - target_center;
+
+    let distance = if to_start.length_squared() <= combined * combined {
+        0.0 // Already overlapping at the start of the sweep
+    } else if length <= f32::EPSILON {
+        return None;
+    } else {
+        // Solve |to_start + dir * t| = combined for the smallest t in [0, length]
+        let direction = delta / length;
+        let b = to_start.dot(direction);
+        let c = to_start.length_squared() - combined * combined;
+        let discriminant = b * b - c;
+        if b > 0.0 || discriminant < 0.0 {
+            return None;
+        }
+        let t = -b - discriminant.sqrt();
+        if t > length {
+            return None;
+        }
+        t
+    };
+
+    let center = if length > f32::EPSILON {
+        start + delta * (distance / length)
+    } else {
+        start
+    };
+    let normal = (center - target_center).normalize_or_zero();
+
+    Some(RaycastHit {
+        entity: Entity::PLACEHOLDER,
+        distance,
+        point: target_center + normal * target_radius,
+        normal,
+    })
+}
+
+/// Earliest time of impact of a moving sphere against a static AABB.
+///
+/// The box is inflated by the sphere radius (Minkowski sum), which is exact on
+/// faces and slightly conservative at edges and corners.
+pub fn sweep_sphere_aabb(
+    start: Vec3,
+    end: Vec3,
+    radius: f32,
+    aabb_center: Vec3,
+    aabb: &AABB,
+) -> Option<RaycastHit> {
+    let inflated = AABB {
+        half_extents: aabb.half_extents + Vec3::splat(radius),
+        center_offset: aabb.center_offset,
+    };
+    let delta = end - start;
+    let length = delta.length();
+
+    let hit = if length <= f32::EPSILON {
+        let (min, max) = inflated.get_bounds(aabb_center);
+        if start.cmplt(min).any() || start.cmpgt(max).any() {
+            return None;
+        }
+        RaycastHit {
+            entity: Entity::PLACEHOLDER,
+            distance: 0.0,
+            point: start,
+            normal: calculate_aabb_normal(start, aabb_center + aabb.center_offset, aabb),
+        }
+    } else {
+        let hit = ray_aabb_intersection(start, delta / length, aabb_center, &inflated)?;
+        if hit.distance > length {
+            return None;
+        }
+        hit
+    };
+
+    Some(RaycastHit {
+        point: hit.point - hit.normal * radius,
+        ..hit
+    })
+}
+
+// ==============================================================================
+// COLLISION FILTERING
+// ==============================================================================
+
+/// Layer/mask and team data used to filter collision pairs
+pub type CollisionFilterQuery<'w, 's> = Query<
+    'w,
+    's,
+    (
+        Option<&'static CollisionMask>,
+        Option<&'static CollisionTeam>,
+    ),
+>;
+
+/// Check whether two colliders may interact given their layers and teams.
+///
+/// Colliders without a `CollisionMask` use the default (unit layer, all masks).
+pub fn should_collide(
+    mask_a: &CollisionMask,
+    team_a: Option<&CollisionTeam>,
+    mask_b: &CollisionMask,
+    team_b: Option<&CollisionTeam>,
+) -> bool {
+    if !mask_a.interacts_with(mask_b) {
+        return false;
+    }
+
+    // Projectiles pass through their own team
+    let involves_projectile = (mask_a.layer | mask_b.layer) & CollisionMask::PROJECTILES != 0;
+    !(involves_projectile && team_a.is_some() && team_a == team_b)
+}
+
+fn pair_passes_filter(
+    filter_query: &CollisionFilterQuery,
+    entity_a: Entity,
+    entity_b: Entity,
+) -> bool {
+    let (mask_a, team_a) = filter_query.get(entity_a).unwrap_or((None, None));
+    let (mask_b, team_b) = filter_query.get(entity_b).unwrap_or((None, None));
+    let default_mask = CollisionMask::default();
+
+    should_collide(
+        mask_a.unwrap_or(&default_mask),
+        team_a,
+        mask_b.unwrap_or(&default_mask),
+        team_b,
+    )
+}
+
+/// Sensors without an explicit mask fall back to `CollisionMask::sensor()`,
+/// and never trigger on terrain regardless of their mask
+fn sensor_passes_filter(
+    filter_query: &CollisionFilterQuery,
+    sensor_entity: Entity,
+    other_entity: Entity,
+) -> bool {
+    let (sensor_mask, sensor_team) = filter_query.get(sensor_entity).unwrap_or((None, None));
+    let (other_mask, other_team) = filter_query.get(other_entity).unwrap_or((None, None));
+    let sensor_mask = sensor_mask.cloned().unwrap_or_else(CollisionMask::sensor);
+    let other_mask = other_mask.cloned().unwrap_or_default();
+
+    if other_mask.layer & !CollisionMask::TERRAIN == 0 {
+        return false;
+    }
+
+    should_collide(&sensor_mask, sensor_team, &other_mask, other_team)
+}
+
 // ==============================================================================
 // COLLISION EVENTS AND TYPES
 // ==============================================================================
@@ -247,6 +788,32 @@ pub struct CollisionEvent {
     pub normal: Vec3,
 }
 
+/// Contact details shared by the persistent collision messages.
+/// Pairs are ordered by entity index and the normal points from a to b.
+#[derive(Clone, Debug)]
+pub struct ContactData {
+    pub entity_a: Entity,
+    pub entity_b: Entity,
+    pub collision_type: CollisionType,
+    pub contact_point: Vec3,
+    pub normal: Vec3,
+}
+
+/// Two colliders started touching this frame
+#[derive(Event, Clone, Debug)]
+pub struct CollisionStarted(pub ContactData);
+
+/// Two colliders are still touching
+#[derive(Event, Clone, Debug)]
+pub struct CollisionStay(pub ContactData);
+
+/// Two colliders stopped touching (or one of them was removed)
+#[derive(Event, Clone, Debug)]
+pub struct CollisionEnded {
+    pub entity_a: Entity,
+    pub entity_b: Entity,
+}
+
 #[derive(Event)]
 pub struct TriggerEvent {
     pub sensor_entity: Entity,
@@ -254,18 +821,38 @@ pub struct TriggerEvent {
     pub entered: bool, // true for enter, false for exit
 }
 
+/// Contacts that were overlapping on the previous frame
+#[derive(Resource, Default, Debug)]
+pub struct ActiveContacts {
+    /// Ordered collider pairs
+    pub collisions: HashSet<(Entity, Entity)>,
+    /// (sensor, triggered_by) pairs
+    pub triggers: HashSet<(Entity, Entity)>,
+}
+
+impl ActiveContacts {
+    pub fn is_touching(&self, entity_a: Entity, entity_b: Entity) -> bool {
+        self.collisions.contains(&ordered_pair(entity_a, entity_b))
+    }
+}
+
 #[derive(Event)]
 pub struct RaycastEvent {
     pub ray_id: u32,
     pub origin: Vec3,
     pub direction: Vec3,
     pub max_distance: f32,
+    /// Only colliders whose layer intersects this mask are tested
+    pub layer_mask: u32,
 }
 
 #[derive(Event)]
 pub struct RaycastResultEvent {
     pub ray_id: u32,
+    /// Closest hit
     pub hit: Option<RaycastHit>,
+    /// Every hit along the ray, sorted by distance
+    pub hits: Vec<RaycastHit>,
 }
 
 #[derive(Clone, Debug)]
@@ -357,6 +944,287 @@ fn calculate_aabb_normal(hit_point: Vec3, aabb_center: Vec3, _aabb: &AABB) -> Ve
     }
 }
 impl bevy::prelude::Message for CollisionEvent {}
+impl bevy::prelude::Message for CollisionStarted {}
+impl bevy::prelude::Message for CollisionStay {}
+impl bevy::prelude::Message for CollisionEnded {}
 impl bevy::prelude::Message for TriggerEvent {}
 impl bevy::prelude::Message for RaycastEvent {}
 impl bevy::prelude::Message for RaycastResultEvent {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy::ecs::system::SystemState;
+
+    fn entity(index: u32) -> Entity {
+        Entity::from_raw_u32(index).unwrap()
+    }
+
+    fn contact_app() -> App {
+        let mut app = App::new();
+        app.add_message::<CollisionEvent>()
+            .add_message::<CollisionStarted>()
+            .add_message::<CollisionStay>()
+            .add_message::<CollisionEnded>()
+            .init_resource::<ActiveContacts>()
+            .add_systems(Update, contact_tracking_system);
+        app
+    }
+
+    fn overlap(app: &mut App, entity_a: Entity, entity_b: Entity) {
+        app.world_mut().write_message(CollisionEvent {
+            entity_a,
+            entity_b,
+            collision_type: CollisionType::Sphere,
+            contact_point: Vec3::ZERO,
+            normal: Vec3::X,
+        });
+    }
+
+    fn count<M: Message>(app: &App) -> usize {
+        app.world()
+            .resource::<Messages<M>>()
+            .iter_current_update_messages()
+            .count()
+    }
+
+    #[test]
+    fn contacts_start_stay_and_end_once() {
+        let mut app = contact_app();
+        let (a, b) = (entity(1), entity(2));
+
+        // Both orderings in one frame count as a single contact
+        overlap(&mut app, a, b);
+        overlap(&mut app, b, a);
+        app.update();
+        assert_eq!(count::<CollisionStarted>(&app), 1);
+        assert!(app.world().resource::<ActiveContacts>().is_touching(b, a));
+
+        overlap(&mut app, b, a);
+        app.update();
+        assert_eq!(count::<CollisionStarted>(&app), 0);
+        assert_eq!(count::<CollisionStay>(&app), 1);
+
+        app.update();
+        assert_eq!(count::<CollisionEnded>(&app), 1);
+        assert!(!app.world().resource::<ActiveContacts>().is_touching(a, b));
+    }
+
+    #[test]
+    fn fast_sweep_hits_thin_targets() {
+        // 100 units in one step through a 1-unit box: a discrete check would miss it
+        let aabb = AABB::from_size(Vec3::ONE);
+        let hit = sweep_sphere_aabb(
+            Vec3::new(-50.0, 0.0, 0.0),
+            Vec3::new(50.0, 0.0, 0.0),
+            0.25,
+            Vec3::ZERO,
+            &aabb,
+        )
+        .expect("sweep should hit");
+        assert!((hit.distance - 49.25).abs() < 1e-4);
+        assert_eq!(hit.normal, Vec3::NEG_X);
+        assert!((hit.point.x + 0.5).abs() < 1e-4);
+
+        let hit = sweep_sphere_sphere(
+            Vec3::new(0.0, 0.0, -20.0),
+            Vec3::new(0.0, 0.0, 20.0),
+            0.5,
+            Vec3::ZERO,
+            0.5,
+        )
+        .expect("sweep should hit");
+        assert!((hit.distance - 19.0).abs() < 1e-4);
+        assert_eq!(hit.normal, Vec3::NEG_Z);
+
+        assert!(
+            sweep_sphere_sphere(
+                Vec3::new(0.0, 0.0, -20.0),
+                Vec3::new(0.0, 0.0, -5.0),
+                0.5,
+                Vec3::ZERO,
+                0.5
+            )
+            .is_none()
+        );
+    }
+
+    #[test]
+    fn projectiles_skip_own_team() {
+        let projectile = CollisionMask::projectile();
+        let unit = CollisionMask::unit();
+
+        assert!(!should_collide(
+            &projectile,
+            Some(&CollisionTeam(1)),
+            &unit,
+            Some(&CollisionTeam(1)),
+        ));
+        assert!(should_collide(
+            &projectile,
+            Some(&CollisionTeam(1)),
+            &unit,
+            Some(&CollisionTeam(2)),
+        ));
+        // Same-team units still collide with each other
+        assert!(should_collide(
+            &unit,
+            Some(&CollisionTeam(1)),
+            &unit,
+            Some(&CollisionTeam(1)),
+        ));
+    }
+
+    #[test]
+    fn layer_masks_are_symmetric() {
+        assert!(!should_collide(
+            &CollisionMask::sensor(),
+            None,
+            &CollisionMask::terrain(),
+            None
+        ));
+        assert!(!should_collide(
+            &CollisionMask::flying(),
+            None,
+            &CollisionMask::terrain(),
+            None
+        ));
+        assert!(!should_collide(
+            &CollisionMask::projectile(),
+            None,
+            &CollisionMask::projectile(),
+            None
+        ));
+        assert!(should_collide(
+            &CollisionMask::sensor(),
+            None,
+            &CollisionMask::unit(),
+            None
+        ));
+    }
+
+    /// Spawn a collider and index it in the world's spatial grid
+    fn spawn_indexed(world: &mut World, position: Vec3, bundle: impl Bundle) -> Entity {
+        let entity = world
+            .spawn((Transform::from_translation(position), bundle))
+            .id();
+        world
+            .resource_mut::<GlobalSpatialGrid>()
+            .grid
+            .insert(entity, position);
+        entity
+    }
+
+    fn raycast_world() -> World {
+        let mut world = World::new();
+        world.init_resource::<GlobalSpatialGrid>();
+        world.init_resource::<ColliderExtents>();
+        world
+    }
+
+    #[test]
+    fn raycast_filters_layers_and_sorts_hits() {
+        let mut world = raycast_world();
+        let far = spawn_indexed(
+            &mut world,
+            Vec3::new(30.0, 0.0, 0.0),
+            PhysicsSphere::new(1.0),
+        );
+        let near = spawn_indexed(
+            &mut world,
+            Vec3::new(10.0, 0.0, 0.0),
+            PhysicsSphere::new(1.0),
+        );
+        let wall = spawn_indexed(
+            &mut world,
+            Vec3::new(20.0, 0.0, 0.0),
+            (AABB::new(Vec3::ONE), Obstacle),
+        );
+
+        let mut state = SystemState::<PhysicsRaycast>::new(&mut world);
+        let raycast = state.get(&world);
+        let ray = RayQuery::new(Vec3::ZERO, Vec3::X, 50.0);
+
+        let hits: Vec<_> = raycast.cast(&ray, |_| true);
+        assert_eq!(
+            hits.iter().map(|hit| hit.entity).collect::<Vec<_>>(),
+            [near, wall, far]
+        );
+        assert!(
+            hits.windows(2)
+                .all(|pair| pair[0].distance <= pair[1].distance)
+        );
+        assert!((hits[0].distance - 9.0).abs() < 1e-4);
+
+        // Units only: the wall between them is ignored
+        let units = raycast.cast(&ray.clone().with_layers(CollisionMask::UNITS), |_| true);
+        assert_eq!(
+            units.iter().map(|hit| hit.entity).collect::<Vec<_>>(),
+            [near, far]
+        );
+        let buildings = raycast.cast_first(&ray.with_layers(CollisionMask::BUILDINGS), |_| true);
+        assert_eq!(buildings.map(|hit| hit.entity), Some(wall));
+    }
+
+    #[test]
+    fn raycast_reaches_large_colliders_off_the_ray() {
+        let mut world = raycast_world();
+        // A fortress 80 wide whose centre is four grid cells from the ray
+        let fortress = spawn_indexed(
+            &mut world,
+            Vec3::new(60.0, 0.0, 20.0),
+            (AABB::new(Vec3::new(40.0, 5.0, 10.0)), Obstacle),
+        );
+        world.run_system_cached(collider_extents_system).unwrap();
+
+        let mut state = SystemState::<PhysicsRaycast>::new(&mut world);
+        let raycast = state.get(&world);
+        let ray = RayQuery::new(Vec3::new(25.0, 0.0, -5.0), Vec3::Z, 50.0);
+
+        let hit = raycast.cast_first(&ray, |_| true).expect("ray should hit");
+        assert_eq!(hit.entity, fortress);
+        assert!((hit.distance - 15.0).abs() < 1e-4);
+    }
+
+    #[test]
+    fn sweeps_reach_large_colliders_off_the_path() {
+        let mut world = raycast_world();
+        let fortress = spawn_indexed(
+            &mut world,
+            Vec3::new(60.0, 0.0, 20.0),
+            (AABB::new(Vec3::new(40.0, 5.0, 10.0)), Obstacle),
+        );
+        world.run_system_cached(collider_extents_system).unwrap();
+
+        let mut state = SystemState::<(
+            Res<GlobalSpatialGrid>,
+            Res<ColliderExtents>,
+            SweepTargetQuery,
+        )>::new(&mut world);
+        let (grid, extents, targets) = state.get(&world);
+        let (start, end) = (Vec3::new(25.0, 0.0, -5.0), Vec3::new(25.0, 0.0, 15.0));
+
+        let hits = sweep_sphere(&grid, Some(&extents), start, end, 0.5, &targets, |_| true);
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].entity, fortress);
+        assert!((hits[0].distance - 14.5).abs() < 1e-4);
+    }
+
+    #[test]
+    fn terrain_does_not_block_air_sight() {
+        let mut world = World::new();
+        world.init_resource::<GlobalSpatialGrid>();
+        // A ridge 10 units high between x = 8 and x = 12
+        let heights = (0..3)
+            .flat_map(|_| (0..5).map(|x| if x == 2 { 10.0 } else { 0.0 }))
+            .collect();
+        world.insert_resource(TerrainHeightfield::new(Vec2::ZERO, 5.0, 5, 3, heights));
+
+        let mut state = SystemState::<PhysicsRaycast>::new(&mut world);
+        let raycast = state.get(&world);
+        let (from, to) = (Vec3::new(1.0, 0.0, 5.0), Vec3::new(19.0, 0.0, 5.0));
+
+        assert!(!raycast.line_of_sight(from, to, sight_blocking_layers(false, false), &[]));
+        assert!(raycast.line_of_sight(from, to, sight_blocking_layers(true, false), &[]));
+    }
+}
@@ -79,6 +79,26 @@ pub enum MovementType {
     Teleporting,
 }
 
+/// Flight layer for `MovementType::Flying` units: they ignore ground
+/// pathfinding, cruise at `altitude` above the terrain and steer apart from
+/// other flyers within `avoidance_radius`
+#[derive(Component, Clone, Debug)]
+pub struct Airborne {
+    pub altitude: f32,
+    pub climb_rate: f32,
+    pub avoidance_radius: f32,
+}
+
+impl Default for Airborne {
+    fn default() -> Self {
+        Self {
+            altitude: 8.0,
+            climb_rate: 4.0,
+            avoidance_radius: 3.0,
+        }
+    }
+}
+
 /// Simple movement target for basic movement
 #[derive(Component, Clone, Debug)]
 pub struct MovementTarget {
@@ -182,12 +202,78 @@ pub struct CollisionMask {
 impl Default for CollisionMask {
     fn default() -> Self {
         Self {
-            layer: 1,
-            mask: u32::MAX,
+            layer: Self::UNITS,
+            mask: Self::ALL,
         }
     }
 }
 
+impl CollisionMask {
+    pub const NONE: u32 = 0;
+    pub const UNITS: u32 = 1 << 0;
+    pub const BUILDINGS: u32 = 1 << 1;
+    pub const PROJECTILES: u32 = 1 << 2;
+    pub const TERRAIN: u32 = 1 << 3;
+    pub const SENSORS: u32 = 1 << 4;
+    pub const FLYING: u32 = 1 << 5;
+    pub const ALL: u32 = u32::MAX;
+
+    pub fn new(layer: u32, mask: u32) -> Self {
+        Self { layer, mask }
+    }
+
+    /// Ground unit: collides with everything
+    pub fn unit() -> Self {
+        Self::new(Self::UNITS, Self::ALL)
+    }
+
+    /// Airborne unit: ignores terrain and ground-only colliders
+    pub fn flying() -> Self {
+        Self::new(
+            Self::FLYING,
+            Self::FLYING | Self::PROJECTILES | Self::SENSORS,
+        )
+    }
+
+    /// Static structure
+    pub fn building() -> Self {
+        Self::new(Self::BUILDINGS, Self::ALL)
+    }
+
+    /// Projectile: hits units, buildings and terrain but not other projectiles
+    pub fn projectile() -> Self {
+        Self::new(
+            Self::PROJECTILES,
+            Self::UNITS | Self::FLYING | Self::BUILDINGS | Self::TERRAIN,
+        )
+    }
+
+    /// Terrain and cliffs
+    pub fn terrain() -> Self {
+        Self::new(Self::TERRAIN, Self::ALL & !Self::SENSORS)
+    }
+
+    /// Trigger volume: never reports terrain
+    pub fn sensor() -> Self {
+        Self::new(Self::SENSORS, Self::ALL & !(Self::TERRAIN | Self::SENSORS))
+    }
+
+    /// Both sides must accept each other's layer
+    pub fn interacts_with(&self, other: &CollisionMask) -> bool {
+        (self.mask & other.layer) != 0 && (other.mask & self.layer) != 0
+    }
+
+    /// Check whether this layer is accepted by a query mask (e.g. a raycast)
+    pub fn matches(&self, query_mask: u32) -> bool {
+        (self.layer & query_mask) != 0
+    }
+}
+
+/// Team ownership used for collision filtering; projectiles never
+/// collide with colliders that share their team
+#[derive(Component, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct CollisionTeam(pub u32);
+
 /// Velocity component for physics-based movement
 #[derive(Component, Clone, Debug, Default)]
 pub struct Velocity {
@@ -323,6 +409,37 @@ impl Sphere {
     }
 }
 
+/// Swept (continuous) hit detection for fast movers such as projectiles.
+///
+/// `previous_position` is recorded at the end of every frame, so consumers
+/// can sweep from it to the current translation and never tunnel through
+/// thin targets regardless of frame time.
+#[derive(Component, Clone, Debug)]
+pub struct ContinuousCollision {
+    pub radius: f32,
+    pub previous_position: Option<Vec3>,
+}
+
+impl Default for ContinuousCollision {
+    fn default() -> Self {
+        Self::new(0.5)
+    }
+}
+
+impl ContinuousCollision {
+    pub fn new(radius: f32) -> Self {
+        Self {
+            radius,
+            previous_position: None,
+        }
+    }
+
+    /// Segment travelled since the previous frame
+    pub fn swept_segment(&self, current: Vec3) -> (Vec3, Vec3) {
+        (self.previous_position.unwrap_or(current), current)
+    }
+}
+
 /// Sensor component for trigger-based collision detection
 #[derive(Component, Clone, Debug, Default)]
 pub struct Sensor {
@@ -0,0 +1,86 @@
+use crate::stats::{StatKind, Stats};
+use bevy::prelude::*;
+
+// ==============================================================================
+// CROWD CONTROL
+// ==============================================================================
+
+/// Crowd control currently affecting an entity.
+///
+/// Kept in sync with the entity's status effects by the combat crate and
+/// obeyed by movement, attacks and abilities.
+#[derive(Component, Clone, Debug, Default, PartialEq, Eq)]
+pub struct CrowdControl {
+    /// Stunned or frozen: no moving, attacking or casting
+    pub stunned: bool,
+    /// No casting abilities
+    pub silenced: bool,
+    /// Attacks may miss
+    pub blinded: bool,
+}
+
+impl CrowdControl {
+    pub fn can_move(&self) -> bool {
+        !self.stunned
+    }
+
+    pub fn can_attack(&self) -> bool {
+        !self.stunned
+    }
+
+    pub fn can_cast(&self) -> bool {
+        !self.stunned && !self.silenced
+    }
+}
+
+/// Whether an entity with optional crowd control may move
+pub fn can_move(control: Option<&CrowdControl>) -> bool {
+    control.is_none_or(CrowdControl::can_move)
+}
+
+/// Whether an entity with optional crowd control may use abilities
+pub fn can_cast(control: Option<&CrowdControl>) -> bool {
+    control.is_none_or(CrowdControl::can_cast)
+}
+
+/// Factor on a mover's commanded speed from movement speed modifiers (slows,
+/// hastes, auras) and crowd control. Zero while stunned.
+pub fn movement_speed_scale(stats: Option<&Stats>, control: Option<&CrowdControl>) -> f32 {
+    if !can_move(control) {
+        return 0.0;
+    }
+
+    stats.map_or(1.0, |stats| stats.ratio(StatKind::MovementSpeed))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::stats::{ModifierOp, ModifierSource};
+
+    #[test]
+    fn slows_and_stuns_scale_movement() {
+        let mut stats = Stats::new().with(StatKind::MovementSpeed, 5.0);
+        assert_eq!(movement_speed_scale(Some(&stats), None), 1.0);
+
+        stats.replace_source(
+            ModifierSource::StatusEffect,
+            [(StatKind::MovementSpeed, ModifierOp::Multiply(0.5))],
+            None,
+        );
+        assert_eq!(movement_speed_scale(Some(&stats), None), 0.5);
+
+        let stunned = CrowdControl {
+            stunned: true,
+            ..default()
+        };
+        assert_eq!(movement_speed_scale(Some(&stats), Some(&stunned)), 0.0);
+        assert!(!stunned.can_attack() && !stunned.can_cast());
+
+        let silenced = CrowdControl {
+            silenced: true,
+            ..default()
+        };
+        assert!(silenced.can_move() && !silenced.can_cast());
+    }
+}
@@ -9,18 +9,53 @@ use bevy::prelude::*;
 // Avoid sphere name conflict with Bevy's math Sphere
 use crate::components::Sphere as PhysicsSphere;
 
+pub mod abilities;
 pub mod collision;
 pub mod components;
+pub mod crowd_control;
 pub mod movement;
+pub mod progression;
+pub mod sanity;
 pub mod spatial;
+pub mod stats;
+pub mod stealth;
+pub mod terrain;
+pub mod weapons;
 
 // Re-export commonly used types
+pub use abilities::{
+    Abilities, AbilityAffects, AbilityCasting, AbilityCost, AbilityDefinition, AbilityEffect,
+    AbilityFailed, AbilityFailure, AbilityLibrary, AbilityResolved, AbilitySlot, AbilityTarget,
+    AbilityTargeting, Displacement, StatusKind, UseAbility,
+};
 pub use collision::{
-    CollisionEvent, CollisionType, RaycastEvent, RaycastHit, RaycastResultEvent, TriggerEvent,
+    ActiveContacts, ColliderExtents, CollisionEnded, CollisionEvent, CollisionFilterQuery,
+    CollisionStarted, CollisionStay, CollisionType, ContactData, DEFAULT_SWEEP_TARGET_RADIUS,
+    LINE_OF_SIGHT_EYE_HEIGHT, PhysicsRaycast, RayQuery, RaycastEvent, RaycastHit,
+    RaycastResultEvent, SweepTargetQuery, TriggerEvent, should_collide, sight_blocking_layers,
+    sweep_sphere, sweep_sphere_aabb, sweep_sphere_sphere,
 };
 pub use components::*;
+pub use crowd_control::{CrowdControl, can_cast, can_move, movement_speed_scale};
 pub use movement::{FlockingAgent, Formation, FormationMember, FormationType};
+pub use progression::{
+    Experience, Promoted, VeteranBonus, VeteranStatus, VeteranTier, next_level_xp,
+    promotion_system, veterancy_bonus_system,
+};
+pub use sanity::{
+    EldritchAura, Madness, MadnessBehaviour, MadnessChanged, Sanity, SanityResistance,
+    SanitySource, eldritch_aura_system, madness_system,
+};
 pub use spatial::{BroadPhaseCollisionPairs, GlobalSpatialGrid, SpatialGrid};
+pub use stats::{
+    DamageType, Healed, Health, ModifierOp, ModifierSource, Shield, StatKind, StatModifier, Stats,
+    UnitClassification, UnitType, stats_update_system,
+};
+pub use stealth::{
+    Detector, RevealStealth, Stealth, StealthMode, StealthState, is_hidden_from, stealth_system,
+};
+pub use terrain::TerrainHeightfield;
+pub use weapons::{Weapon, WeaponType, Weapons};
 
 // ==============================================================================
 // PHYSICS PLUGIN
@@ -53,11 +88,16 @@ impl Plugin for GamePhysicsPlugin {
     fn build(&self, app: &mut App) {
         // Add resources
         app.insert_resource(GlobalSpatialGrid::new(self.spatial_grid_cell_size))
-            .insert_resource(BroadPhaseCollisionPairs::default());
+            .insert_resource(BroadPhaseCollisionPairs::default())
+            .insert_resource(ActiveContacts::default())
+            .init_resource::<ColliderExtents>();
 
         // Add collision events
         if self.enable_collision_detection {
             app.add_message::<CollisionEvent>()
+                .add_message::<CollisionStarted>()
+                .add_message::<CollisionStay>()
+                .add_message::<CollisionEnded>()
                 .add_message::<TriggerEvent>()
                 .add_message::<RaycastEvent>()
                 .add_message::<RaycastResultEvent>();
@@ -71,6 +111,40 @@ impl Plugin for GamePhysicsPlugin {
         // Add core physics systems
         app.add_plugins(avian::PhysicsPlugins::default());
 
+        // Stealth is resolved before anything targets this frame
+        app.add_message::<RevealStealth>()
+            .add_systems(PreUpdate, stealth_system);
+
+        // Modifiers settle before gameplay reads final stat values
+        app.add_systems(PreUpdate, stats_update_system);
+
+        // Sanity drained last frame decides who goes mad before anyone acts
+        app.add_message::<MadnessChanged>().add_systems(
+            PreUpdate,
+            (sanity::eldritch_aura_system, sanity::madness_system).chain(),
+        );
+
+        // Player input and AI activate abilities through `UseAbility`; healing and
+        // displacement resolve here, other effects in the crates that own them
+        app.init_resource::<AbilityLibrary>()
+            .add_message::<UseAbility>()
+            .add_message::<AbilityFailed>()
+            .add_message::<AbilityResolved>()
+            .add_message::<Healed>()
+            .add_systems(
+                Update,
+                (
+                    abilities::ability_activation_system,
+                    abilities::ability_casting_system,
+                    abilities::ability_effect_system,
+                )
+                    .chain(),
+            );
+
+        // Experience earned in combat promotes units and scales their stats
+        app.add_message::<Promoted>()
+            .add_systems(Update, (promotion_system, veterancy_bonus_system).chain());
+
         if self.enable_movement_systems {
             app.add_systems(
                 Update,
@@ -78,6 +152,7 @@ impl Plugin for GamePhysicsPlugin {
                     // movement::physics_movement_system, // Replaced by Avian
                     sync_velocity_system,
                     movement::simple_movement_system,
+                    movement::flying_avoidance_system,
                     movement::pathfinding_movement_system,
                     movement::waypoint_movement_system,
                 ),
@@ -89,12 +164,18 @@ impl Plugin for GamePhysicsPlugin {
                 Update,
                 (
                     collision::broad_phase_collision_system,
-                    collision::aabb_collision_system,
-                    collision::sphere_collision_system,
-                    collision::sensor_system,
-                    collision::collision_response_system,
-                    collision::raycast_system,
-                ),
+                    (
+                        collision::aabb_collision_system,
+                        collision::sphere_collision_system,
+                        collision::sensor_system,
+                    ),
+                    collision::contact_tracking_system,
+                    (
+                        collision::collision_response_system,
+                        collision::raycast_system,
+                    ),
+                )
+                    .chain(),
             );
         }
 
@@ -112,8 +193,17 @@ impl Plugin for GamePhysicsPlugin {
         // Add spatial indexing update system
         app.add_systems(
             PostUpdate,
-            (spatial_indexing_update_system, movement_command_system),
+            (
+                spatial_cleanup_system,
+                spatial_indexing_update_system,
+                collision::collider_extents_system,
+                movement_command_system,
+            )
+                .chain(),
         );
+
+        // Remember end-of-frame positions for swept projectile checks
+        app.add_systems(Last, collision::continuous_collision_tracking_system);
     }
 }
 
@@ -159,26 +249,48 @@ fn sync_velocity_system(
 // SPATIAL INDEXING SYSTEMS
 // ==============================================================================
 
-/// Update spatial indices for all entities
+/// Update spatial indices for entities whose transform changed.
+///
+/// The grid is incremental: entities only move between cells when their cell
+/// changes, so there is no need to rebuild it every frame.
 pub fn spatial_indexing_update_system(
     mut spatial_grid: ResMut<GlobalSpatialGrid>,
     mut query: Query<(Entity, &Transform, &mut SpatialData), Changed<Transform>>,
-    time: Res<Time>,
 ) {
-    let _current_time = time.elapsed_seconds();
+    let cell_size = spatial_grid.grid.cell_size;
 
     for (entity, transform, mut spatial_data) in query.iter_mut() {
         // Update spatial data
-        spatial_data.update_position(transform.translation, spatial_grid.grid.cell_size);
+        spatial_data.update_position(transform.translation, cell_size);
 
-        // Update spatial grid if position changed significantly
-        if spatial_data.has_moved {
-            spatial_grid.grid.insert(entity, transform.translation);
+        // Always refresh the exact position; the grid only re-buckets on cell changes
+        if spatial_grid.grid.update(entity, transform.translation) {
             spatial_grid.needs_update = true;
         }
     }
 }
 
+/// Remove despawned or de-indexed entities from the spatial grid.
+///
+/// Entities stay indexed while they still have `SpatialData` or a collider.
+pub fn spatial_cleanup_system(
+    mut spatial_grid: ResMut<GlobalSpatialGrid>,
+    mut removed_spatial: RemovedComponents<SpatialData>,
+    mut removed_boxes: RemovedComponents<AABB>,
+    mut removed_spheres: RemovedComponents<PhysicsSphere>,
+    indexed_query: Query<(), Or<(With<SpatialData>, With<AABB>, With<PhysicsSphere>)>>,
+) {
+    for entity in removed_spatial
+        .read()
+        .chain(removed_boxes.read())
+        .chain(removed_spheres.read())
+    {
+        if !indexed_query.contains(entity) {
+            spatial_grid.grid.remove(entity);
+        }
+    }
+}
+
 /// System to handle movement commands
 pub fn movement_command_system(
     mut movement_events: MessageReader<MovementCommandEvent>,
@@ -306,7 +418,11 @@ pub fn create_aabb_collider(
     ));
 
     if is_sensor {
-        entity_commands.insert((Sensor { is_active: true }, avian::Sensor));
+        entity_commands.insert((
+            Sensor { is_active: true },
+            CollisionMask::sensor(),
+            avian::Sensor,
+        ));
     }
 
     entity_commands.id()
@@ -331,12 +447,56 @@ pub fn create_sphere_collider(
     ));
 
     if is_sensor {
-        entity_commands.insert((Sensor { is_active: true }, avian::Sensor));
+        entity_commands.insert((
+            Sensor { is_active: true },
+            CollisionMask::sensor(),
+            avian::Sensor,
+        ));
     }
 
     entity_commands.id()
 }
 
+/// Create a static terrain collider from a heightfield.
+///
+/// Avian heightfields are centred on their entity, so the collider is placed
+/// at the middle of the sampled area.
+pub fn create_heightfield_collider(
+    commands: &mut Commands,
+    heightfield: &TerrainHeightfield,
+) -> Entity {
+    let size_x = (heightfield.width.saturating_sub(1)) as f32 * heightfield.cell_size;
+    let size_z = (heightfield.depth.saturating_sub(1)) as f32 * heightfield.cell_size;
+    let center = Vec3::new(
+        heightfield.origin.x + size_x * 0.5,
+        0.0,
+        heightfield.origin.y + size_z * 0.5,
+    );
+
+    // Avian expects one row per X subdivision, each holding the Z samples
+    let heights = (0..heightfield.width)
+        .map(|x| {
+            (0..heightfield.depth)
+                .map(|z| heightfield.sample(x, z).unwrap_or(0.0))
+                .collect()
+        })
+        .collect();
+
+    commands
+        .spawn((
+            Transform::from_translation(center),
+            GlobalTransform::default(),
+            CollisionMask::terrain(),
+            RigidBodyType {
+                body_type: RigidBodyVariant::Static,
+            },
+            // Avian
+            avian::RigidBody::Static,
+            avian::Collider::heightfield(heights, Vec3::new(size_x, 1.0, size_z)),
+        ))
+        .id()
+}
+
 // ==============================================================================
 // PRELUDE MODULE
 // ==============================================================================
@@ -346,9 +506,16 @@ pub mod prelude {
     pub use crate::{
         AABB,
         Acceleration,
+        Airborne,
         BroadPhaseCollisionPairs,
+        CollisionEnded,
         CollisionEvent,
         CollisionMask,
+        CollisionStarted,
+        CollisionStay,
+        CollisionTeam,
+        ContinuousCollision,
+        Detector,
         Friction,
         GamePhysicsPlugin,
         // Resources
@@ -363,6 +530,8 @@ pub mod prelude {
         MovementPath,
         MovementTarget,
         MovementType,
+        PhysicsRaycast,
+        RayQuery,
         RaycastEvent,
         RaycastResultEvent,
         RigidBodyType,
@@ -370,15 +539,60 @@ pub mod prelude {
         Sensor,
         SpatialData,
         SpatialIndex,
+        Stealth,
+        StealthState,
+        TerrainHeightfield,
         TriggerEvent,
         Velocity,
         create_aabb_collider,
+        create_heightfield_collider,
         // Utilities
         create_physics_entity,
         create_sphere_collider,
         movement_command_system,
+        spatial_cleanup_system,
         // Systems
         spatial_indexing_update_system,
     };
 }
 impl bevy::prelude::Message for MovementCommandEvent {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cleanup_keeps_entities_that_still_qualify() {
+        let mut app = App::new();
+        app.init_resource::<GlobalSpatialGrid>()
+            .add_systems(Update, spatial_cleanup_system);
+
+        let entity = app
+            .world_mut()
+            .spawn((SpatialData::new(Vec3::ZERO), AABB::from_size(Vec3::ONE)))
+            .id();
+        app.world_mut()
+            .resource_mut::<GlobalSpatialGrid>()
+            .grid
+            .insert(entity, Vec3::ZERO);
+
+        // Still has SpatialData, so it stays indexed
+        app.world_mut().entity_mut(entity).remove::<AABB>();
+        app.update();
+        assert!(
+            app.world()
+                .resource::<GlobalSpatialGrid>()
+                .grid
+                .contains(entity)
+        );
+
+        app.world_mut().entity_mut(entity).remove::<SpatialData>();
+        app.update();
+        assert!(
+            !app.world()
+                .resource::<GlobalSpatialGrid>()
+                .grid
+                .contains(entity)
+        );
+    }
+}
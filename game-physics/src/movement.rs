@@ -1,4 +1,8 @@
 use crate::components::*;
+use crate::crowd_control::{CrowdControl, movement_speed_scale};
+use crate::spatial::GlobalSpatialGrid;
+use crate::stats::Stats;
+use crate::terrain::TerrainHeightfield;
 use bevy::prelude::*;
 
 // ==============================================================================
@@ -53,48 +57,109 @@ pub fn physics_movement_system(
     }
 }
 
-/// Simple movement system using MovementTarget
+/// Simple movement system using MovementTarget.
+///
+/// Ground units follow the terrain surface and are slowed or stopped by steep
+/// slopes; airborne units hold their cruise altitude instead.
+#[allow(clippy::type_complexity)]
 pub fn simple_movement_system(
     time: Res<Time>,
-    mut query: Query<(&mut Transform, &mut MovementTarget)>,
+    terrain: Option<Res<TerrainHeightfield>>,
+    mut query: Query<(
+        &mut Transform,
+        &mut MovementTarget,
+        Option<&Airborne>,
+        Option<&Stats>,
+        Option<&CrowdControl>,
+    )>,
 ) {
-    for (mut transform, mut target) in query.iter_mut() {
+    let dt = time.delta_seconds();
+
+    for (mut transform, mut target, airborne, stats, control) in query.iter_mut() {
         if target.reached {
             continue;
         }
 
+        let speed = target.speed * movement_speed_scale(stats, control);
+        if speed <= 0.0 {
+            continue;
+        }
+
         let target_position = Vec3::new(target.x, transform.translation.y, target.z);
         let direction = target_position - transform.translation;
         let distance = direction.length();
 
         if distance < 0.1 {
             target.reached = true;
-            transform.translation = target_position;
+            transform.translation = match airborne {
+                Some(airborne) => Vec3::new(
+                    target.x,
+                    cruise_height(terrain.as_deref(), target_position, airborne, dt),
+                    target.z,
+                ),
+                None => ground_position(terrain.as_deref(), target_position),
+            };
         } else {
-            let movement = direction.normalize() * target.speed * time.delta_seconds();
-            transform.translation += movement;
+            let movement = direction.normalize() * speed * dt;
+            match airborne {
+                Some(airborne) => {
+                    let mut next = transform.translation + movement;
+                    next.y = cruise_height(terrain.as_deref(), next, airborne, dt);
+                    transform.translation = next;
+                }
+                None => match ground_step(terrain.as_deref(), transform.translation, movement) {
+                    Some(next) => transform.translation = next,
+                    None => continue, // Too steep to climb
+                },
+            }
 
             // Rotate to face movement direction
             if direction.length() > 0.01 {
                 let look_direction = direction.normalize();
                 let target_rotation =
                     Quat::from_rotation_y(look_direction.x.atan2(look_direction.z));
-                transform.rotation = transform
-                    .rotation
-                    .slerp(target_rotation, 5.0 * time.delta_seconds());
+                transform.rotation = transform.rotation.slerp(target_rotation, 5.0 * dt);
             }
         }
     }
 }
 
-/// Advanced movement system with pathfinding support
+/// Advanced movement system with pathfinding support.
+///
+/// Ground movers steer on the XZ plane and stay on the terrain surface;
+/// flyers steer on the XZ plane and hold their cruise altitude; other
+/// movement types keep full 3D steering. `max_speed` is scaled by movement
+/// speed modifiers, and stunned movers hold still without losing their path.
+#[allow(clippy::type_complexity)]
 pub fn pathfinding_movement_system(
     time: Res<Time>,
-    mut query: Query<(&mut Transform, &mut MovementController)>,
+    terrain: Option<Res<TerrainHeightfield>>,
+    mut query: Query<(
+        &mut Transform,
+        &mut MovementController,
+        Option<&Airborne>,
+        Option<&Stats>,
+        Option<&CrowdControl>,
+    )>,
 ) {
     let dt = time.delta_seconds();
 
-    for (mut transform, mut controller) in query.iter_mut() {
+    for (mut transform, mut controller, airborne, stats, control) in query.iter_mut() {
+        let flying = controller.movement_type == MovementType::Flying;
+        if flying {
+            // Hold altitude even while hovering in place
+            let airborne = airborne.cloned().unwrap_or_default();
+            transform.translation.y =
+                cruise_height(terrain.as_deref(), transform.translation, &airborne, dt);
+        }
+
+        let max_speed = controller.max_speed * movement_speed_scale(stats, control);
+        if max_speed <= 0.0 {
+            controller.velocity = Vec3::ZERO;
+            controller.is_moving = false;
+            continue;
+        }
+
         // Check if we have a current target
         let current_target = if let Some(target) = controller.target_position {
             target
@@ -110,8 +175,12 @@ pub fn pathfinding_movement_system(
             continue;
         };
 
+        let grounded = controller.movement_type == MovementType::Ground;
         let current_pos = transform.translation;
-        let direction = current_target - current_pos;
+        let mut direction = current_target - current_pos;
+        if grounded || flying {
+            direction.y = 0.0;
+        }
         let distance = direction.length();
 
         // Check if we reached the current target
@@ -130,19 +199,34 @@ pub fn pathfinding_movement_system(
         }
 
         // Calculate desired velocity
-        let desired_velocity = direction.normalize() * controller.max_speed;
+        let desired_velocity = direction.normalize() * max_speed;
+        if flying {
+            controller.velocity.y = 0.0;
+        }
 
         // Apply steering forces (seek behavior)
         let steering_force = (desired_velocity - controller.velocity) * controller.acceleration;
         controller.velocity += steering_force * dt;
 
         // Limit velocity to max speed
-        if controller.velocity.length() > controller.max_speed {
-            controller.velocity = controller.velocity.normalize() * controller.max_speed;
+        if controller.velocity.length() > max_speed {
+            controller.velocity = controller.velocity.normalize() * max_speed;
         }
 
         // Update position
-        transform.translation += controller.velocity * dt;
+        if grounded {
+            match ground_step(terrain.as_deref(), current_pos, controller.velocity * dt) {
+                Some(next) => transform.translation = next,
+                None => {
+                    // Blocked by a slope that is too steep
+                    controller.velocity = Vec3::ZERO;
+                    controller.is_moving = false;
+                    continue;
+                }
+            }
+        } else {
+            transform.translation += controller.velocity * dt;
+        }
         controller.is_moving = controller.velocity.length() > 0.1;
 
         // Rotate to face movement direction
@@ -156,16 +240,58 @@ pub fn pathfinding_movement_system(
     }
 }
 
+/// Step along the ground, or move freely when there is no terrain
+fn ground_step(terrain: Option<&TerrainHeightfield>, position: Vec3, step: Vec3) -> Option<Vec3> {
+    match terrain {
+        Some(terrain) => terrain.ground_step(position, step),
+        None => Some(position + step),
+    }
+}
+
+/// Height a flyer should be at after climbing or descending for `dt`
+/// towards `altitude` above the terrain (or above y = 0 off the map)
+fn cruise_height(
+    terrain: Option<&TerrainHeightfield>,
+    position: Vec3,
+    airborne: &Airborne,
+    dt: f32,
+) -> f32 {
+    let ground = terrain
+        .and_then(|terrain| terrain.height_at(position.x, position.z))
+        .unwrap_or(0.0);
+    let target = ground + airborne.altitude;
+    let max_change = airborne.climb_rate * dt;
+    position.y + (target - position.y).clamp(-max_change, max_change)
+}
+
+/// Snap a position onto the terrain surface if there is one
+fn ground_position(terrain: Option<&TerrainHeightfield>, mut position: Vec3) -> Vec3 {
+    if let Some(height) = terrain.and_then(|terrain| terrain.height_at(position.x, position.z)) {
+        position.y = height;
+    }
+    position
+}
+
 /// Path-based movement system with waypoints
 pub fn waypoint_movement_system(
     time: Res<Time>,
-    mut query: Query<(&mut Transform, &mut MovementPath)>,
+    mut query: Query<(
+        &mut Transform,
+        &mut MovementPath,
+        Option<&Stats>,
+        Option<&CrowdControl>,
+    )>,
 ) {
-    for (mut transform, mut path) in query.iter_mut() {
+    for (mut transform, mut path, stats, control) in query.iter_mut() {
         if !path.is_moving || path.waypoints.is_empty() {
             continue;
         }
 
+        let speed = path.movement_speed * movement_speed_scale(stats, control);
+        if speed <= 0.0 {
+            continue;
+        }
+
         if path.current_waypoint_index >= path.waypoints.len() {
             path.is_moving = false;
             path.current_waypoint_index = 0;
@@ -185,7 +311,7 @@ pub fn waypoint_movement_system(
             }
         } else {
             // Move toward current waypoint
-            let movement = direction.normalize() * path.movement_speed * time.delta_seconds();
+            let movement = direction.normalize() * speed * time.delta_seconds();
             transform.translation += movement;
 
             // Rotate to face movement direction
@@ -286,6 +412,60 @@ pub fn flocking_system(
     // This is simplified - in practice, you'd match entities properly
 }
 
+/// Keep flyers from stacking on top of each other.
+///
+/// Flyers ignore ground obstacles, so the only thing they avoid is each
+/// other: each one steers away from other airborne entities inside its
+/// `avoidance_radius`, weighted by how close they are.
+pub fn flying_avoidance_system(
+    spatial_grid: Res<GlobalSpatialGrid>,
+    flyers: Query<(Entity, &Transform, &Airborne)>,
+    mut controllers: Query<&mut MovementController, With<Airborne>>,
+) {
+    for (entity, transform, airborne) in flyers.iter() {
+        let Ok(mut controller) = controllers.get_mut(entity) else {
+            continue;
+        };
+        if controller.movement_type != MovementType::Flying || airborne.avoidance_radius <= 0.0 {
+            continue;
+        }
+
+        let mut push = Vec3::ZERO;
+        for other in spatial_grid
+            .grid
+            .query_radius(transform.translation, airborne.avoidance_radius)
+        {
+            if other == entity {
+                continue;
+            }
+            let Ok((_, other_transform, _)) = flyers.get(other) else {
+                continue;
+            };
+
+            let mut away = transform.translation - other_transform.translation;
+            away.y = 0.0;
+            let distance = away.length();
+            if distance < airborne.avoidance_radius {
+                // Split exact overlaps deterministically
+                let direction = if distance > 0.001 {
+                    away / distance
+                } else if entity.index() < other.index() {
+                    Vec3::X
+                } else {
+                    Vec3::NEG_X
+                };
+                push += direction * (1.0 - distance / airborne.avoidance_radius);
+            }
+        }
+
+        if push != Vec3::ZERO {
+            let max_speed = controller.max_speed;
+            controller.velocity += push * max_speed * 0.5;
+            controller.velocity = controller.velocity.clamp_length_max(max_speed);
+        }
+    }
+}
+
 /// Obstacle avoidance system
 pub fn obstacle_avoidance_system(
     mut query: Query<(&mut MovementController, &Transform)>,
@@ -416,3 +596,55 @@ fn calculate_formation_position(
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn flyers_keep_altitude_over_terrain() {
+        let mut app = App::new();
+        app.init_resource::<Time>()
+            .insert_resource(TerrainHeightfield::new(
+                Vec2::ZERO,
+                5.0,
+                5,
+                5,
+                vec![2.0; 25],
+            ))
+            .add_systems(Update, simple_movement_system);
+
+        let target = MovementTarget {
+            x: 15.0,
+            y: 0.0,
+            z: 10.0,
+            speed: 5.0,
+            reached: false,
+        };
+        let flyer = app
+            .world_mut()
+            .spawn((
+                Transform::from_xyz(5.0, 10.0, 10.0),
+                target.clone(),
+                Airborne::default(),
+            ))
+            .id();
+        let walker = app
+            .world_mut()
+            .spawn((Transform::from_xyz(5.0, 2.0, 10.0), target))
+            .id();
+
+        for _ in 0..5 {
+            app.world_mut()
+                .resource_mut::<Time>()
+                .advance_by(std::time::Duration::from_millis(100));
+            app.update();
+        }
+
+        let flyer = app.world().get::<Transform>(flyer).unwrap().translation;
+        let walker = app.world().get::<Transform>(walker).unwrap().translation;
+        assert!(flyer.x > 6.0, "flyer moved towards its target");
+        assert_eq!(flyer.y, 10.0, "cruising 8 above terrain at height 2");
+        assert_eq!(walker.y, 2.0);
+    }
+}
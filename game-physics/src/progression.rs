@@ -0,0 +1,260 @@
+use crate::stats::{ModifierOp, ModifierSource, StatKind, Stats};
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+// ==============================================================================
+// EXPERIENCE
+// ==============================================================================
+
+/// Experience a unit has earned, shared by combat (which awards it) and
+/// units (which spawn with it). Total experience drives `VeteranStatus`.
+#[derive(Component, Clone, Debug, Serialize, Deserialize)]
+pub struct Experience {
+    /// Experience towards the next level
+    pub current: u32,
+    pub total: u32,
+    pub level: u32,
+    pub next_level_xp: u32,
+}
+
+impl Default for Experience {
+    fn default() -> Self {
+        Self::at_level(1)
+    }
+}
+
+impl Experience {
+    /// Fresh experience for a unit that starts at `level`
+    pub fn at_level(level: u32) -> Self {
+        let level = level.max(1);
+        Self {
+            current: 0,
+            total: (1..level).map(next_level_xp).sum(),
+            level,
+            next_level_xp: next_level_xp(level),
+        }
+    }
+
+    pub fn add_xp(&mut self, amount: u32) {
+        self.current += amount;
+        self.total += amount;
+    }
+
+    pub fn can_level_up(&self) -> bool {
+        self.current >= self.next_level_xp
+    }
+
+    pub fn level_up(&mut self) {
+        self.current -= self.next_level_xp;
+        self.level += 1;
+        self.next_level_xp = next_level_xp(self.level);
+    }
+}
+
+/// Experience needed to go from `level` to the next
+pub fn next_level_xp(level: u32) -> u32 {
+    if level <= 1 {
+        return 100;
+    }
+    // Exponential growth formula
+    100 + (level * level * 50)
+}
+
+// ==============================================================================
+// VETERANCY
+// ==============================================================================
+
+/// Veterancy tiers units are promoted through as they earn experience
+#[derive(
+    Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize,
+)]
+pub enum VeteranTier {
+    #[default]
+    Recruit,
+    Regular,
+    Veteran,
+    Elite,
+    Champion,
+    Legendary,
+}
+
+impl VeteranTier {
+    pub const ALL: [VeteranTier; 6] = [
+        VeteranTier::Recruit,
+        VeteranTier::Regular,
+        VeteranTier::Veteran,
+        VeteranTier::Elite,
+        VeteranTier::Champion,
+        VeteranTier::Legendary,
+    ];
+
+    /// Total experience needed to reach this tier
+    pub fn xp_threshold(self) -> u32 {
+        match self {
+            VeteranTier::Recruit => 0,
+            VeteranTier::Regular => 100,
+            VeteranTier::Veteran => 400,
+            VeteranTier::Elite => 1000,
+            VeteranTier::Champion => 2000,
+            VeteranTier::Legendary => 4000,
+        }
+    }
+
+    /// Highest tier a unit with `total_xp` has earned
+    pub fn for_xp(total_xp: u32) -> Self {
+        Self::ALL
+            .into_iter()
+            .rev()
+            .find(|tier| total_xp >= tier.xp_threshold())
+            .unwrap_or_default()
+    }
+
+    /// 0 for recruits up to 5 for legends
+    pub fn rank(self) -> u32 {
+        self as u32
+    }
+
+    pub fn bonus(self) -> VeteranBonus {
+        let (health, damage, speed) = match self {
+            VeteranTier::Recruit => (1.0, 1.0, 1.0),
+            VeteranTier::Regular => (1.1, 1.1, 1.05),
+            VeteranTier::Veteran => (1.25, 1.25, 1.1),
+            VeteranTier::Elite => (1.4, 1.4, 1.15),
+            VeteranTier::Champion => (1.6, 1.6, 1.2),
+            VeteranTier::Legendary => (2.0, 2.0, 1.25),
+        };
+        VeteranBonus {
+            health_multiplier: health,
+            damage_multiplier: damage,
+            speed_multiplier: speed,
+            xp_multiplier: 1.0,
+        }
+    }
+}
+
+/// Stat multipliers a veterancy tier grants
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct VeteranBonus {
+    pub health_multiplier: f32,
+    pub damage_multiplier: f32,
+    pub speed_multiplier: f32,
+    pub xp_multiplier: f32,
+}
+
+impl Default for VeteranBonus {
+    fn default() -> Self {
+        VeteranTier::Recruit.bonus()
+    }
+}
+
+/// A unit's veterancy, promoted from its `Experience`
+#[derive(Component, Clone, Debug, Default)]
+pub struct VeteranStatus {
+    pub tier: VeteranTier,
+}
+
+impl VeteranStatus {
+    pub fn new(tier: VeteranTier) -> Self {
+        Self { tier }
+    }
+}
+
+/// A unit was promoted to a higher veterancy tier
+#[derive(Event, Clone, Debug)]
+pub struct Promoted {
+    pub entity: Entity,
+    pub from: VeteranTier,
+    pub to: VeteranTier,
+}
+
+/// Promote units whose total experience crossed a tier threshold. Units are
+/// never demoted.
+pub fn promotion_system(
+    mut query: Query<(Entity, &Experience, &mut VeteranStatus), Changed<Experience>>,
+    mut promotions: MessageWriter<Promoted>,
+) {
+    for (entity, experience, mut veteran) in query.iter_mut() {
+        let tier = VeteranTier::for_xp(experience.total);
+        if tier > veteran.tier {
+            promotions.write(Promoted {
+                entity,
+                from: veteran.tier,
+                to: tier,
+            });
+            veteran.tier = tier;
+        }
+    }
+}
+
+/// Veterancy scales health, damage, speed and XP gain through the stat pipeline
+pub fn veterancy_bonus_system(
+    mut query: Query<(&VeteranStatus, &mut Stats), Changed<VeteranStatus>>,
+) {
+    for (veteran, mut stats) in query.iter_mut() {
+        let bonus = veteran.tier.bonus();
+        stats.replace_source(
+            ModifierSource::Veterancy,
+            [
+                (
+                    StatKind::MaxHealth,
+                    ModifierOp::Multiply(bonus.health_multiplier),
+                ),
+                (
+                    StatKind::AttackDamage,
+                    ModifierOp::Multiply(bonus.damage_multiplier),
+                ),
+                (
+                    StatKind::MovementSpeed,
+                    ModifierOp::Multiply(bonus.speed_multiplier),
+                ),
+                (StatKind::XpGain, ModifierOp::Multiply(bonus.xp_multiplier)),
+            ],
+            None,
+        );
+    }
+}
+
+impl bevy::prelude::Message for Promoted {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn experience_promotes_and_scales_stats() {
+        let mut app = App::new();
+        app.add_message::<Promoted>()
+            .add_systems(Update, (promotion_system, veterancy_bonus_system).chain());
+
+        let entity = app
+            .world_mut()
+            .spawn((
+                Experience::default(),
+                VeteranStatus::default(),
+                Stats::new().with(StatKind::AttackDamage, 10.0),
+            ))
+            .id();
+        app.update();
+        assert_eq!(
+            app.world().get::<VeteranStatus>(entity).unwrap().tier,
+            VeteranTier::Recruit
+        );
+
+        // Enough for two tiers at once promotes straight to the higher one
+        app.world_mut()
+            .get_mut::<Experience>(entity)
+            .unwrap()
+            .add_xp(450);
+        app.update();
+
+        let promotions: Vec<_> = app
+            .world()
+            .resource::<Messages<Promoted>>()
+            .iter_current_update_messages()
+            .map(|promotion| (promotion.from, promotion.to))
+            .collect();
+        assert_eq!(promotions, [(VeteranTier::Recruit, VeteranTier::Veteran)]);
+        let stats = app.world().get::<Stats>(entity).unwrap();
+        assert_eq!(stats.get(StatKind::AttackDamage), 12.5);
+    }
+}
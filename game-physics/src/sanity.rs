@@ -0,0 +1,333 @@
+use crate::components::CollisionTeam;
+use bevy::prelude::*;
+
+// ==============================================================================
+// SANITY AND MADNESS
+// ==============================================================================
+
+/// Sanity at or below this fraction of the maximum sends a unit into a panic
+pub const PANIC_THRESHOLD: f32 = 0.5;
+
+/// Sanity at or below this fraction of the maximum sends a unit into a frenzy
+pub const FRENZY_THRESHOLD: f32 = 0.25;
+
+/// Seconds a bout of panic or frenzy lasts
+pub const MADNESS_DURATION: f32 = 8.0;
+
+/// Seconds a broken unit fights for the team that broke it
+pub const DEFECTION_DURATION: f32 = 15.0;
+
+/// Fraction of maximum sanity a unit has at least once a bout of madness ends
+pub const RECOVERED_SANITY: f32 = 0.6;
+
+/// What drained a unit's sanity, so cults can resist each differently
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum SanitySource {
+    /// Eldritch creatures, curses and maddening effects
+    Eldritch,
+    /// Corrupted ground and corruption effects
+    Corruption,
+    /// Watching others die
+    Death,
+}
+
+/// Fraction of each kind of sanity loss a unit shrugs off (0.0 - 1.0)
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct SanityResistance {
+    pub eldritch: f32,
+    pub corruption: f32,
+    pub death: f32,
+}
+
+impl SanityResistance {
+    pub fn against(&self, source: SanitySource) -> f32 {
+        match source {
+            SanitySource::Eldritch => self.eldritch,
+            SanitySource::Corruption => self.corruption,
+            SanitySource::Death => self.death,
+        }
+        .clamp(0.0, 1.0)
+    }
+}
+
+/// A unit's grip on reality.
+///
+/// Drained by eldritch sources, corrupted ground and witnessed deaths, and
+/// slowly regained while the unit is sane. Running low triggers `Madness`.
+#[derive(Component, Clone, Debug)]
+pub struct Sanity {
+    pub current: f32,
+    pub maximum: f32,
+    /// Sanity regained per second while sane
+    pub recovery_rate: f32,
+    pub resistance: SanityResistance,
+    /// Team of whoever last drained this unit; a broken unit defects to it
+    pub tormentor: Option<u32>,
+}
+
+impl Sanity {
+    pub fn new(max: f32) -> Self {
+        Self {
+            current: max,
+            maximum: max,
+            recovery_rate: 1.0,
+            resistance: SanityResistance::default(),
+            tormentor: None,
+        }
+    }
+
+    pub fn with_resistance(mut self, resistance: SanityResistance) -> Self {
+        self.resistance = resistance;
+        self
+    }
+
+    pub fn percentage(&self) -> f32 {
+        if self.maximum > 0.0 {
+            self.current / self.maximum
+        } else {
+            0.0
+        }
+    }
+
+    /// Lose sanity after resistance and return how much was lost
+    pub fn drain(&mut self, source: SanitySource, amount: f32, tormentor: Option<u32>) -> f32 {
+        let lost = (amount * (1.0 - self.resistance.against(source))).min(self.current);
+        if lost > 0.0 {
+            self.current -= lost;
+            if tormentor.is_some() {
+                self.tormentor = tormentor;
+            }
+        }
+        lost
+    }
+}
+
+/// What a unit does while its mind is broken
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MadnessBehaviour {
+    /// Flees from nearby enemies
+    Panic,
+    /// Attacks anything in reach, allies included
+    Frenzy,
+    /// Fights for another team until it comes to its senses
+    Defection { from: u32, to: u32 },
+}
+
+impl MadnessBehaviour {
+    fn severity(&self) -> u8 {
+        match self {
+            MadnessBehaviour::Panic => 1,
+            MadnessBehaviour::Frenzy => 2,
+            MadnessBehaviour::Defection { .. } => 3,
+        }
+    }
+
+    fn duration(&self) -> f32 {
+        match self {
+            MadnessBehaviour::Defection { .. } => DEFECTION_DURATION,
+            _ => MADNESS_DURATION,
+        }
+    }
+}
+
+/// An ongoing bout of madness, maintained by `madness_system`
+#[derive(Component, Clone, Debug)]
+pub struct Madness {
+    pub behaviour: MadnessBehaviour,
+    /// Seconds until the unit comes to its senses
+    pub remaining: f32,
+}
+
+/// Sent when a unit goes mad, gets worse or recovers (`current` is `None`)
+#[derive(Event, Clone, Debug)]
+pub struct MadnessChanged {
+    pub entity: Entity,
+    pub previous: Option<MadnessBehaviour>,
+    pub current: Option<MadnessBehaviour>,
+}
+
+/// Source of eldritch horror that drains the sanity of units around it.
+///
+/// Units on the aura's own `CollisionTeam` are unaffected.
+#[derive(Component, Clone, Debug)]
+pub struct EldritchAura {
+    pub radius: f32,
+    /// Sanity drained per second from units in range
+    pub drain_per_second: f32,
+}
+
+/// Madness a unit's sanity calls for, if any
+fn madness_for(sanity: &Sanity, team: Option<u32>) -> Option<MadnessBehaviour> {
+    let percentage = sanity.percentage();
+    if percentage <= 0.0 {
+        // A broken mind turns to whoever broke it, or lashes out at everyone
+        return match (team, sanity.tormentor) {
+            (Some(from), Some(to)) if from != to => Some(MadnessBehaviour::Defection { from, to }),
+            _ => Some(MadnessBehaviour::Frenzy),
+        };
+    }
+    if percentage <= FRENZY_THRESHOLD {
+        Some(MadnessBehaviour::Frenzy)
+    } else if percentage <= PANIC_THRESHOLD {
+        Some(MadnessBehaviour::Panic)
+    } else {
+        None
+    }
+}
+
+/// Drain the sanity of units near eldritch auras.
+///
+/// Auras are few, so each one checks every unit with sanity.
+pub fn eldritch_aura_system(
+    time: Res<Time>,
+    auras: Query<(&Transform, &EldritchAura, Option<&CollisionTeam>)>,
+    mut minds: Query<(&Transform, &mut Sanity, Option<&CollisionTeam>)>,
+) {
+    let dt = time.delta_seconds();
+
+    for (aura_transform, aura, aura_team) in auras.iter() {
+        let aura_team = aura_team.map(|team| team.0);
+
+        for (transform, mut sanity, team) in minds.iter_mut() {
+            if aura_team.is_some() && aura_team == team.map(|team| team.0) {
+                continue;
+            }
+            if aura_transform.translation.distance(transform.translation) <= aura.radius {
+                sanity.drain(
+                    SanitySource::Eldritch,
+                    aura.drain_per_second * dt,
+                    aura_team,
+                );
+            }
+        }
+    }
+}
+
+/// Recover sanity, and start, worsen or end bouts of madness.
+///
+/// Madness only ever gets worse during a bout; once it ends the unit is back
+/// on its own team with at least `RECOVERED_SANITY` of its sanity.
+pub fn madness_system(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut query: Query<(
+        Entity,
+        &mut Sanity,
+        Option<&mut Madness>,
+        Option<&mut CollisionTeam>,
+    )>,
+    mut madness_events: MessageWriter<MadnessChanged>,
+) {
+    let dt = time.delta_seconds();
+
+    for (entity, mut sanity, madness, mut team) in query.iter_mut() {
+        let previous = madness.as_ref().map(|madness| madness.behaviour);
+
+        if let Some(mut madness) = madness {
+            madness.remaining -= dt;
+            if madness.remaining <= 0.0 {
+                if let (MadnessBehaviour::Defection { from, .. }, Some(team)) =
+                    (madness.behaviour, team.as_mut())
+                {
+                    team.0 = from;
+                }
+                sanity.current = sanity.current.max(sanity.maximum * RECOVERED_SANITY);
+                sanity.tormentor = None;
+                commands.entity(entity).remove::<Madness>();
+                madness_events.write(MadnessChanged {
+                    entity,
+                    previous,
+                    current: None,
+                });
+                continue;
+            }
+        }
+
+        let Some(behaviour) = madness_for(&sanity, team.as_ref().map(|team| team.0)) else {
+            // Sane minds slowly mend
+            sanity.current = (sanity.current + sanity.recovery_rate * dt).min(sanity.maximum);
+            continue;
+        };
+        if previous.is_some_and(|previous| previous.severity() >= behaviour.severity()) {
+            continue;
+        }
+
+        if let (MadnessBehaviour::Defection { to, .. }, Some(team)) = (behaviour, team.as_mut()) {
+            team.0 = to;
+        }
+        commands.entity(entity).insert(Madness {
+            behaviour,
+            remaining: behaviour.duration(),
+        });
+        madness_events.write(MadnessChanged {
+            entity,
+            previous,
+            current: Some(behaviour),
+        });
+    }
+}
+
+impl bevy::prelude::Message for MadnessChanged {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn madness_app() -> App {
+        let mut app = App::new();
+        app.init_resource::<Time>()
+            .add_message::<MadnessChanged>()
+            .add_systems(Update, madness_system);
+        app
+    }
+
+    fn step(app: &mut App, seconds: f32) {
+        app.world_mut()
+            .resource_mut::<Time>()
+            .advance_by(Duration::from_secs_f32(seconds));
+        app.update();
+    }
+
+    #[test]
+    fn resistance_and_thresholds() {
+        let mut sanity = Sanity::new(100.0).with_resistance(SanityResistance {
+            death: 0.5,
+            ..default()
+        });
+        assert_eq!(sanity.drain(SanitySource::Death, 20.0, None), 10.0);
+        assert_eq!(sanity.drain(SanitySource::Eldritch, 20.0, None), 20.0);
+        assert_eq!(madness_for(&sanity, Some(1)), None);
+
+        sanity.drain(SanitySource::Eldritch, 25.0, None);
+        assert_eq!(madness_for(&sanity, Some(1)), Some(MadnessBehaviour::Panic));
+
+        sanity.drain(SanitySource::Eldritch, 30.0, None);
+        assert_eq!(
+            madness_for(&sanity, Some(1)),
+            Some(MadnessBehaviour::Frenzy)
+        );
+    }
+
+    #[test]
+    fn broken_units_defect_then_return() {
+        let mut app = madness_app();
+        let mut sanity = Sanity::new(100.0);
+        sanity.drain(SanitySource::Eldritch, 100.0, Some(2));
+        let cultist = app.world_mut().spawn((sanity, CollisionTeam(1))).id();
+
+        step(&mut app, 0.1);
+        let madness = app.world().get::<Madness>(cultist).unwrap();
+        assert_eq!(
+            madness.behaviour,
+            MadnessBehaviour::Defection { from: 1, to: 2 }
+        );
+        assert_eq!(app.world().get::<CollisionTeam>(cultist).unwrap().0, 2);
+
+        step(&mut app, DEFECTION_DURATION);
+        assert!(app.world().get::<Madness>(cultist).is_none());
+        assert_eq!(app.world().get::<CollisionTeam>(cultist).unwrap().0, 1);
+        let sanity = app.world().get::<Sanity>(cultist).unwrap();
+        assert!(sanity.percentage() >= RECOVERED_SANITY);
+    }
+}
@@ -8,10 +8,15 @@ use wasm_bindgen::prelude::*;
 // ==============================================================================
 
 /// High-performance spatial grid for entity queries and collision detection
+///
+/// The grid is maintained incrementally: `update` only moves an entity between
+/// cells when its cell actually changes, while the exact position is always
+/// refreshed so radius, k-nearest and AABB queries can do precise distance checks.
 pub struct SpatialGrid {
     pub cell_size: f32,
     pub cells: HashMap<(i32, i32), Vec<Entity>>,
     pub entity_positions: HashMap<Entity, (i32, i32)>,
+    pub entity_points: HashMap<Entity, Vec3>,
 }
 
 impl SpatialGrid {
@@ -20,6 +25,7 @@ impl SpatialGrid {
             cell_size,
             cells: HashMap::default(),
             entity_positions: HashMap::default(),
+            entity_points: HashMap::default(),
         }
     }
 
@@ -27,43 +33,64 @@ impl SpatialGrid {
     pub fn clear(&mut self) {
         self.cells.clear();
         self.entity_positions.clear();
+        self.entity_points.clear();
     }
 
     /// Insert an entity at a given position
     pub fn insert(&mut self, entity: Entity, position: Vec3) {
-        let cell_x = (position.x / self.cell_size).floor() as i32;
-        let cell_z = (position.z / self.cell_size).floor() as i32;
-        let cell_coord = (cell_x, cell_z);
-
-        // Remove entity from old cell if it exists
-        if let Some(old_coord) = self.entity_positions.get(&entity) {
-            if let Some(old_cell) = self.cells.get_mut(old_coord) {
-                old_cell.retain(|&e| e != entity);
-                if old_cell.is_empty() {
-                    self.cells.remove(old_coord);
-                }
+        self.update(entity, position);
+    }
+
+    /// Update an entity's position, moving it between cells only when its cell changed.
+    /// Returns true if the entity changed cell (or was newly inserted).
+    pub fn update(&mut self, entity: Entity, position: Vec3) -> bool {
+        let cell_coord = self.world_to_cell(position);
+        self.entity_points.insert(entity, position);
+
+        match self.entity_positions.get(&entity).copied() {
+            Some(old_coord) if old_coord == cell_coord => false,
+            Some(old_coord) => {
+                self.remove_from_cell(entity, old_coord);
+                self.cells.entry(cell_coord).or_default().push(entity);
+                self.entity_positions.insert(entity, cell_coord);
+                true
+            }
+            None => {
+                self.cells.entry(cell_coord).or_default().push(entity);
+                self.entity_positions.insert(entity, cell_coord);
+                true
             }
         }
-
-        // Insert into new cell
-        self.cells.entry(cell_coord).or_default().push(entity);
-
-        self.entity_positions.insert(entity, cell_coord);
     }
 
     /// Remove an entity from the grid
     pub fn remove(&mut self, entity: Entity) {
+        self.entity_points.remove(&entity);
         if let Some(cell_coord) = self.entity_positions.remove(&entity) {
-            if let Some(cell) = self.cells.get_mut(&cell_coord) {
-                cell.retain(|&e| e != entity);
-                if cell.is_empty() {
-                    self.cells.remove(&cell_coord);
-                }
+            self.remove_from_cell(entity, cell_coord);
+        }
+    }
+
+    fn remove_from_cell(&mut self, entity: Entity, cell_coord: (i32, i32)) {
+        if let Some(cell) = self.cells.get_mut(&cell_coord) {
+            cell.retain(|&e| e != entity);
+            if cell.is_empty() {
+                self.cells.remove(&cell_coord);
             }
         }
     }
 
-    /// Query all entities within a radius of a position
+    /// Check whether an entity is currently indexed
+    pub fn contains(&self, entity: Entity) -> bool {
+        self.entity_positions.contains_key(&entity)
+    }
+
+    /// Last known position of an indexed entity
+    pub fn position_of(&self, entity: Entity) -> Option<Vec3> {
+        self.entity_points.get(&entity).copied()
+    }
+
+    /// Query all entities in cells overlapping a radius (broad, no distance check)
     pub fn query_range(&self, position: Vec3, radius: f32) -> Vec<Entity> {
         let mut results = Vec::new();
 
@@ -83,6 +110,196 @@ impl SpatialGrid {
         results
     }
 
+    /// Query entities whose position is within `radius` of `position`
+    pub fn query_radius(&self, position: Vec3, radius: f32) -> Vec<Entity> {
+        self.query_radius_filtered(position, radius, |_| true)
+    }
+
+    /// Query entities within an exact radius that also pass `filter`.
+    ///
+    /// The filter is where callers apply team or component checks, e.g.
+    /// `|e| enemies.get(e).is_ok_and(|team| team.id != my_team)`.
+    pub fn query_radius_filtered<F>(
+        &self,
+        position: Vec3,
+        radius: f32,
+        mut filter: F,
+    ) -> Vec<Entity>
+    where
+        F: FnMut(Entity) -> bool,
+    {
+        let radius_sq = radius * radius;
+        self.query_range(position, radius)
+            .into_iter()
+            .filter(|entity| {
+                self.entity_points
+                    .get(entity)
+                    .is_some_and(|point| point.distance_squared(position) <= radius_sq)
+            })
+            .filter(|entity| filter(*entity))
+            .collect()
+    }
+
+    /// Find up to `k` nearest entities within `max_radius`, sorted by distance
+    pub fn query_k_nearest(&self, position: Vec3, k: usize, max_radius: f32) -> Vec<(Entity, f32)> {
+        self.query_k_nearest_filtered(position, k, max_radius, |_| true)
+    }
+
+    /// Find up to `k` nearest entities within `max_radius` that pass `filter`
+    pub fn query_k_nearest_filtered<F>(
+        &self,
+        position: Vec3,
+        k: usize,
+        max_radius: f32,
+        mut filter: F,
+    ) -> Vec<(Entity, f32)>
+    where
+        F: FnMut(Entity) -> bool,
+    {
+        if k == 0 {
+            return Vec::new();
+        }
+
+        let (center_x, center_z) = self.world_to_cell(position);
+        let max_ring = (max_radius / self.cell_size).ceil() as i32 + 1;
+        let mut found: Vec<(Entity, f32)> = Vec::new();
+
+        // Expand ring by ring; anything closer than `ring * cell_size` can no
+        // longer be beaten by entities in outer rings.
+        for ring in 0..=max_ring {
+            for x in (center_x - ring)..=(center_x + ring) {
+                for z in (center_z - ring)..=(center_z + ring) {
+                    if (x - center_x).abs() != ring && (z - center_z).abs() != ring {
+                        continue;
+                    }
+                    let Some(entities) = self.cells.get(&(x, z)) else {
+                        continue;
+                    };
+                    for &entity in entities {
+                        if let Some(point) = self.entity_points.get(&entity) {
+                            let distance = point.distance(position);
+                            if distance <= max_radius && filter(entity) {
+                                found.push((entity, distance));
+                            }
+                        }
+                    }
+                }
+            }
+
+            let settled_radius = ring as f32 * self.cell_size;
+            if found.iter().filter(|(_, d)| *d <= settled_radius).count() >= k {
+                break;
+            }
+        }
+
+        found.sort_by(|a, b| a.1.partial_cmp(&b.1).unwrap_or(std::cmp::Ordering::Equal));
+        found.truncate(k);
+        found
+    }
+
+    /// Nearest entity within `max_radius` that passes `filter`
+    pub fn query_nearest_filtered<F>(
+        &self,
+        position: Vec3,
+        max_radius: f32,
+        filter: F,
+    ) -> Option<(Entity, f32)>
+    where
+        F: FnMut(Entity) -> bool,
+    {
+        self.query_k_nearest_filtered(position, 1, max_radius, filter)
+            .into_iter()
+            .next()
+    }
+
+    /// Query entities whose position lies inside an axis-aligned box
+    pub fn query_aabb(&self, min: Vec3, max: Vec3) -> Vec<Entity> {
+        let (min_x, min_z) = self.world_to_cell(min);
+        let (max_x, max_z) = self.world_to_cell(max);
+        let mut results = Vec::new();
+
+        for x in min_x..=max_x {
+            for z in min_z..=max_z {
+                let Some(entities) = self.cells.get(&(x, z)) else {
+                    continue;
+                };
+                results.extend(entities.iter().copied().filter(|entity| {
+                    self.entity_points
+                        .get(entity)
+                        .is_some_and(|point| point.cmpge(min).all() && point.cmple(max).all())
+                }));
+            }
+        }
+
+        results
+    }
+
+    /// Cells crossed by a ray on the XZ plane, in traversal order (2D DDA)
+    pub fn ray_cells(&self, origin: Vec3, direction: Vec3, max_distance: f32) -> Vec<(i32, i32)> {
+        let mut cells = Vec::new();
+        let dir = Vec2::new(direction.x, direction.z);
+        let planar_length = dir.length();
+        let (mut x, mut z) = self.world_to_cell(origin);
+        cells.push((x, z));
+
+        if planar_length < f32::EPSILON {
+            return cells;
+        }
+
+        // Distances are measured along the (possibly non-planar) ray, so scale
+        // the planar step by how much of the direction lies in the XZ plane.
+        let dir_norm = dir / planar_length;
+        let planar_max = max_distance * planar_length / direction.length().max(f32::EPSILON);
+
+        let step_x = if dir_norm.x >= 0.0 { 1 } else { -1 };
+        let step_z = if dir_norm.y >= 0.0 { 1 } else { -1 };
+
+        let next_boundary = |cell: i32, step: i32| -> f32 {
+            (if step > 0 { cell + 1 } else { cell }) as f32 * self.cell_size
+        };
+
+        let mut t_max_x = if dir_norm.x.abs() > f32::EPSILON {
+            (next_boundary(x, step_x) - origin.x) / dir_norm.x
+        } else {
+            f32::INFINITY
+        };
+        let mut t_max_z = if dir_norm.y.abs() > f32::EPSILON {
+            (next_boundary(z, step_z) - origin.z) / dir_norm.y
+        } else {
+            f32::INFINITY
+        };
+        let t_delta_x = self.cell_size / dir_norm.x.abs().max(f32::EPSILON);
+        let t_delta_z = self.cell_size / dir_norm.y.abs().max(f32::EPSILON);
+
+        loop {
+            if t_max_x < t_max_z {
+                if t_max_x > planar_max {
+                    break;
+                }
+                x += step_x;
+                t_max_x += t_delta_x;
+            } else {
+                if t_max_z > planar_max {
+                    break;
+                }
+                z += step_z;
+                t_max_z += t_delta_z;
+            }
+            cells.push((x, z));
+        }
+
+        cells
+    }
+
+    /// Entities in every cell crossed by a ray, in traversal order
+    pub fn query_ray(&self, origin: Vec3, direction: Vec3, max_distance: f32) -> Vec<Entity> {
+        self.ray_cells(origin, direction, max_distance)
+            .into_iter()
+            .filter_map(|cell| self.cells.get(&cell))
+            .flat_map(|entities| entities.iter().copied())
+            .collect()
+    }
+
     /// Query entities in a specific cell
     pub fn query_cell(&self, cell_x: i32, cell_z: i32) -> Vec<Entity> {
         self.cells
@@ -256,26 +473,121 @@ impl SpatialHash {
 }
 
 /// Broad phase collision detection using spatial partitioning
+///
+/// Pairs are stored once, ordered by entity index (see `ordered_pair`).
 #[derive(Resource, Default)]
 pub struct BroadPhaseCollisionPairs {
     pub pairs: Vec<(Entity, Entity)>,
+    seen: HashSet<(Entity, Entity)>,
 }
 
 impl BroadPhaseCollisionPairs {
     pub fn clear(&mut self) {
         self.pairs.clear();
+        self.seen.clear();
     }
 
     pub fn add_pair(&mut self, entity_a: Entity, entity_b: Entity) {
-        // Ensure consistent ordering
-        let pair = if entity_a.index() < entity_b.index() {
-            (entity_a, entity_b)
-        } else {
-            (entity_b, entity_a)
-        };
+        let pair = ordered_pair(entity_a, entity_b);
 
-        if !self.pairs.contains(&pair) {
+        if self.seen.insert(pair) {
             self.pairs.push(pair);
         }
     }
 }
+
+/// Order a pair of entities by index so the same contact always maps to the same key
+pub fn ordered_pair(entity_a: Entity, entity_b: Entity) -> (Entity, Entity) {
+    if entity_a.index() <= entity_b.index() {
+        (entity_a, entity_b)
+    } else {
+        (entity_b, entity_a)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entity(index: u32) -> Entity {
+        Entity::from_raw_u32(index).unwrap()
+    }
+
+    #[test]
+    fn test_broad_phase_pairs_are_ordered_and_unique() {
+        let mut pairs = BroadPhaseCollisionPairs::default();
+        pairs.add_pair(entity(2), entity(1));
+        pairs.add_pair(entity(1), entity(2));
+        pairs.add_pair(entity(1), entity(3));
+
+        assert_eq!(
+            pairs.pairs,
+            vec![(entity(1), entity(2)), (entity(1), entity(3))]
+        );
+    }
+
+    #[test]
+    fn test_update_only_moves_on_cell_change() {
+        let mut grid = SpatialGrid::new(10.0);
+        let e = entity(1);
+
+        assert!(grid.update(e, Vec3::new(1.0, 0.0, 1.0)));
+        assert!(!grid.update(e, Vec3::new(5.0, 0.0, 5.0)));
+        assert_eq!(grid.position_of(e), Some(Vec3::new(5.0, 0.0, 5.0)));
+
+        assert!(grid.update(e, Vec3::new(15.0, 0.0, 5.0)));
+        assert_eq!(grid.query_cell(0, 0), Vec::<Entity>::new());
+        assert_eq!(grid.query_cell(1, 0), vec![e]);
+
+        grid.remove(e);
+        assert!(!grid.contains(e));
+        assert!(grid.cells.is_empty());
+    }
+
+    #[test]
+    fn test_query_radius_is_exact() {
+        let mut grid = SpatialGrid::new(10.0);
+        grid.insert(entity(1), Vec3::new(1.0, 0.0, 0.0));
+        grid.insert(entity(2), Vec3::new(9.0, 0.0, 0.0));
+
+        // Same cell, but only one within the radius
+        assert_eq!(grid.query_radius(Vec3::ZERO, 5.0), vec![entity(1)]);
+        assert_eq!(grid.query_range(Vec3::ZERO, 5.0).len(), 2);
+    }
+
+    #[test]
+    fn test_k_nearest_and_filter() {
+        let mut grid = SpatialGrid::new(10.0);
+        for i in 1..=5 {
+            grid.insert(entity(i), Vec3::new(i as f32 * 7.0, 0.0, 0.0));
+        }
+
+        let nearest = grid.query_k_nearest(Vec3::ZERO, 2, 100.0);
+        assert_eq!(
+            nearest.iter().map(|(e, _)| *e).collect::<Vec<_>>(),
+            vec![entity(1), entity(2)]
+        );
+
+        let even = grid.query_nearest_filtered(Vec3::ZERO, 100.0, |e| e.index() % 2 == 0);
+        assert_eq!(even.map(|(e, _)| e), Some(entity(2)));
+    }
+
+    #[test]
+    fn test_aabb_and_ray_queries() {
+        let mut grid = SpatialGrid::new(10.0);
+        grid.insert(entity(1), Vec3::new(5.0, 0.0, 5.0));
+        grid.insert(entity(2), Vec3::new(25.0, 0.0, 5.0));
+        grid.insert(entity(3), Vec3::new(5.0, 0.0, 25.0));
+
+        let inside = grid.query_aabb(Vec3::new(0.0, -1.0, 0.0), Vec3::new(30.0, 1.0, 10.0));
+        assert_eq!(inside.len(), 2);
+        assert!(!inside.contains(&entity(3)));
+
+        let cells = grid.ray_cells(Vec3::new(1.0, 0.0, 5.0), Vec3::X, 25.0);
+        assert_eq!(cells, vec![(0, 0), (1, 0), (2, 0)]);
+        assert_eq!(
+            grid.query_ray(Vec3::new(1.0, 0.0, 5.0), Vec3::X, 25.0),
+            vec![entity(1), entity(2)]
+        );
+    }
+}
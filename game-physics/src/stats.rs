@@ -0,0 +1,421 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+// ==============================================================================
+// STATS AND MODIFIERS
+// ==============================================================================
+
+/// A stat every gameplay crate reads through `Stats`
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum StatKind {
+    MaxHealth,
+    AttackDamage,
+    /// Attacks per second
+    AttackSpeed,
+    MovementSpeed,
+    Armor,
+    MagicResist,
+    CriticalChance,
+    CriticalDamage,
+    LifeSteal,
+    /// Multiplier on experience earned
+    XpGain,
+}
+
+/// Damage types for resistance calculations
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum DamageType {
+    Physical,
+    Magic,
+    True,  // Ignores armor/resist
+    Chaos, // Lovecraftian - mixed damage
+    Siege, // Reduced by armor, built for structures
+}
+
+/// Unit type classification, read by targeting and the damage matrix
+#[derive(Component, Clone, Debug, Serialize, Deserialize)]
+pub struct UnitType {
+    pub classification: UnitClassification,
+    pub is_flying: bool,
+    pub is_mechanical: bool,
+    pub is_biological: bool,
+    pub is_ethereal: bool,
+}
+
+impl UnitType {
+    /// Unit of a classification with none of the extra flags set
+    pub fn new(classification: UnitClassification) -> Self {
+        Self {
+            classification,
+            is_flying: false,
+            is_mechanical: false,
+            is_biological: false,
+            is_ethereal: false,
+        }
+    }
+
+    pub fn flying(mut self) -> Self {
+        self.is_flying = true;
+        self
+    }
+
+    pub fn mechanical(mut self) -> Self {
+        self.is_mechanical = true;
+        self
+    }
+
+    pub fn biological(mut self) -> Self {
+        self.is_biological = true;
+        self
+    }
+
+    pub fn ethereal(mut self) -> Self {
+        self.is_ethereal = true;
+        self
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub enum UnitClassification {
+    Infantry,
+    Vehicle,
+    Monster,
+    Hero,
+    Building,
+}
+
+/// Where a modifier came from, so it can be refreshed or removed as a group
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ModifierSource {
+    /// Passive aura of a leader
+    Aura(Entity),
+    /// Active ability cast by an entity
+    Ability(Entity),
+    Veterancy,
+    Level,
+    StatusEffect,
+    /// Cult doctrine bonuses
+    Cult,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ModifierOp {
+    /// Added to the base value before multipliers
+    Add(f32),
+    /// Multiplies the summed value
+    Multiply(f32),
+}
+
+#[derive(Clone, Debug)]
+pub struct StatModifier {
+    pub stat: StatKind,
+    pub op: ModifierOp,
+    pub source: ModifierSource,
+    /// Seconds left; `None` lasts until the source removes it
+    pub remaining: Option<f32>,
+}
+
+/// Base values, active modifiers and the derived final values of an entity.
+///
+/// Final value = (base + sum of adds) * product of multipliers, never below
+/// zero. Stats without a base value read as 0.
+#[derive(Component, Clone, Debug, Default)]
+pub struct Stats {
+    base: HashMap<StatKind, f32>,
+    modifiers: Vec<StatModifier>,
+    values: HashMap<StatKind, f32>,
+}
+
+impl Stats {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Builder-style base value
+    pub fn with(mut self, stat: StatKind, base: f32) -> Self {
+        self.set_base(stat, base);
+        self
+    }
+
+    pub fn base(&self, stat: StatKind) -> f32 {
+        self.base.get(&stat).copied().unwrap_or(0.0)
+    }
+
+    pub fn has(&self, stat: StatKind) -> bool {
+        self.base.contains_key(&stat)
+    }
+
+    pub fn set_base(&mut self, stat: StatKind, value: f32) {
+        self.base.insert(stat, value);
+        self.recompute();
+    }
+
+    /// Final value after all modifiers
+    pub fn get(&self, stat: StatKind) -> f32 {
+        self.values.get(&stat).copied().unwrap_or(0.0)
+    }
+
+    /// Final value relative to the base value; 1.0 without a base
+    pub fn ratio(&self, stat: StatKind) -> f32 {
+        let base = self.base(stat);
+        if base > 0.0 {
+            self.get(stat) / base
+        } else {
+            1.0
+        }
+    }
+
+    pub fn modifiers(&self) -> impl Iterator<Item = &StatModifier> {
+        self.modifiers.iter()
+    }
+
+    pub fn add_modifier(&mut self, modifier: StatModifier) {
+        self.modifiers.push(modifier);
+        self.recompute();
+    }
+
+    /// Replace every modifier from `source` with a new set sharing one duration
+    pub fn replace_source(
+        &mut self,
+        source: ModifierSource,
+        modifiers: impl IntoIterator<Item = (StatKind, ModifierOp)>,
+        duration: Option<f32>,
+    ) {
+        self.modifiers.retain(|modifier| modifier.source != source);
+        self.modifiers
+            .extend(modifiers.into_iter().map(|(stat, op)| StatModifier {
+                stat,
+                op,
+                source,
+                remaining: duration,
+            }));
+        self.recompute();
+    }
+
+    pub fn remove_source(&mut self, source: ModifierSource) {
+        let before = self.modifiers.len();
+        self.modifiers.retain(|modifier| modifier.source != source);
+        if self.modifiers.len() != before {
+            self.recompute();
+        }
+    }
+
+    pub fn has_timed_modifiers(&self) -> bool {
+        self.modifiers
+            .iter()
+            .any(|modifier| modifier.remaining.is_some())
+    }
+
+    /// Count down timed modifiers and drop expired ones
+    pub fn tick(&mut self, delta: f32) {
+        let before = self.modifiers.len();
+        for modifier in &mut self.modifiers {
+            if let Some(remaining) = &mut modifier.remaining {
+                *remaining -= delta;
+            }
+        }
+        self.modifiers
+            .retain(|modifier| modifier.remaining.is_none_or(|remaining| remaining > 0.0));
+        if self.modifiers.len() != before {
+            self.recompute();
+        }
+    }
+
+    fn recompute(&mut self) {
+        let mut sums = self.base.clone();
+        let mut multipliers: HashMap<StatKind, f32> = HashMap::new();
+
+        for modifier in &self.modifiers {
+            match modifier.op {
+                ModifierOp::Add(amount) => *sums.entry(modifier.stat).or_default() += amount,
+                ModifierOp::Multiply(factor) => {
+                    *multipliers.entry(modifier.stat).or_insert(1.0) *= factor;
+                    sums.entry(modifier.stat).or_default();
+                }
+            }
+        }
+
+        self.values = sums
+            .into_iter()
+            .map(|(stat, sum)| {
+                let factor = multipliers.get(&stat).copied().unwrap_or(1.0);
+                (stat, (sum * factor).max(0.0))
+            })
+            .collect();
+    }
+}
+
+/// The one health pool damage, healing and health bars all use.
+///
+/// `maximum` follows the entity's `StatKind::MaxHealth` when it has `Stats`.
+#[derive(Component, Clone, Debug)]
+pub struct Health {
+    pub current: f32,
+    pub maximum: f32,
+}
+
+impl Health {
+    pub fn new(max: f32) -> Self {
+        Self {
+            current: max,
+            maximum: max,
+        }
+    }
+
+    pub fn percentage(&self) -> f32 {
+        if self.maximum > 0.0 {
+            self.current / self.maximum
+        } else {
+            0.0
+        }
+    }
+
+    pub fn is_dead(&self) -> bool {
+        self.current <= 0.0
+    }
+
+    pub fn heal(&mut self, amount: f32) {
+        self.current = (self.current + amount).min(self.maximum);
+    }
+}
+
+/// Health restored to an entity, for combat logs
+#[derive(Event, Clone, Debug)]
+pub struct Healed {
+    pub healer: Option<Entity>,
+    pub target: Entity,
+    /// Health actually restored, not counting overhealing
+    pub amount: f32,
+}
+
+/// Shield that absorbs damage before health
+#[derive(Component, Clone, Debug)]
+pub struct Shield {
+    pub current: f32,
+    pub maximum: f32,
+    pub regeneration_rate: f32,
+    pub regeneration_delay: f32,
+    pub time_since_damage: f32,
+}
+
+impl Shield {
+    pub fn new(max: f32) -> Self {
+        Self {
+            current: max,
+            maximum: max,
+            regeneration_rate: 5.0,
+            regeneration_delay: 3.0,
+            time_since_damage: 0.0,
+        }
+    }
+}
+
+/// Expire timed modifiers and keep health pools in line with max health.
+///
+/// A change in max health keeps the current health percentage.
+pub fn stats_update_system(time: Res<Time>, mut query: Query<(&mut Stats, Option<&mut Health>)>) {
+    let dt = time.delta_seconds();
+
+    for (mut stats, health) in query.iter_mut() {
+        if stats.has_timed_modifiers() {
+            stats.tick(dt);
+        }
+
+        let Some(mut health) = health else {
+            continue;
+        };
+        if !stats.has(StatKind::MaxHealth) {
+            continue;
+        }
+
+        let maximum = stats.get(StatKind::MaxHealth);
+        if (health.maximum - maximum).abs() > f32::EPSILON {
+            let percentage = health.percentage();
+            health.maximum = maximum;
+            health.current = maximum * percentage;
+        }
+    }
+}
+
+impl bevy::prelude::Message for Healed {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn modifiers_combine_and_expire() {
+        let mut stats = Stats::new()
+            .with(StatKind::AttackDamage, 10.0)
+            .with(StatKind::Armor, 0.0);
+        let leader = Entity::from_raw_u32(1).unwrap();
+
+        stats.replace_source(
+            ModifierSource::Aura(leader),
+            [
+                (StatKind::AttackDamage, ModifierOp::Add(2.0)),
+                (StatKind::AttackDamage, ModifierOp::Multiply(1.5)),
+            ],
+            Some(1.0),
+        );
+        stats.add_modifier(StatModifier {
+            stat: StatKind::Armor,
+            op: ModifierOp::Add(5.0),
+            source: ModifierSource::Veterancy,
+            remaining: None,
+        });
+        assert_eq!(stats.get(StatKind::AttackDamage), 18.0);
+        assert_eq!(stats.get(StatKind::Armor), 5.0);
+
+        // Refreshing a source replaces rather than stacks
+        stats.replace_source(
+            ModifierSource::Aura(leader),
+            [(StatKind::AttackDamage, ModifierOp::Multiply(1.5))],
+            Some(1.0),
+        );
+        assert_eq!(stats.get(StatKind::AttackDamage), 15.0);
+
+        stats.tick(1.5);
+        assert_eq!(stats.get(StatKind::AttackDamage), 10.0);
+        assert_eq!(stats.get(StatKind::Armor), 5.0);
+    }
+
+    #[test]
+    fn health_follows_max_health() {
+        let mut app = App::new();
+        app.init_resource::<Time>()
+            .add_systems(Update, stats_update_system);
+
+        let entity = app
+            .world_mut()
+            .spawn((
+                Stats::new().with(StatKind::MaxHealth, 100.0),
+                Health {
+                    current: 50.0,
+                    maximum: 100.0,
+                },
+            ))
+            .id();
+
+        app.world_mut()
+            .get_mut::<Stats>(entity)
+            .unwrap()
+            .replace_source(
+                ModifierSource::Veterancy,
+                [(StatKind::MaxHealth, ModifierOp::Multiply(2.0))],
+                Some(1.0),
+            );
+        app.update();
+        let health = app.world().get::<Health>(entity).unwrap();
+        assert_eq!((health.current, health.maximum), (100.0, 200.0));
+
+        app.world_mut()
+            .resource_mut::<Time>()
+            .advance_by(Duration::from_secs_f32(2.0));
+        app.update();
+        let health = app.world().get::<Health>(entity).unwrap();
+        assert_eq!((health.current, health.maximum), (50.0, 100.0));
+    }
+}
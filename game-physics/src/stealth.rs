@@ -0,0 +1,247 @@
+use crate::components::CollisionTeam;
+use bevy::prelude::*;
+use std::collections::HashSet;
+
+// ==============================================================================
+// STEALTH AND DETECTION
+// ==============================================================================
+
+/// Movement below this speed (units per second) counts as standing still
+const STATIONARY_SPEED: f32 = 0.05;
+
+/// Makes an entity untargetable by teams that don't detect it.
+///
+/// Stealth only affects targeting: collisions, projectiles and area damage
+/// still hit cloaked entities.
+#[derive(Component, Clone, Debug)]
+#[require(StealthState)]
+pub struct Stealth {
+    pub mode: StealthMode,
+    /// Seconds a `WhileStationary` unit must stand still before cloaking
+    pub stationary_delay: f32,
+}
+
+impl Stealth {
+    pub fn permanent() -> Self {
+        Self {
+            mode: StealthMode::Permanent,
+            stationary_delay: 0.0,
+        }
+    }
+
+    pub fn while_stationary(delay: f32) -> Self {
+        Self {
+            mode: StealthMode::WhileStationary,
+            stationary_delay: delay,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StealthMode {
+    /// Always cloaked unless revealed
+    Permanent,
+    /// Cloaked after standing still for `stationary_delay` seconds
+    WhileStationary,
+}
+
+/// Current stealth status, maintained by `stealth_system`
+#[derive(Component, Clone, Debug, Default)]
+pub struct StealthState {
+    /// Whether stealth is currently active
+    pub cloaked: bool,
+    /// Teams with a detector in range this frame
+    pub detected_by: HashSet<u32>,
+    /// Seconds left of a forced reveal (e.g. after attacking)
+    pub revealed_for: f32,
+    pub stationary_time: f32,
+    pub last_position: Option<Vec3>,
+}
+
+impl StealthState {
+    /// Whether `team` is unable to see or target this entity
+    pub fn is_hidden_from(&self, team: u32) -> bool {
+        self.cloaked && !self.detected_by.contains(&team)
+    }
+}
+
+/// Whether an entity with optional stealth is hidden from a team
+pub fn is_hidden_from(stealth: Option<&StealthState>, team: u32) -> bool {
+    stealth.is_some_and(|stealth| stealth.is_hidden_from(team))
+}
+
+/// Reveals cloaked enemies of its team within `radius`.
+///
+/// Detectors need a `CollisionTeam` to know whose side they are on.
+#[derive(Component, Clone, Debug)]
+pub struct Detector {
+    pub radius: f32,
+}
+
+/// Force a stealthed entity out of stealth for a while
+#[derive(Event, Clone, Debug)]
+pub struct RevealStealth {
+    pub entity: Entity,
+    pub duration: f32,
+}
+
+/// Update cloaking from each entity's stealth rules, reveals and nearby
+/// detectors.
+///
+/// Reveal rules: a forced reveal (`RevealStealth`) wins over everything,
+/// `WhileStationary` units uncloak as soon as they move, and a cloaked unit
+/// is visible to every team with a detector in range. Its own team always
+/// sees it.
+pub fn stealth_system(
+    time: Res<Time>,
+    mut reveal_events: MessageReader<RevealStealth>,
+    mut stealthy: Query<(
+        &Stealth,
+        &mut StealthState,
+        &Transform,
+        Option<&CollisionTeam>,
+    )>,
+    detectors: Query<(&Transform, &Detector, &CollisionTeam)>,
+) {
+    let dt = time.delta_seconds();
+
+    for event in reveal_events.read() {
+        if let Ok((_, mut state, _, _)) = stealthy.get_mut(event.entity) {
+            state.revealed_for = state.revealed_for.max(event.duration);
+        }
+    }
+
+    for (stealth, mut state, transform, team) in stealthy.iter_mut() {
+        let position = transform.translation;
+        let moved = state
+            .last_position
+            .is_some_and(|last| last.distance(position) > STATIONARY_SPEED * dt.max(f32::EPSILON));
+        state.last_position = Some(position);
+        state.stationary_time = if moved {
+            0.0
+        } else {
+            state.stationary_time + dt
+        };
+        state.revealed_for = (state.revealed_for - dt).max(0.0);
+
+        state.cloaked = state.revealed_for <= 0.0
+            && match stealth.mode {
+                StealthMode::Permanent => true,
+                StealthMode::WhileStationary => state.stationary_time >= stealth.stationary_delay,
+            };
+
+        // Own team always sees its own stealthed units
+        state.detected_by.clear();
+        if let Some(team) = team {
+            state.detected_by.insert(team.0);
+        }
+        if !state.cloaked {
+            continue;
+        }
+
+        // Detectors are few, so check them all
+        for (detector_transform, detector, detector_team) in detectors.iter() {
+            if detector_transform.translation.distance(position) <= detector.radius {
+                state.detected_by.insert(detector_team.0);
+            }
+        }
+    }
+}
+
+impl bevy::prelude::Message for RevealStealth {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn stealth_app() -> App {
+        let mut app = App::new();
+        app.init_resource::<Time>()
+            .add_message::<RevealStealth>()
+            .add_systems(Update, stealth_system);
+        app
+    }
+
+    fn step(app: &mut App, seconds: f32) {
+        app.world_mut()
+            .resource_mut::<Time>()
+            .advance_by(Duration::from_secs_f32(seconds));
+        app.update();
+    }
+
+    #[test]
+    fn detectors_reveal_cloaked_enemies() {
+        let mut app = stealth_app();
+        let assassin = app
+            .world_mut()
+            .spawn((Stealth::permanent(), Transform::default(), CollisionTeam(1)))
+            .id();
+        let detector = app
+            .world_mut()
+            .spawn((
+                Detector { radius: 5.0 },
+                Transform::from_xyz(20.0, 0.0, 0.0),
+                CollisionTeam(2),
+            ))
+            .id();
+
+        step(&mut app, 0.1);
+        let state = app.world().get::<StealthState>(assassin).unwrap();
+        assert!(state.is_hidden_from(2));
+        assert!(!state.is_hidden_from(1));
+
+        app.world_mut()
+            .get_mut::<Transform>(detector)
+            .unwrap()
+            .translation
+            .x = 3.0;
+        step(&mut app, 0.1);
+        assert!(
+            !app.world()
+                .get::<StealthState>(assassin)
+                .unwrap()
+                .is_hidden_from(2)
+        );
+
+        // Attacking forces a reveal even without a detector
+        app.world_mut()
+            .get_mut::<Transform>(detector)
+            .unwrap()
+            .translation
+            .x = 20.0;
+        app.world_mut().write_message(RevealStealth {
+            entity: assassin,
+            duration: 1.0,
+        });
+        step(&mut app, 0.1);
+        assert!(
+            !app.world()
+                .get::<StealthState>(assassin)
+                .unwrap()
+                .is_hidden_from(2)
+        );
+    }
+
+    #[test]
+    fn moving_breaks_stationary_stealth() {
+        let mut app = stealth_app();
+        let scout = app
+            .world_mut()
+            .spawn((Stealth::while_stationary(1.0), Transform::default()))
+            .id();
+
+        for _ in 0..3 {
+            step(&mut app, 0.5);
+        }
+        assert!(app.world().get::<StealthState>(scout).unwrap().cloaked);
+
+        app.world_mut()
+            .get_mut::<Transform>(scout)
+            .unwrap()
+            .translation
+            .x = 1.0;
+        step(&mut app, 0.5);
+        assert!(!app.world().get::<StealthState>(scout).unwrap().cloaked);
+    }
+}
@@ -0,0 +1,264 @@
+use bevy::prelude::*;
+
+// ==============================================================================
+// TERRAIN HEIGHTFIELD
+// ==============================================================================
+
+/// Slope (rise over run) above which ground movement starts slowing down
+pub const SLOPE_SLOWDOWN_START: f32 = 0.15;
+
+/// Slope above which ground units cannot move at all
+pub const MAX_WALKABLE_SLOPE: f32 = 0.6;
+
+/// Slowest fraction of normal speed just below `MAX_WALKABLE_SLOPE`
+const MIN_SLOPE_SPEED_FACTOR: f32 = 0.3;
+
+/// Terrain heights sampled on a regular XZ grid.
+///
+/// Sample `(x, z)` sits at `origin + (x, z) * cell_size` and heights are
+/// stored row-major (`z * width + x`). Between samples the surface is
+/// bilinearly interpolated. Outside the sampled area there is no terrain.
+#[derive(Resource, Clone, Debug, Default)]
+pub struct TerrainHeightfield {
+    /// World XZ position of sample (0, 0)
+    pub origin: Vec2,
+    pub cell_size: f32,
+    pub width: usize,
+    pub depth: usize,
+    pub heights: Vec<f32>,
+    /// Entity reported by raycasts that hit the terrain
+    pub entity: Option<Entity>,
+}
+
+impl TerrainHeightfield {
+    pub fn new(
+        origin: Vec2,
+        cell_size: f32,
+        width: usize,
+        depth: usize,
+        heights: Vec<f32>,
+    ) -> Self {
+        debug_assert_eq!(heights.len(), width * depth);
+        Self {
+            origin,
+            cell_size,
+            width,
+            depth,
+            heights,
+            entity: None,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.heights.is_empty() || self.width == 0 || self.depth == 0
+    }
+
+    /// Raw sample height, if the sample exists
+    pub fn sample(&self, x: usize, z: usize) -> Option<f32> {
+        if x < self.width && z < self.depth {
+            self.heights.get(z * self.width + x).copied()
+        } else {
+            None
+        }
+    }
+
+    /// Bilinearly interpolated terrain height at a world XZ position
+    pub fn height_at(&self, x: f32, z: f32) -> Option<f32> {
+        if self.is_empty() || self.cell_size <= 0.0 {
+            return None;
+        }
+
+        let local_x = (x - self.origin.x) / self.cell_size;
+        let local_z = (z - self.origin.y) / self.cell_size;
+        let max_x = (self.width - 1) as f32;
+        let max_z = (self.depth - 1) as f32;
+
+        if !(0.0..=max_x).contains(&local_x) || !(0.0..=max_z).contains(&local_z) {
+            return None;
+        }
+
+        let x0 = local_x.floor() as usize;
+        let z0 = local_z.floor() as usize;
+        let x1 = (x0 + 1).min(self.width - 1);
+        let z1 = (z0 + 1).min(self.depth - 1);
+        let fx = local_x - x0 as f32;
+        let fz = local_z - z0 as f32;
+
+        let h00 = self.sample(x0, z0)?;
+        let h10 = self.sample(x1, z0)?;
+        let h01 = self.sample(x0, z1)?;
+        let h11 = self.sample(x1, z1)?;
+
+        let near = h00 + (h10 - h00) * fx;
+        let far = h01 + (h11 - h01) * fx;
+        Some(near + (far - near) * fz)
+    }
+
+    /// Surface normal from central differences of the interpolated height
+    pub fn normal_at(&self, x: f32, z: f32) -> Option<Vec3> {
+        let center = self.height_at(x, z)?;
+        let e = self.cell_size * 0.1;
+        let left = self.height_at(x - e, z).unwrap_or(center);
+        let right = self.height_at(x + e, z).unwrap_or(center);
+        let back = self.height_at(x, z - e).unwrap_or(center);
+        let front = self.height_at(x, z + e).unwrap_or(center);
+
+        Some(Vec3::new(left - right, 2.0 * e, back - front).normalize_or(Vec3::Y))
+    }
+
+    /// Steepness at a position as rise over run (0 is flat, 1 is 45°)
+    pub fn slope_at(&self, x: f32, z: f32) -> Option<f32> {
+        let normal = self.normal_at(x, z)?;
+        Some(Vec2::new(normal.x, normal.z).length() / normal.y.max(f32::EPSILON))
+    }
+
+    /// Speed multiplier for ground movement: 1.0 on gentle ground, falling
+    /// linearly to `MIN_SLOPE_SPEED_FACTOR`, and 0.0 past `MAX_WALKABLE_SLOPE`
+    pub fn slope_speed_factor(&self, x: f32, z: f32) -> f32 {
+        let Some(slope) = self.slope_at(x, z) else {
+            return 1.0;
+        };
+
+        if slope > MAX_WALKABLE_SLOPE {
+            0.0
+        } else if slope <= SLOPE_SLOWDOWN_START {
+            1.0
+        } else {
+            let t = (slope - SLOPE_SLOWDOWN_START) / (MAX_WALKABLE_SLOPE - SLOPE_SLOWDOWN_START);
+            1.0 - t * (1.0 - MIN_SLOPE_SPEED_FACTOR)
+        }
+    }
+
+    /// Move a ground position along `step` (only X/Z are used), scaled by the
+    /// slope at the destination and snapped onto the surface. Returns `None`
+    /// when the destination is too steep to enter.
+    pub fn ground_step(&self, position: Vec3, step: Vec3) -> Option<Vec3> {
+        let planar = Vec3::new(step.x, 0.0, step.z);
+        let destination = position + planar;
+        let factor = self.slope_speed_factor(destination.x, destination.z);
+        if factor <= 0.0 {
+            return None;
+        }
+
+        let mut next = position + planar * factor;
+        if let Some(height) = self.height_at(next.x, next.z) {
+            next.y = height;
+        }
+        Some(next)
+    }
+
+    /// Ray against the terrain surface.
+    ///
+    /// Marches in quarter-cell steps and refines the crossing by bisection.
+    /// Only downward crossings count, so a ray starting on the ground does
+    /// not hit the ground it stands on. Returns (distance, point, normal).
+    pub fn raycast(
+        &self,
+        origin: Vec3,
+        direction: Vec3,
+        max_distance: f32,
+    ) -> Option<(f32, Vec3, Vec3)> {
+        let direction = direction.normalize_or_zero();
+        if self.is_empty() || direction == Vec3::ZERO || max_distance <= 0.0 {
+            return None;
+        }
+
+        let above = |t: f32| {
+            let point = origin + direction * t;
+            self.height_at(point.x, point.z).map(|h| point.y - h)
+        };
+
+        let step = (self.cell_size * 0.25).max(0.01);
+        let mut previous_t = 0.0;
+        let mut previous_above = above(0.0);
+        let mut t = 0.0;
+
+        while t < max_distance {
+            t = (t + step).min(max_distance);
+            let current_above = above(t);
+
+            if let (Some(prev), Some(curr)) = (previous_above, current_above)
+                && prev > 0.0
+                && curr <= 0.0
+            {
+                // Refine the crossing between the last two samples
+                let (mut lo, mut hi) = (previous_t, t);
+                for _ in 0..8 {
+                    let mid = (lo + hi) * 0.5;
+                    if above(mid).is_some_and(|a| a > 0.0) {
+                        lo = mid;
+                    } else {
+                        hi = mid;
+                    }
+                }
+
+                let point = origin + direction * hi;
+                let normal = self.normal_at(point.x, point.z).unwrap_or(Vec3::Y);
+                return Some((hi, point, normal));
+            }
+
+            previous_t = t;
+            previous_above = current_above;
+        }
+
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn slope() -> TerrainHeightfield {
+        // Height rises by 1 per sample along X
+        let heights = (0..3).flat_map(|_| (0..3).map(|x| x as f32)).collect();
+        TerrainHeightfield::new(Vec2::ZERO, 10.0, 3, 3, heights)
+    }
+
+    #[test]
+    fn test_height_is_bilinear() {
+        let terrain = slope();
+        assert_eq!(terrain.height_at(0.0, 0.0), Some(0.0));
+        assert_eq!(terrain.height_at(15.0, 7.0), Some(1.5));
+        assert_eq!(terrain.height_at(-1.0, 0.0), None);
+    }
+
+    #[test]
+    fn test_steep_slope_blocks_ground_step() {
+        let mut terrain = slope();
+        // 0.1 rise per unit: walkable, slightly slowed
+        let next = terrain
+            .ground_step(Vec3::new(5.0, 0.0, 5.0), Vec3::new(1.0, 0.0, 0.0))
+            .unwrap();
+        assert!(next.x > 5.0 && next.x <= 6.0);
+        assert!((next.y - 0.6).abs() < 0.1);
+
+        // Make the middle column a cliff
+        for z in 0..3 {
+            terrain.heights[z * 3 + 1] = 20.0;
+        }
+        assert!(terrain.slope_at(9.0, 10.0).unwrap() > MAX_WALKABLE_SLOPE);
+        assert!(
+            terrain
+                .ground_step(Vec3::new(8.0, 0.0, 10.0), Vec3::X)
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn test_ray_hits_rising_ground() {
+        let terrain = slope();
+        let (distance, point, normal) = terrain
+            .raycast(Vec3::new(0.0, 1.0, 5.0), Vec3::X, 30.0)
+            .expect("ray should hit the slope");
+
+        assert!((point.x - 10.0).abs() < 0.1);
+        assert!((distance - 10.0).abs() < 0.1);
+        assert!(normal.x < 0.0 && normal.y > 0.0);
+        assert!(
+            terrain
+                .raycast(Vec3::new(0.0, 5.0, 5.0), Vec3::X, 15.0)
+                .is_none()
+        );
+    }
+}
@@ -0,0 +1,140 @@
+use crate::stats::DamageType;
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+// ==============================================================================
+// WEAPONS
+// ==============================================================================
+
+/// A single weapon a unit attacks with. Units may carry several in `Weapons`,
+/// each firing on its own cooldown.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Weapon {
+    pub weapon_type: WeaponType,
+    pub damage: f32,
+    pub damage_type: DamageType,
+    /// Seconds between attacks at base attack speed
+    pub cooldown: f32,
+    pub min_range: f32,
+    pub range: f32,
+    /// `None` for weapons that hit instantly
+    pub projectile_speed: Option<f32>,
+    pub area_of_effect: Option<f32>,
+    /// Fraction of the target's armor or magic resist ignored, 0.0 to 1.0
+    pub penetration: f32,
+    /// Further targets a projectile passes through after its first hit
+    pub pierce: u32,
+    pub can_target_air: bool,
+    pub can_target_ground: bool,
+    #[serde(skip)]
+    pub cooldown_remaining: f32,
+}
+
+impl Weapon {
+    /// Weapon with the usual projectile speed and damage type for its kind
+    pub fn new(weapon_type: WeaponType, damage: f32, cooldown: f32, range: f32) -> Self {
+        let projectile_speed = match weapon_type {
+            WeaponType::Melee => None,
+            WeaponType::Ranged => Some(30.0),
+            WeaponType::Magic => Some(20.0),
+            WeaponType::Siege => Some(15.0),
+        };
+        let damage_type = match weapon_type {
+            WeaponType::Magic => DamageType::Magic,
+            WeaponType::Siege => DamageType::Siege,
+            _ => DamageType::Physical,
+        };
+        Self {
+            weapon_type,
+            damage,
+            damage_type,
+            cooldown,
+            min_range: 0.0,
+            range,
+            projectile_speed,
+            area_of_effect: None,
+            penetration: 0.0,
+            pierce: 0,
+            can_target_air: false,
+            can_target_ground: true,
+            cooldown_remaining: 0.0,
+        }
+    }
+
+    pub fn with_damage_type(mut self, damage_type: DamageType) -> Self {
+        self.damage_type = damage_type;
+        self
+    }
+
+    pub fn with_min_range(mut self, min_range: f32) -> Self {
+        self.min_range = min_range;
+        self
+    }
+
+    pub fn with_area(mut self, radius: f32) -> Self {
+        self.area_of_effect = Some(radius);
+        self
+    }
+
+    pub fn with_penetration(mut self, penetration: f32) -> Self {
+        self.penetration = penetration.clamp(0.0, 1.0);
+        self
+    }
+
+    pub fn with_pierce(mut self, pierce: u32) -> Self {
+        self.pierce = pierce;
+        self
+    }
+
+    /// Which targets the weapon can engage
+    pub fn targeting(mut self, air: bool, ground: bool) -> Self {
+        self.can_target_air = air;
+        self.can_target_ground = ground;
+        self
+    }
+
+    pub fn is_ready(&self) -> bool {
+        self.cooldown_remaining <= 0.0
+    }
+
+    /// Whether a target at `distance` is within this weapon's reach
+    pub fn can_hit(&self, distance: f32, airborne: bool) -> bool {
+        let layer_ok = if airborne {
+            self.can_target_air
+        } else {
+            self.can_target_ground
+        };
+        layer_ok && distance >= self.min_range && distance <= self.range
+    }
+}
+
+/// Weapons a unit attacks with, e.g. a melee weapon plus a ranged one, or
+/// separate anti-air and anti-ground weapons
+#[derive(Component, Clone, Debug, Default, Serialize, Deserialize)]
+pub struct Weapons {
+    pub weapons: Vec<Weapon>,
+}
+
+impl Weapons {
+    pub fn new(weapons: impl IntoIterator<Item = Weapon>) -> Self {
+        Self {
+            weapons: weapons.into_iter().collect(),
+        }
+    }
+
+    /// Longest reach of any weapon
+    pub fn max_range(&self) -> f32 {
+        self.weapons
+            .iter()
+            .map(|weapon| weapon.range)
+            .fold(0.0, f32::max)
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub enum WeaponType {
+    Melee,
+    Ranged,
+    Magic,
+    Siege,
+}
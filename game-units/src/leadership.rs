@@ -1,5 +1,8 @@
-use crate::{AuraBuff, AuraType, BaseStats, Leader, Unit};
+use crate::{AuraType, Health, Leader, ModifierOp, ModifierSource, StatKind, Stats, Team, Unit};
 use bevy::prelude::*;
+use game_physics::{
+    Abilities, AbilityAffects, AbilityLibrary, AbilityTarget, AbilityTargeting, UseAbility,
+};
 #[cfg(feature = "web")]
 use web_sys::console;
 
@@ -15,9 +18,12 @@ pub struct LeadershipBuilding {
 }
 
 // Defeat condition system - checks if critical leaders have died
-pub fn defeat_condition_system(mut commands: Commands, mut leader_query: Query<&mut Leader>) {
-    for mut leader in leader_query.iter_mut() {
-        if leader.health <= 0.0 && leader.alive && leader.defeat_on_death {
+pub fn defeat_condition_system(
+    mut commands: Commands,
+    mut leader_query: Query<(&mut Leader, &Health)>,
+) {
+    for (mut leader, health) in leader_query.iter_mut() {
+        if health.is_dead() && leader.alive && leader.defeat_on_death {
             leader.alive = false;
 
             #[cfg(feature = "web")]
@@ -36,186 +42,90 @@ pub fn defeat_condition_system(mut commands: Commands, mut leader_query: Query<&
     }
 }
 
-// Leader abilities system - handles special leader powers
-pub fn leader_abilities_system(
-    mut commands: Commands,
-    time: Res<Time>,
-    mut leader_query: Query<(&mut Leader, &Transform)>,
-    unit_query: Query<(Entity, &Transform, &Unit), Without<Leader>>,
-) {
-    let current_time = time.elapsed_seconds();
-
-    for (mut leader, leader_transform) in leader_query.iter_mut() {
-        if !leader.alive {
-            continue;
-        }
-
-        // Ability 1: Combat buff (every 30 seconds)
-        if current_time - leader.last_ability1_use >= 30.0 {
-            use_ability1(
-                &mut commands,
-                &mut leader,
-                leader_transform,
-                &unit_query,
-                current_time,
-            );
-        }
-
-        // Ability 2: Area heal (every 45 seconds)
-        if current_time - leader.last_ability2_use >= 45.0 {
-            use_ability2(
-                &mut commands,
-                &mut leader,
-                leader_transform,
-                &unit_query,
-                current_time,
-            );
-        }
+// Abilities a cult's leader can use, in hotkey order
+pub fn leader_abilities(cult: &str) -> Abilities {
+    match cult {
+        "crimson_covenant" => Abilities::new(["rally", "mend", "blood_nova"]),
+        "deep_ones" => Abilities::new(["rally", "mend", "tidal_grasp", "call_the_deep"]),
+        "void_seekers" => Abilities::new(["rally", "mend", "void_bolt", "void_step"]),
+        _ => Abilities::new(["rally", "mend"]),
     }
 }
 
-fn use_ability1(
-    commands: &mut Commands,
-    leader: &mut Leader,
-    leader_transform: &Transform,
-    unit_query: &Query<(Entity, &Transform, &Unit), Without<Leader>>,
-    current_time: f32,
+// Leader abilities system - leaders use their self-centred abilities on their own
+// whenever they're ready and someone they'd affect is in reach. Aimed abilities
+// are left to the player and AI.
+#[allow(clippy::type_complexity)]
+pub fn leader_abilities_system(
+    library: Res<AbilityLibrary>,
+    leader_query: Query<(Entity, &Leader, &Transform, &Team, &Abilities)>,
+    unit_query: Query<(Entity, &Transform, &Team), With<Unit>>,
+    mut ability_events: MessageWriter<UseAbility>,
 ) {
-    let mut affected_units = 0;
-
-    for (unit_entity, unit_transform, unit) in unit_query.iter() {
-        if unit.cult != leader.cult {
+    for (leader_entity, leader, leader_transform, leader_team, abilities) in leader_query.iter() {
+        if !leader.alive || abilities.casting.is_some() {
             continue;
         }
 
-        let distance = leader_transform
-            .translation
-            .distance(unit_transform.translation);
-        if distance <= 15.0 {
-            // Ability radius
-            // Apply temporary combat buff
-            commands.spawn(AuraBuff {
-                target_unit: unit_entity,
-                team: 1,        // TODO: Get from unit
-                atk_mul: 2.0,   // Double attack for 10 seconds
-                hp_mul: 1.5,    // 50% more HP
-                speed_mul: 1.3, // 30% speed boost
-                xp_mul: 1.0,
-                expires_at: current_time + 10.0,
-                strength: 1.0,
+        let ready = abilities
+            .slots
+            .iter()
+            .filter(|slot| slot.cooldown_remaining <= 0.0)
+            .filter_map(|slot| library.get(&slot.ability));
+
+        for definition in ready {
+            let radius = match definition.targeting {
+                AbilityTargeting::Area { radius } if definition.range <= 0.0 => radius,
+                _ => continue,
+            };
+
+            let in_reach = unit_query.iter().any(|(entity, transform, team)| {
+                let allied = team.id == leader_team.id;
+                let affected = match definition.affects {
+                    AbilityAffects::Allies => allied && entity != leader_entity,
+                    AbilityAffects::Enemies => !allied,
+                    AbilityAffects::All => entity != leader_entity,
+                };
+                affected && leader_transform.translation.distance(transform.translation) <= radius
             });
-            affected_units += 1;
-        }
-    }
-
-    if affected_units > 0 {
-        leader.last_ability1_use = current_time;
-
-        #[cfg(feature = "web")]
-        console::log_1(
-            &format!(
-                "Leader {} used combat rally, affecting {} units",
-                leader.name, affected_units
-            )
-            .into(),
-        );
-    }
-}
+            if !in_reach {
+                continue;
+            }
 
-fn use_ability2(
-    commands: &mut Commands,
-    leader: &mut Leader,
-    leader_transform: &Transform,
-    unit_query: &Query<(Entity, &Transform, &Unit), Without<Leader>>,
-    current_time: f32,
-) {
-    let mut healed_units = 0;
+            ability_events.write(UseAbility {
+                caster: leader_entity,
+                ability: definition.id.clone(),
+                target: AbilityTarget::Caster,
+            });
 
-    for (unit_entity, unit_transform, unit) in unit_query.iter() {
-        if unit.cult != leader.cult {
-            continue;
-        }
+            #[cfg(feature = "web")]
+            console::log_1(&format!("Leader {} used {}", leader.name, definition.name).into());
 
-        let distance = leader_transform
-            .translation
-            .distance(unit_transform.translation);
-        if distance <= 25.0 {
-            // Heal radius
-            // TODO: Apply healing (would need mutable access to Unit health)
-            // For now, just count the units that would be healed
-            healed_units += 1;
+            // One activation at a time; the rest wait for the next frame
+            break;
         }
     }
-
-    if healed_units > 0 {
-        leader.last_ability2_use = current_time;
-
-        #[cfg(feature = "web")]
-        console::log_1(
-            &format!(
-                "Leader {} used area heal, affecting {} units",
-                leader.name, healed_units
-            )
-            .into(),
-        );
-    }
 }
 
 // Platform building system functionality is implemented below at line 236
 
-// Buff application system - actually applies stat modifications
-pub fn buff_application_system(
-    mut unit_query: Query<&mut Unit>,
-    aura_buff_query: Query<&AuraBuff>,
-    base_stats_query: Query<&BaseStats>,
-) {
-    for aura_buff in aura_buff_query.iter() {
-        if let Ok(mut unit) = unit_query.get_mut(aura_buff.target_unit)
-            && let Ok(base_stats) = base_stats_query.get(aura_buff.target_unit)
-        {
-            // Apply multiplicative bonuses using base stats
-            // TODO: Add attack damage field to Unit or use separate attack component
-            unit.max_health = base_stats.base_health * aura_buff.hp_mul;
-            unit.movement_speed = base_stats.base_speed * aura_buff.speed_mul;
-            unit.attack_damage = base_stats.base_attack_damage * aura_buff.atk_mul;
-            // TODO: Add XP multiplier application
-        }
-    }
-}
-
-// Aura cleanup system - removes expired buffs
-pub fn aura_cleanup_system(
-    mut commands: Commands,
-    time: Res<Time>,
-    aura_query: Query<(Entity, &AuraBuff)>,
-) {
-    let current_time = time.elapsed_seconds();
-
-    for (entity, aura_buff) in aura_query.iter() {
-        if current_time >= aura_buff.expires_at {
-            commands.entity(entity).despawn();
-        }
-    }
-}
-
 // Aura range visualization system - disabled pending gizmos API updates
 // TODO: Re-enable when aura visualization is needed
 
+/// How long an aura keeps affecting a unit after it leaves the radius
+pub const AURA_LINGER_TIME: f32 = 1.0;
+
 // Passive aura system - applies continuous aura effects
 pub fn passive_aura_system(
-    mut commands: Commands,
-    time: Res<Time>,
-    leader_query: Query<(&Transform, &Leader)>,
-    unit_query: Query<(Entity, &Transform, &Unit), Without<Leader>>,
+    leader_query: Query<(Entity, &Transform, &Leader)>,
+    mut unit_query: Query<(&Transform, &Unit, &mut Stats), Without<Leader>>,
 ) {
-    let current_time = time.elapsed_seconds();
-
-    for (leader_transform, leader) in leader_query.iter() {
+    for (leader_entity, leader_transform, leader) in leader_query.iter() {
         if !leader.alive {
             continue;
         }
 
-        for (unit_entity, unit_transform, unit) in unit_query.iter() {
+        for (unit_transform, unit, mut stats) in unit_query.iter_mut() {
             if unit.cult != leader.cult {
                 continue;
             }
@@ -232,16 +142,16 @@ pub fn passive_aura_system(
                     AuraType::Leadership => (1.1, 1.1, 1.1, 1.1), // Balanced bonus
                 };
 
-                commands.spawn(AuraBuff {
-                    target_unit: unit_entity,
-                    team: 1, // TODO: Get team from unit
-                    atk_mul,
-                    hp_mul,
-                    speed_mul,
-                    xp_mul,
-                    expires_at: current_time + 1.0, // Short duration, continuously reapplied
-                    strength: 1.0,
-                });
+                stats.replace_source(
+                    ModifierSource::Aura(leader_entity),
+                    [
+                        (StatKind::AttackDamage, ModifierOp::Multiply(atk_mul)),
+                        (StatKind::MaxHealth, ModifierOp::Multiply(hp_mul)),
+                        (StatKind::MovementSpeed, ModifierOp::Multiply(speed_mul)),
+                        (StatKind::XpGain, ModifierOp::Multiply(xp_mul)),
+                    ],
+                    Some(AURA_LINGER_TIME), // Continuously refreshed while in range
+                );
             }
         }
     }
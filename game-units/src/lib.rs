@@ -1,14 +1,15 @@
 #![allow(unused)]
 use bevy::prelude::*;
 use game_physics::{
-    AABB, GamePhysicsPlugin, Mass, MovementCommand, MovementCommandEvent, MovementController,
-    SpatialData, Velocity,
+    AABB, AbilityLibrary, AbilityResolved, GamePhysicsPlugin, Mass, MovementCommand,
+    MovementCommandEvent, MovementController, SpatialData, UseAbility, Velocity,
 };
 
 // Module declarations
 pub mod components;
 pub mod formations;
 pub mod leadership;
+pub mod madness;
 pub mod pathfinding_integration;
 pub mod physics_integration;
 pub mod selection;
@@ -19,6 +20,7 @@ pub mod visuals;
 pub use components::*;
 pub use formations::*;
 pub use leadership::*;
+pub use madness::*;
 pub use pathfinding_integration::*;
 pub use physics_integration::*;
 pub use selection::*;
@@ -52,6 +54,12 @@ impl Plugin for GameUnitsPlugin {
             .init_resource::<InputState>()
             .init_resource::<CommandQueue>()
             .init_resource::<UnitTemplates>()
+            // Ability requests come from player input and leaders; summons resolve here
+            .init_resource::<AbilityLibrary>()
+            .add_message::<UseAbility>()
+            .add_message::<AbilityResolved>()
+            // Promotions update the veteran stars
+            .add_message::<Promoted>()
             // Add startup system for loading assets
             .add_systems(Startup, init_game_assets)
             // Register systems in groups to avoid tuple length limits
@@ -61,6 +69,7 @@ impl Plugin for GameUnitsPlugin {
                     // Selection systems
                     selection_system,
                     movement_command_system,
+                    (ability_hotkey_system, ability_input_system).chain(),
                     enhanced_movement_system,
                     group_selection_system,
                 ),
@@ -96,17 +105,25 @@ impl Plugin for GameUnitsPlugin {
                     // Leadership systems
                     defeat_condition_system,
                     leader_abilities_system,
-                    buff_application_system,
-                    aura_cleanup_system,
                     passive_aura_system,
                     platform_building_system,
                 ),
             )
+            .add_systems(
+                Update,
+                (
+                    // Madness systems
+                    panic_flee_system,
+                    madness_team_system,
+                ),
+            )
             .add_systems(
                 Update,
                 (
                     // Spawning systems (optional debug systems)
                     debug_spawn_system,
+                    ability_summon_system,
+                    sync_observable_system,
                 ),
             );
     }
@@ -0,0 +1,116 @@
+use crate::{Team, Unit};
+use bevy::prelude::*;
+use game_physics::{
+    Madness, MadnessBehaviour, MadnessChanged, MovementController, MovementPath, Sanity,
+    SanityResistance,
+};
+use game_world::{Observable, VisionProvider};
+
+/// Panicked units run from enemies within this radius
+pub const PANIC_SCAN_RADIUS: f32 = 20.0;
+
+/// How far ahead a panicked unit runs before looking again
+pub const PANIC_FLEE_DISTANCE: f32 = 10.0;
+
+// Starting sanity for a cult's units; each cult is steeled against different horrors
+pub fn cult_sanity(cult: &str) -> Sanity {
+    let resistance = match cult {
+        // The Deep Ones have long stared into the abyss
+        "deep_ones" => SanityResistance {
+            eldritch: 0.5,
+            corruption: 0.2,
+            death: 0.2,
+        },
+        // Blood rites make death familiar
+        "crimson_covenant" => SanityResistance {
+            eldritch: 0.1,
+            corruption: 0.2,
+            death: 0.6,
+        },
+        // Void Seekers feel at home on corrupted ground
+        "void_seekers" => SanityResistance {
+            eldritch: 0.3,
+            corruption: 0.6,
+            death: 0.1,
+        },
+        _ => SanityResistance::default(),
+    };
+
+    Sanity::new(100.0).with_resistance(resistance)
+}
+
+// Panic system - panicked units run from the nearest enemy in sight
+#[allow(clippy::type_complexity)]
+pub fn panic_flee_system(
+    mut panicked_query: Query<
+        (
+            Entity,
+            &Transform,
+            &Team,
+            &Madness,
+            &mut MovementController,
+            Option<&mut MovementPath>,
+        ),
+        With<Unit>,
+    >,
+    unit_query: Query<(Entity, &Transform, &Team), With<Unit>>,
+) {
+    for (entity, transform, team, madness, mut controller, path) in panicked_query.iter_mut() {
+        if madness.behaviour != MadnessBehaviour::Panic {
+            continue;
+        }
+
+        let position = transform.translation;
+        let nearest_enemy = unit_query
+            .iter()
+            .filter(|(other, _, other_team)| *other != entity && other_team.id != team.id)
+            .map(|(_, other_transform, _)| other_transform.translation)
+            .filter(|enemy| enemy.distance(position) <= PANIC_SCAN_RADIUS)
+            .min_by(|a, b| a.distance(position).total_cmp(&b.distance(position)));
+        let Some(enemy) = nearest_enemy else {
+            continue;
+        };
+
+        let away = Vec3::new(position.x - enemy.x, 0.0, position.z - enemy.z).normalize_or(Vec3::X);
+        controller.target_position = Some(position + away * PANIC_FLEE_DISTANCE);
+        controller.waypoints.clear();
+        controller.path_index = 0;
+        controller.is_moving = true;
+
+        // Drop any ordered path so it doesn't pull the unit back
+        if let Some(mut path) = path {
+            path.waypoints.clear();
+            path.current_waypoint_index = 0;
+            path.is_moving = false;
+        }
+    }
+}
+
+// Defection system - defectors see, are seen and are commanded as the team they joined
+pub fn madness_team_system(
+    mut madness_events: MessageReader<MadnessChanged>,
+    mut query: Query<(
+        &mut Team,
+        Option<&mut VisionProvider>,
+        Option<&mut Observable>,
+    )>,
+) {
+    for event in madness_events.read() {
+        let team_id = match (event.current, event.previous) {
+            (Some(MadnessBehaviour::Defection { to, .. }), _) => to,
+            (_, Some(MadnessBehaviour::Defection { from, .. })) => from,
+            _ => continue,
+        };
+
+        let Ok((mut team, vision, observable)) = query.get_mut(event.entity) else {
+            continue;
+        };
+        team.id = team_id;
+        if let Some(mut vision) = vision {
+            vision.team = team_id;
+        }
+        if let Some(mut observable) = observable {
+            observable.team = team_id;
+        }
+    }
+}
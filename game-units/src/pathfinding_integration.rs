@@ -1,9 +1,10 @@
 use crate::{Team, Unit};
 use bevy::prelude::*;
 use game_physics::{
-    AABB, MovementCommand, MovementCommandEvent, MovementController, Obstacle, Velocity,
+    AABB, MovementCommand, MovementCommandEvent, MovementController, MovementType, Obstacle,
+    Velocity,
 };
-use game_world::{GameMap, PathfindingGrid, find_path};
+use game_world::{GameMap, MovementDomain, PathfindingGrid, find_path_for};
 
 // ==============================================================================
 // PATHFINDING INTEGRATION
@@ -12,20 +13,37 @@ use game_world::{GameMap, PathfindingGrid, find_path};
 /// System to handle pathfinding requests for units
 pub fn pathfinding_request_system(
     mut movement_events: MessageReader<MovementCommandEvent>,
-    mut unit_query: Query<(&Transform, &mut MovementController), With<Unit>>,
+    mut unit_query: Query<
+        (&Transform, &mut MovementController, Option<&MovementDomain>),
+        With<Unit>,
+    >,
     game_map: Res<GameMap>,
     pathfinding_grid: Res<PathfindingGrid>,
 ) {
     for event in movement_events.read() {
         match &event.command {
             MovementCommand::MoveTo { position, speed } => {
-                if let Ok((transform, mut controller)) = unit_query.get_mut(event.entity) {
+                if let Ok((transform, mut controller, domain)) = unit_query.get_mut(event.entity) {
+                    // Flyers ignore ground pathfinding and head straight there
+                    if controller.movement_type == MovementType::Flying {
+                        controller.target_position = Some(*position);
+                        controller.waypoints.clear();
+                        controller.path_index = 0;
+                        controller.max_speed = *speed;
+                        controller.is_moving = true;
+                        continue;
+                    }
+
+                    let domain = domain.copied().unwrap_or_default();
+
                     // Convert world position to grid coordinates
                     let start_grid = world_to_grid(transform.translation, game_map.tile_size);
                     let goal_grid = world_to_grid(*position, game_map.tile_size);
 
-                    // Find path using A* from game-world
-                    if let Some(grid_path) = find_path(start_grid, goal_grid, &pathfinding_grid) {
+                    // Find path using A* on this unit's movement domain layer
+                    if let Some(grid_path) =
+                        find_path_for(domain, start_grid, goal_grid, &pathfinding_grid)
+                    {
                         // Convert grid path to world waypoints
                         let waypoints: Vec<Vec3> = grid_path
                             .iter()
@@ -47,8 +65,14 @@ pub fn pathfinding_request_system(
                             )
                             .into(),
                         );
-                    } else {
-                        // No path found, try direct movement
+                    } else if is_path_clear(
+                        transform.translation,
+                        *position,
+                        domain,
+                        &pathfinding_grid,
+                        game_map.tile_size,
+                    ) {
+                        // No path found, but the straight line is passable
                         controller.target_position = Some(*position);
                         controller.waypoints.clear();
                         controller.max_speed = *speed;
@@ -58,12 +82,18 @@ pub fn pathfinding_request_system(
                         web_sys::console::log_1(
                             &"No path found, attempting direct movement".into(),
                         );
+                    } else {
+                        // Unreachable for this domain (e.g. land unit ordered into water)
+                        controller.target_position = None;
+                        controller.waypoints.clear();
+                        controller.path_index = 0;
+                        controller.is_moving = false;
                     }
                 }
             }
 
             MovementCommand::SetPath { waypoints, speed } => {
-                if let Ok((_transform, mut controller)) = unit_query.get_mut(event.entity) {
+                if let Ok((_transform, mut controller, _)) = unit_query.get_mut(event.entity) {
                     controller.waypoints = waypoints.clone();
                     controller.path_index = 0;
                     controller.max_speed = *speed;
@@ -84,28 +114,19 @@ pub fn update_pathfinding_obstacles(
     mut pathfinding_grid: ResMut<PathfindingGrid>,
     game_map: Res<GameMap>,
 ) {
-    // First, reset all tiles to their default walkability
+    // First, reset all tiles to their default walkability in every domain
     for (&grid_pos, tile_info) in &game_map.tiles {
-        let walkable = match tile_info.tile_type {
-            game_world::map::TileType::Ground => true,
-            game_world::map::TileType::Bridge => true,
-            game_world::map::TileType::Water => false,
-            game_world::map::TileType::Cliff => false,
-            game_world::map::TileType::Void => tile_info.corruption_level < 0.9,
-        };
-        pathfinding_grid.walkable.insert(grid_pos, walkable);
+        pathfinding_grid.set_tile(grid_pos, tile_info.tile_type, tile_info.corruption_level);
     }
 
     // Mark tiles with obstacles as non-walkable
     for obstacle_transform in obstacle_query.iter() {
         let grid_pos = world_to_grid(obstacle_transform.translation, game_map.tile_size);
-        pathfinding_grid.walkable.insert(grid_pos, false);
 
         // Also mark adjacent tiles to give obstacles some clearance
         for dx in -1..=1 {
             for dz in -1..=1 {
-                let adjacent = (grid_pos.0 + dx, grid_pos.1 + dz);
-                pathfinding_grid.walkable.insert(adjacent, false);
+                pathfinding_grid.block_tile((grid_pos.0 + dx, grid_pos.1 + dz));
             }
         }
     }
@@ -199,6 +220,7 @@ pub fn grid_to_world(grid_pos: (i32, i32), tile_size: f32) -> Vec3 {
 /// Smooth path by removing unnecessary waypoints
 pub fn smooth_path(
     waypoints: Vec<Vec3>,
+    domain: MovementDomain,
     pathfinding_grid: &PathfindingGrid,
     tile_size: f32,
 ) -> Vec<Vec3> {
@@ -217,6 +239,7 @@ pub fn smooth_path(
             if is_path_clear(
                 waypoints[current_index],
                 waypoints[i],
+                domain,
                 pathfinding_grid,
                 tile_size,
             ) {
@@ -233,27 +256,23 @@ pub fn smooth_path(
     smoothed
 }
 
-/// Check if a straight path between two points is clear
-fn is_path_clear(
+/// Check if a straight path between two points is clear for a movement domain
+pub fn is_path_clear(
     start: Vec3,
     end: Vec3,
+    domain: MovementDomain,
     pathfinding_grid: &PathfindingGrid,
     tile_size: f32,
 ) -> bool {
     let distance = start.distance(end);
-    let steps = (distance / tile_size).ceil() as usize;
+    let steps = ((distance / tile_size).ceil() as usize).max(1);
 
     for i in 0..=steps {
         let t = i as f32 / steps as f32;
         let pos = start.lerp(end, t);
         let grid_pos = world_to_grid(pos, tile_size);
 
-        if !pathfinding_grid
-            .walkable
-            .get(&grid_pos)
-            .copied()
-            .unwrap_or(false)
-        {
+        if !pathfinding_grid.is_walkable(domain, grid_pos) {
             return false;
         }
     }
@@ -282,3 +301,161 @@ impl Plugin for PathfindingIntegrationPlugin {
         );
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use game_world::map::TileType;
+
+    /// App with a river down x = 3 (tiles 10 apart), crossable only at z = 4
+    fn river_app() -> App {
+        let mut app = App::new();
+        app.add_message::<MovementCommandEvent>()
+            .init_resource::<GameMap>()
+            .add_systems(Update, pathfinding_request_system);
+
+        let mut grid = PathfindingGrid::default();
+        for x in 0..7 {
+            for z in 0..5 {
+                let tile_type = match (x, z) {
+                    (3, 0..=2) => TileType::Water,
+                    (3, 3) => TileType::Cliff,
+                    _ => TileType::Ground,
+                };
+                grid.set_tile((x, z), tile_type, 0.0);
+            }
+        }
+        app.insert_resource(grid);
+        app
+    }
+
+    fn spawn_unit(app: &mut App, domain: MovementDomain) -> Entity {
+        app.world_mut()
+            .spawn((
+                Unit {
+                    cult: "deep_ones".to_string(),
+                    unit_type: "cultist".to_string(),
+                },
+                Transform::default(),
+                MovementController::default(),
+                domain,
+            ))
+            .id()
+    }
+
+    fn order_move(app: &mut App, entity: Entity, position: Vec3) {
+        app.world_mut().write_message(MovementCommandEvent {
+            entity,
+            command: MovementCommand::MoveTo {
+                position,
+                speed: 5.0,
+            },
+        });
+        app.update();
+    }
+
+    #[test]
+    fn move_orders_route_by_movement_domain() {
+        let mut app = river_app();
+        let walker = spawn_unit(&mut app, MovementDomain::Land);
+        let swimmer = spawn_unit(&mut app, MovementDomain::Amphibious);
+        let far_bank = Vec3::new(60.0, 0.0, 0.0);
+
+        order_move(&mut app, walker, far_bank);
+        order_move(&mut app, swimmer, far_bank);
+
+        // Land units detour over the dry crossing
+        let walker_path = &app
+            .world()
+            .get::<MovementController>(walker)
+            .unwrap()
+            .waypoints;
+        assert!(walker_path.contains(&grid_to_world((3, 4), 10.0)));
+        assert!(
+            !walker_path
+                .iter()
+                .any(|point| point.x == 30.0 && point.z < 30.0)
+        );
+
+        // Amphibious units swim straight across
+        let swimmer_path = &app
+            .world()
+            .get::<MovementController>(swimmer)
+            .unwrap()
+            .waypoints;
+        assert!(
+            swimmer_path
+                .iter()
+                .any(|point| point.x == 30.0 && point.z < 30.0)
+        );
+        assert!(!swimmer_path.contains(&grid_to_world((3, 4), 10.0)));
+
+        // Land units can't be ordered into the river at all
+        order_move(&mut app, walker, Vec3::new(30.0, 0.0, 0.0));
+        assert!(
+            !app.world()
+                .get::<MovementController>(walker)
+                .unwrap()
+                .is_moving
+        );
+    }
+
+    #[test]
+    fn flyers_ignore_blocking_terrain() {
+        let mut app = App::new();
+        app.add_message::<MovementCommandEvent>()
+            .init_resource::<GameMap>()
+            .init_resource::<PathfindingGrid>()
+            .add_systems(Update, pathfinding_request_system);
+
+        // A wall of cliffs between the unit and its destination
+        let mut grid = PathfindingGrid::default();
+        for x in 0..5 {
+            for z in -2..=2 {
+                let tile_type = if x == 2 {
+                    TileType::Cliff
+                } else {
+                    TileType::Ground
+                };
+                grid.set_tile((x, z), tile_type, 0.0);
+            }
+        }
+        app.insert_resource(grid);
+
+        let unit = |movement_type| {
+            (
+                Unit {
+                    cult: "crimson_covenant".to_string(),
+                    unit_type: "warrior".to_string(),
+                },
+                Transform::default(),
+                MovementController {
+                    movement_type,
+                    ..default()
+                },
+            )
+        };
+        let flyer = app.world_mut().spawn(unit(MovementType::Flying)).id();
+        let walker = app.world_mut().spawn(unit(MovementType::Ground)).id();
+
+        let destination = Vec3::new(40.0, 0.0, 0.0);
+        for entity in [flyer, walker] {
+            app.world_mut().write_message(MovementCommandEvent {
+                entity,
+                command: MovementCommand::MoveTo {
+                    position: destination,
+                    speed: 5.0,
+                },
+            });
+        }
+        app.update();
+
+        let flyer = app.world().get::<MovementController>(flyer).unwrap();
+        assert!(flyer.is_moving);
+        assert!(flyer.waypoints.is_empty());
+        assert_eq!(flyer.target_position, Some(destination));
+
+        let walker = app.world().get::<MovementController>(walker).unwrap();
+        assert!(!walker.is_moving, "no ground route through the cliffs");
+    }
+}
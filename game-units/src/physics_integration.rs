@@ -1,9 +1,9 @@
-use crate::{Team, Unit};
+use crate::{Health, Stats, Team, Unit};
 use bevy::prelude::*;
 use game_physics::{
-    AABB, CollisionEvent, CollisionType, Mass, MovementCommand, MovementCommandEvent,
-    MovementController, RaycastEvent, RaycastHit, RaycastResultEvent, SpatialData, TriggerEvent,
-    Velocity,
+    AABB, CollisionEvent, CollisionStarted, CollisionStay, CollisionType, CrowdControl, Mass,
+    MovementCommand, MovementCommandEvent, MovementController, RaycastEvent, RaycastHit,
+    RaycastResultEvent, SpatialData, TriggerEvent, Velocity, movement_speed_scale,
 };
 
 // ==============================================================================
@@ -37,40 +37,49 @@ pub fn unit_collision_handler(
     }
 }
 
-/// Handle units colliding with obstacles
+/// Handle units running into obstacles. The first frame of contact drops the
+/// current path so it gets replanned; every frame of contact cancels the
+/// velocity pushing into the obstacle so units can't slide through it.
 pub fn obstacle_collision_handler(
-    mut collision_events: MessageReader<CollisionEvent>,
+    mut started_events: MessageReader<CollisionStarted>,
+    mut stay_events: MessageReader<CollisionStay>,
     mut unit_query: Query<(&mut Velocity, &mut MovementController), With<Unit>>,
     obstacle_query: Query<&Transform, (With<AABB>, Without<Unit>)>,
 ) {
-    for collision_event in collision_events.read() {
-        // Check if one entity is a unit and the other is an obstacle
-        if let Ok((mut velocity, mut controller)) = unit_query.get_mut(collision_event.entity_a)
-            && obstacle_query.get(collision_event.entity_b).is_ok()
-        {
-            // Stop unit movement and recalculate path
-            velocity.linear *= 0.5; // Slow down
-            controller.is_moving = false;
+    let contacts = started_events
+        .read()
+        .map(|CollisionStarted(contact)| (contact, true))
+        .chain(
+            stay_events
+                .read()
+                .map(|CollisionStay(contact)| (contact, false)),
+        );
 
-            // Mark that unit needs to recalculate path
-            if let Some(target) = controller.target_position {
-                // Will trigger pathfinding recalculation
-                controller.waypoints.clear();
-                controller.path_index = 0;
-            }
+    for (contact, started) in contacts {
+        // The normal points from a to b; flip it when the unit is b
+        let (unit, obstacle, into_obstacle) = if unit_query.contains(contact.entity_a) {
+            (contact.entity_a, contact.entity_b, contact.normal)
+        } else {
+            (contact.entity_b, contact.entity_a, -contact.normal)
+        };
+        if !obstacle_query.contains(obstacle) {
+            continue;
         }
+        let Ok((mut velocity, mut controller)) = unit_query.get_mut(unit) else {
+            continue;
+        };
 
-        // Check reverse case
-        if let Ok((mut velocity, mut controller)) = unit_query.get_mut(collision_event.entity_b)
-            && obstacle_query.get(collision_event.entity_a).is_ok()
-        {
+        let pushing = velocity.linear.dot(into_obstacle);
+        if pushing > 0.0 {
+            velocity.linear -= into_obstacle * pushing;
+        }
+
+        if started {
+            // Stop and clear the path so pathfinding recalculates it
             velocity.linear *= 0.5;
             controller.is_moving = false;
-
-            if let Some(target) = controller.target_position {
-                controller.waypoints.clear();
-                controller.path_index = 0;
-            }
+            controller.waypoints.clear();
+            controller.path_index = 0;
         }
     }
 }
@@ -133,19 +142,37 @@ pub fn apply_steering_behavior(
 }
 
 /// System for smooth physics-based unit movement with steering
+#[allow(clippy::type_complexity)]
 pub fn physics_steering_movement_system(
     time: Res<Time>,
-    mut query: Query<(&mut Velocity, &Transform, &mut MovementController, &Mass), With<Unit>>,
+    mut query: Query<
+        (
+            &mut Velocity,
+            &Transform,
+            &mut MovementController,
+            &Mass,
+            Option<&Stats>,
+            Option<&CrowdControl>,
+        ),
+        With<Unit>,
+    >,
 ) {
     let dt = time.delta_seconds();
 
-    for (mut velocity, transform, mut controller, mass) in query.iter_mut() {
+    for (mut velocity, transform, mut controller, mass, stats, control) in query.iter_mut() {
         if !controller.is_moving {
             // Apply friction when not moving
             velocity.linear *= 0.9;
             continue;
         }
 
+        // Stunned units hold still until the stun ends
+        let max_speed = controller.max_speed * movement_speed_scale(stats, control);
+        if max_speed <= 0.0 {
+            velocity.linear = Vec3::ZERO;
+            continue;
+        }
+
         // Get current target (either direct target or current waypoint)
         let target = if !controller.waypoints.is_empty()
             && controller.path_index < controller.waypoints.len()
@@ -162,7 +189,7 @@ pub fn physics_steering_movement_system(
             velocity.linear,
             target,
             transform.translation,
-            controller.max_speed,
+            max_speed,
             controller.acceleration * mass.value,
         );
 
@@ -170,7 +197,7 @@ pub fn physics_steering_movement_system(
         velocity.linear += steering_force * dt / mass.value;
 
         // Limit velocity to max speed
-        velocity.linear = velocity.linear.clamp_length_max(controller.max_speed);
+        velocity.linear = velocity.linear.clamp_length_max(max_speed);
 
         // Check if reached current waypoint
         let distance_to_target = transform.translation.distance(target);
@@ -195,57 +222,6 @@ pub fn physics_steering_movement_system(
     }
 }
 
-// ==============================================================================
-// PROJECTILE COLLISION SYSTEM
-// ==============================================================================
-
-/// Handle projectile collisions with units
-pub fn projectile_collision_system(
-    mut collision_events: MessageReader<CollisionEvent>,
-    projectile_query: Query<&ProjectileMarker>,
-    mut unit_query: Query<&mut Unit>,
-    mut commands: Commands,
-) {
-    for collision_event in collision_events.read() {
-        // Check if entity_a is a projectile
-        if let Ok(projectile) = projectile_query.get(collision_event.entity_a)
-            && let Ok(mut unit) = unit_query.get_mut(collision_event.entity_b)
-        {
-            // Apply damage
-            unit.health -= projectile.damage;
-
-            // Despawn projectile
-            commands.entity(collision_event.entity_a).despawn();
-
-            #[cfg(feature = "web")]
-            web_sys::console::log_1(
-                &format!("Projectile hit unit for {} damage", projectile.damage).into(),
-            );
-        }
-
-        // Check reverse case
-        if let Ok(projectile) = projectile_query.get(collision_event.entity_b)
-            && let Ok(mut unit) = unit_query.get_mut(collision_event.entity_a)
-        {
-            unit.health -= projectile.damage;
-            commands.entity(collision_event.entity_b).despawn();
-
-            #[cfg(feature = "web")]
-            web_sys::console::log_1(
-                &format!("Projectile hit unit for {} damage", projectile.damage).into(),
-            );
-        }
-    }
-}
-
-/// Marker component for projectiles
-#[derive(Component)]
-pub struct ProjectileMarker {
-    pub damage: f32,
-    pub owner: Entity,
-    pub team: u32,
-}
-
 // ==============================================================================
 // PLUGIN
 // ==============================================================================
@@ -262,8 +238,59 @@ impl Plugin for UnitsPhysicsIntegrationPlugin {
                 obstacle_collision_handler,
                 physics_steering_movement_system,
                 update_unit_spatial_data,
-                projectile_collision_system,
             ),
         );
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use game_physics::ContactData;
+
+    #[test]
+    fn units_pushing_into_obstacles_stay_blocked() {
+        let mut app = App::new();
+        app.add_message::<CollisionStarted>()
+            .add_message::<CollisionStay>()
+            .add_systems(Update, obstacle_collision_handler);
+
+        let unit = app
+            .world_mut()
+            .spawn((
+                Unit {
+                    cult: "crimson_covenant".to_string(),
+                    unit_type: "warrior".to_string(),
+                },
+                Velocity::default(),
+                MovementController::default(),
+            ))
+            .id();
+        let wall = app
+            .world_mut()
+            .spawn((Transform::default(), AABB::new(Vec3::ONE)))
+            .id();
+        let contact = ContactData {
+            entity_a: unit,
+            entity_b: wall,
+            collision_type: CollisionType::AABB,
+            contact_point: Vec3::ZERO,
+            normal: Vec3::X,
+        };
+
+        // Keep pushing into the wall for several frames after first contact
+        app.world_mut()
+            .write_message(CollisionStarted(contact.clone()));
+        app.update();
+        for _ in 0..3 {
+            app.world_mut().get_mut::<Velocity>(unit).unwrap().linear = Vec3::new(4.0, 0.0, 2.0);
+            app.world_mut()
+                .write_message(CollisionStay(contact.clone()));
+            app.update();
+
+            let velocity = app.world().get::<Velocity>(unit).unwrap().linear;
+            assert_eq!(velocity.x, 0.0, "no velocity into the wall");
+            assert_eq!(velocity.z, 2.0, "sliding along the wall is fine");
+        }
+    }
+}
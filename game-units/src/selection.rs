@@ -1,8 +1,8 @@
-use crate::{Selectable, Selected, Unit};
+use crate::{Selectable, Selected, Stats, Unit};
 use bevy::prelude::*;
 use game_physics::{
-    MovementCommand, MovementCommandEvent, MovementController, MovementPath, MovementTarget,
-    Velocity,
+    Abilities, AbilityTarget, CrowdControl, MovementCommand, MovementCommandEvent,
+    MovementController, MovementPath, MovementTarget, UseAbility, Velocity, movement_speed_scale,
 };
 #[cfg(feature = "web")]
 use web_sys::console;
@@ -33,6 +33,8 @@ pub struct InputState {
     pub shift_held: bool,
     pub ctrl_held: bool,
     pub mouse_world_position: Vec3,
+    /// Ability slot the player activated this frame, consumed when handled
+    pub ability_slot_pressed: Option<usize>,
 }
 
 impl Default for InputState {
@@ -43,6 +45,7 @@ impl Default for InputState {
             shift_held: false,
             ctrl_held: false,
             mouse_world_position: Vec3::ZERO,
+            ability_slot_pressed: None,
         }
     }
 }
@@ -182,7 +185,71 @@ pub fn movement_command_system(
     }
 }
 
+/// Hotkeys for ability slots 1-4, in slot order
+pub const ABILITY_HOTKEYS: [KeyCode; 4] =
+    [KeyCode::KeyQ, KeyCode::KeyW, KeyCode::KeyE, KeyCode::KeyR];
+
+// Ability hotkey system - Q/W/E/R queue the matching ability slot for
+// ability_input_system
+pub fn ability_hotkey_system(
+    input: Res<ButtonInput<KeyCode>>,
+    mut input_state: ResMut<InputState>,
+) {
+    if let Some(slot) = ABILITY_HOTKEYS
+        .iter()
+        .position(|&key| input.just_pressed(key))
+    {
+        input_state.ability_slot_pressed = Some(slot);
+    }
+}
+
+// Ability input system - selected units use the ability in the pressed slot,
+// aimed at the unit under the cursor or else the point under it
+pub fn ability_input_system(
+    mut input_state: ResMut<InputState>,
+    selection_state: Res<SelectionState>,
+    caster_query: Query<&Abilities>,
+    target_query: Query<(Entity, &Transform, &Selectable), With<Unit>>,
+    mut ability_events: MessageWriter<UseAbility>,
+) {
+    let Some(slot) = input_state.ability_slot_pressed.take() else {
+        return;
+    };
+
+    let cursor = input_state.mouse_world_position;
+    let target = target_query
+        .iter()
+        .filter(|(_, transform, selectable)| {
+            transform.translation.distance(cursor) <= selectable.selection_radius
+        })
+        .min_by(|(_, a, _), (_, b, _)| {
+            a.translation
+                .distance(cursor)
+                .total_cmp(&b.translation.distance(cursor))
+        })
+        .map_or(AbilityTarget::Point(cursor), |(entity, _, _)| {
+            AbilityTarget::Unit(entity)
+        });
+
+    for entity in &selection_state.selected_entities {
+        let Some(ability) = caster_query
+            .get(*entity)
+            .ok()
+            .and_then(|abilities| abilities.slots.get(slot))
+        else {
+            continue;
+        };
+
+        ability_events.write(UseAbility {
+            caster: *entity,
+            ability: ability.ability.clone(),
+            target,
+        });
+    }
+}
+
 // Physics-based movement system using velocity for smooth unit movement
+#[allow(clippy::type_complexity)]
 pub fn enhanced_movement_system(
     time: Res<Time>,
     mut unit_query: Query<
@@ -191,11 +258,22 @@ pub fn enhanced_movement_system(
             &Transform,
             &mut MovementController,
             &mut MovementPath,
+            Option<&Stats>,
+            Option<&CrowdControl>,
         ),
         With<Unit>,
     >,
 ) {
-    for (mut velocity, transform, mut controller, mut movement_path) in unit_query.iter_mut() {
+    for (mut velocity, transform, mut controller, mut movement_path, stats, control) in
+        unit_query.iter_mut()
+    {
+        // Slows and hastes scale the commanded speed; stuns hold the unit in place
+        let max_speed = controller.max_speed * movement_speed_scale(stats, control);
+        if max_speed <= 0.0 {
+            velocity.linear = Vec3::ZERO;
+            continue;
+        }
+
         // Check if we have waypoints to follow
         if !movement_path.waypoints.is_empty() {
             // Get current waypoint
@@ -221,15 +299,15 @@ pub fn enhanced_movement_system(
                     let direction = (waypoint - transform.translation).normalize();
 
                     // Apply steering forces for smooth movement
-                    let desired_velocity = direction * controller.max_speed;
+                    let desired_velocity = direction * max_speed;
                     let steering = (desired_velocity - velocity.linear) * controller.acceleration;
 
                     // Update velocity with steering force
                     velocity.linear += steering * time.delta_seconds();
 
                     // Limit velocity to max speed
-                    if velocity.linear.length() > controller.max_speed {
-                        velocity.linear = velocity.linear.normalize() * controller.max_speed;
+                    if velocity.linear.length() > max_speed {
+                        velocity.linear = velocity.linear.normalize() * max_speed;
                     }
                 }
             }
@@ -246,14 +324,14 @@ pub fn enhanced_movement_system(
                 console::log_1(&"Unit reached destination".into());
             } else {
                 // Apply physics-based movement
-                let desired_velocity = direction.normalize() * controller.max_speed;
+                let desired_velocity = direction.normalize() * max_speed;
                 let steering = (desired_velocity - velocity.linear) * controller.acceleration;
 
                 velocity.linear += steering * time.delta_seconds();
 
                 // Limit velocity
-                if velocity.linear.length() > controller.max_speed {
-                    velocity.linear = velocity.linear.normalize() * controller.max_speed;
+                if velocity.linear.length() > max_speed {
+                    velocity.linear = velocity.linear.normalize() * max_speed;
                 }
 
                 controller.is_moving = true;
@@ -347,3 +425,67 @@ fn handle_unit_selection(
         selection_state.selection_changed = true;
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hotkeys_use_the_matching_ability_slot() {
+        let mut app = App::new();
+        app.init_resource::<ButtonInput<KeyCode>>()
+            .init_resource::<InputState>()
+            .init_resource::<SelectionState>()
+            .add_message::<UseAbility>()
+            .add_systems(
+                Update,
+                (ability_hotkey_system, ability_input_system).chain(),
+            );
+
+        let caster = app
+            .world_mut()
+            .spawn(Abilities::new(["dark_pact", "blood_frenzy", "void_step"]))
+            .id();
+        app.world_mut()
+            .resource_mut::<SelectionState>()
+            .selected_entities
+            .push(caster);
+
+        app.world_mut()
+            .resource_mut::<ButtonInput<KeyCode>>()
+            .press(KeyCode::KeyW);
+        app.update();
+
+        let used: Vec<_> = app
+            .world()
+            .resource::<Messages<UseAbility>>()
+            .iter_current_update_messages()
+            .map(|event| (event.caster, event.ability.clone()))
+            .collect();
+        assert_eq!(used, [(caster, "blood_frenzy".to_string())]);
+        assert_eq!(
+            app.world().resource::<InputState>().ability_slot_pressed,
+            None,
+            "the press is consumed"
+        );
+
+        // Holding the key doesn't recast, and an empty slot does nothing
+        let casts = |app: &App| {
+            app.world()
+                .resource::<Messages<UseAbility>>()
+                .iter_current_update_messages()
+                .count()
+        };
+        app.world_mut()
+            .resource_mut::<ButtonInput<KeyCode>>()
+            .clear();
+        app.update();
+        assert_eq!(casts(&app), 0);
+
+        app.world_mut()
+            .resource_mut::<ButtonInput<KeyCode>>()
+            .press(KeyCode::KeyR);
+        app.update();
+        assert_eq!(casts(&app), 0);
+    }
+}
@@ -1,21 +1,36 @@
 use crate::visuals::*;
 use crate::{
-    AuraType, BaseStats, Experience, Leader, Selectable, Team, Unit, VeteranBonus, VeteranStatus,
-    VeteranTier,
+    AuraType, Experience, Health, Leader, Selectable, Shield, Team, Unit, VeteranStatus,
+    VeteranTier, base_unit_stats, cult_sanity, leader_abilities,
 };
 use bevy::pbr::StandardMaterial;
 use bevy::prelude::*;
 use bevy::render::alpha::AlphaMode;
 use game_physics::{
-    AABB, CollisionMask, Friction, Mass, MovementController, MovementPath, MovementTarget,
-    RigidBodyType, RigidBodyVariant, SpatialData, Velocity,
+    AABB, AbilityEffect, AbilityResolved, Airborne, CollisionMask, CollisionTeam, Detector,
+    Friction, Mass, MovementController, MovementPath, MovementTarget, MovementType, RigidBodyType,
+    RigidBodyVariant, SpatialData, Stealth, UnitClassification, UnitType, Velocity, Weapon,
+    WeaponType, Weapons,
 };
+use game_world::{MovementDomain, Observable, ObservedKind, VisionProvider};
 use std::collections::HashMap;
 #[cfg(feature = "web")]
 use web_sys::console;
 
+/// How far regular units see through the fog of war
+pub const UNIT_SIGHT_RANGE: f32 = 20.0;
+
+/// How far leaders see through the fog of war
+pub const LEADER_SIGHT_RANGE: f32 = 30.0;
+
+/// Veterancy tier leaders start at
+pub const LEADER_TIER: VeteranTier = VeteranTier::Veteran;
+
+/// Leaders sense stealthed enemies within this radius
+pub const LEADER_DETECTION_RADIUS: f32 = 12.0;
+
 /// Resource containing loaded GLB model handles
-#[derive(Resource)]
+#[derive(Resource, Default)]
 pub struct GameAssets {
     // Unit models by cult
     pub crimson_acolyte: Handle<Scene>,
@@ -40,10 +55,16 @@ pub struct GameAssets {
     pub aura_mesh: Handle<Mesh>,
     pub platform_mesh: Handle<Mesh>,
     pub veteran_star_mesh: Handle<Mesh>,
+    /// Veteran star material for each rank, shared by every star of that rank
+    pub veteran_star_materials: Vec<Handle<StandardMaterial>>,
 }
 
 impl GameAssets {
-    pub fn load(asset_server: &AssetServer, meshes: &mut Assets<Mesh>) -> Self {
+    pub fn load(
+        asset_server: &AssetServer,
+        meshes: &mut Assets<Mesh>,
+        materials: &mut Assets<StandardMaterial>,
+    ) -> Self {
         Self {
             // Load actual GLB models from game-assets folder
             crimson_acolyte: asset_server
@@ -75,9 +96,21 @@ impl GameAssets {
             aura_mesh: meshes.add(Sphere::new(1.0)),
             platform_mesh: meshes.add(Cylinder::new(2.0, 0.3)),
             veteran_star_mesh: meshes.add(Sphere::new(0.3)),
+            veteran_star_materials: (0..=VeteranTier::Legendary.rank())
+                .map(|rank| materials.add(veteran_indicator_material(rank)))
+                .collect(),
         }
     }
 
+    /// Shared veteran star material for a rank, capped at the highest rank
+    pub fn veteran_star_material(&self, rank: u32) -> Handle<StandardMaterial> {
+        let last = self.veteran_star_materials.len().saturating_sub(1);
+        self.veteran_star_materials
+            .get((rank as usize).min(last))
+            .cloned()
+            .unwrap_or_default()
+    }
+
     pub fn get_unit_model(&self, unit_type: &str, cult: &str) -> Handle<Scene> {
         match (cult, unit_type) {
             ("crimson_covenant", "cultist") => self.crimson_acolyte.clone(),
@@ -108,8 +141,9 @@ pub fn init_game_assets(
     mut commands: Commands,
     asset_server: Res<AssetServer>,
     mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
 ) {
-    let assets = GameAssets::load(&asset_server, &mut meshes);
+    let assets = GameAssets::load(&asset_server, &mut meshes, &mut materials);
     commands.insert_resource(assets);
 }
 
@@ -146,14 +180,8 @@ pub fn spawn_unit(
             Unit {
                 unit_type: unit_type.to_string(),
                 cult: cult.to_string(),
-                health: 100.0,
-                max_health: 100.0,
-                experience: 0,
-                veteran_tier: 0,
-                attack_damage: 10.0,
-                movement_speed: 5.0,
-                attack_speed: 1.0,
             },
+            Health::new(100.0),
             Team {
                 id: team_id,
                 cult: cult.to_string(),
@@ -184,13 +212,25 @@ pub fn spawn_unit(
             RigidBodyType {
                 body_type: RigidBodyVariant::Dynamic,
             },
-            CollisionMask {
-                layer: 1,       // Unit layer
-                mask: u32::MAX, // Collide with everything
-            },
+            CollisionMask::unit(),      // Unit layer, collides with everything
             SpatialData::new(position), // For spatial indexing
         ))
         .insert((
+            Name::new(unit_type.to_string()),
+            UnitType::new(UnitClassification::Infantry).biological(),
+            CollisionTeam(team_id), // Own projectiles pass through
+            get_cult_movement_domain(cult),
+            VisionProvider {
+                sight_range: UNIT_SIGHT_RANGE,
+                team: team_id,
+            },
+            Observable {
+                team: team_id,
+                kind: ObservedKind::Unit,
+                type_name: unit_type.to_string(),
+                health: 100.0,
+                max_health: 100.0,
+            },
             // === MOVEMENT & STATS COMPONENTS ===
             MovementTarget::new(position.x, position.z, position.z, 5.0),
             MovementPath {
@@ -199,20 +239,10 @@ pub fn spawn_unit(
                 movement_speed: 5.0,
                 is_moving: false,
             },
-            BaseStats {
-                base_attack_damage: 10.0,
-                base_health: 100.0,
-                base_speed: 5.0,
-                base_attack_speed: 1.0,
-                initialized: true,
-            },
+            base_unit_stats(100.0, 10.0, 5.0, 1.0),
+            cult_sanity(cult),
             Experience::default(),
-            VeteranStatus {
-                tier: VeteranTier::Recruit,
-                promotion_ready: false,
-                visual_scale: 1.0,
-                bonuses: VeteranBonus::default(),
-            },
+            VeteranStatus::default(),
         ))
         .with_children(|parent| {
             // === SELECTION INDICATOR (initially hidden) ===
@@ -307,27 +337,15 @@ pub fn spawn_leader(
             Leader {
                 name: name.to_string(),
                 cult: cult.to_string(),
-                health: 200.0,
-                max_health: 200.0,
-                shield: 50.0,
                 aura_radius: 15.0,
                 aura_type: aura_type.clone(),
                 platform_entity: None,
                 defeat_on_death: true,
                 alive: true,
-                last_ability1_use: 0.0,
-                last_ability2_use: 0.0,
             },
             Unit {
                 unit_type: "leader".to_string(),
                 cult: cult.to_string(),
-                health: 200.0,
-                max_health: 200.0,
-                experience: 0,
-                veteran_tier: 3,
-                attack_damage: 25.0,
-                movement_speed: 6.0,
-                attack_speed: 1.5,
             },
             Team {
                 id: team_id,
@@ -345,30 +363,38 @@ pub fn spawn_leader(
                 movement_speed: 6.0,
                 is_moving: false,
             },
-            BaseStats {
-                base_attack_damage: 25.0,
-                base_health: 200.0,
-                base_speed: 6.0,
-                base_attack_speed: 1.5,
-                initialized: true,
+            base_unit_stats(200.0, 25.0, 6.0, 1.5),
+            Experience::at_level(4),
+            VeteranStatus::new(LEADER_TIER),
+        ))
+        .insert((
+            Name::new("leader"),
+            Health::new(200.0),
+            // Leaders strike in melee and blast air or ground from range
+            Weapons::new([
+                Weapon::new(WeaponType::Melee, 25.0, 0.7, 2.5),
+                Weapon::new(WeaponType::Magic, 15.0, 1.5, 15.0).targeting(true, true),
+            ]),
+            Shield::new(50.0),
+            UnitType::new(UnitClassification::Hero).biological(),
+            cult_sanity(cult),
+            leader_abilities(cult),
+            SpatialData::new(position), // For spatial indexing
+            CollisionTeam(team_id),     // Own projectiles pass through
+            get_cult_movement_domain(cult),
+            VisionProvider {
+                sight_range: LEADER_SIGHT_RANGE,
+                team: team_id,
             },
-            Experience {
-                current: 0,
-                total_earned: 1000,
-                level: 5,
-                kills: 0,
-                buildings_destroyed: 0,
+            Detector {
+                radius: LEADER_DETECTION_RADIUS,
             },
-            VeteranStatus {
-                tier: VeteranTier::Veteran,
-                promotion_ready: false,
-                visual_scale: 1.2,
-                bonuses: VeteranBonus {
-                    health_multiplier: 1.5,
-                    damage_multiplier: 1.3,
-                    speed_multiplier: 1.2,
-                    xp_multiplier: 1.0,
-                },
+            Observable {
+                team: team_id,
+                kind: ObservedKind::Unit,
+                type_name: "leader".to_string(),
+                health: 200.0,
+                max_health: 200.0,
             },
         ))
         .with_children(|parent| {
@@ -454,24 +480,13 @@ pub fn spawn_leader(
                         HealthBarFill,
                     ));
                 });
-
-            // === VETERAN STAR (leaders always have one) ===
-            parent.spawn((
-                Name::new("VeteranIndicator"),
-                Mesh3d(assets.veteran_star_mesh.clone()),
-                MeshMaterial3d(materials.add(StandardMaterial {
-                    base_color: Color::srgb(1.0, 0.85, 0.0),
-                    emissive: LinearRgba::rgb(2.0, 1.7, 0.0),
-                    metallic: 0.9,
-                    perceptual_roughness: 0.1,
-                    ..default()
-                })),
-                Transform::from_translation(Vec3::new(0.0, 3.5, 0.0)),
-                VeteranIndicator,
-            ));
         })
         .id();
 
+    // Leaders start as veterans without a promotion, so give them their star
+    let star = create_veteran_indicator(commands, assets, LEADER_TIER.rank());
+    commands.entity(entity).add_child(star);
+
     #[cfg(feature = "web")]
     console::log_1(
         &format!(
@@ -543,6 +558,69 @@ fn get_cult_color(cult: &str) -> Color {
     }
 }
 
+// Default movement domain for a cult's units (the Deep Ones are at home in water)
+pub fn get_cult_movement_domain(cult: &str) -> MovementDomain {
+    match cult {
+        "deep_ones" => MovementDomain::Amphibious,
+        _ => MovementDomain::Land,
+    }
+}
+
+// Keep what enemies can observe about a unit or building in sync with its health
+pub fn sync_observable_system(mut query: Query<(&Health, &mut Observable), Changed<Health>>) {
+    for (health, mut observable) in query.iter_mut() {
+        observable.health = health.current;
+        observable.max_health = health.maximum;
+    }
+}
+
+/// How far from the target point summoned units appear
+pub const SUMMON_SPREAD: f32 = 2.0;
+
+// Summon system - resolved abilities with summon effects call units from the
+// caster's cult templates, in a ring around where the ability landed
+pub fn ability_summon_system(
+    mut commands: Commands,
+    mut resolved_events: MessageReader<AbilityResolved>,
+    caster_query: Query<&Team>,
+    templates: Res<UnitTemplates>,
+    assets: Option<Res<GameAssets>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    let Some(assets) = assets else {
+        return;
+    };
+
+    for event in resolved_events.read() {
+        let Ok(team) = caster_query.get(event.caster) else {
+            continue;
+        };
+
+        for effect in &event.effects {
+            let AbilityEffect::Summon { unit_type, count } = effect else {
+                continue;
+            };
+            let Some(template) = templates.templates.get(unit_type) else {
+                continue;
+            };
+
+            for i in 0..*count {
+                let angle = i as f32 / *count as f32 * std::f32::consts::TAU;
+                let offset = Vec3::new(angle.cos(), 0.0, angle.sin()) * SUMMON_SPREAD;
+                spawn_unit_from_template(
+                    &mut commands,
+                    template,
+                    event.point + offset,
+                    &team.cult,
+                    team.id,
+                    &assets,
+                    &mut materials,
+                );
+            }
+        }
+    }
+}
+
 // Debug spawning system for testing (updated with visual assets)
 pub fn debug_spawn_system(
     input: Res<ButtonInput<KeyCode>>,
@@ -608,6 +686,12 @@ pub struct UnitTemplate {
     pub attack_speed: f32,
     pub cost: HashMap<String, u32>,
     pub build_time: f32,
+    pub movement_domain: MovementDomain,
+    pub movement_type: MovementType,
+    pub stealth: Option<Stealth>,
+    pub weapons: Weapons,
+    /// Classification for targeting and the damage matrix
+    pub class: UnitType,
 }
 
 impl Default for UnitTemplates {
@@ -626,6 +710,11 @@ impl Default for UnitTemplates {
                 attack_speed: 1.2,
                 cost: HashMap::from([("energy".to_string(), 50)]),
                 build_time: 30.0,
+                movement_domain: MovementDomain::Land,
+                movement_type: MovementType::Ground,
+                stealth: None,
+                weapons: Weapons::new([Weapon::new(WeaponType::Melee, 12.0, 0.8, 2.0)]),
+                class: UnitType::new(UnitClassification::Infantry).biological(),
             },
         );
 
@@ -640,6 +729,11 @@ impl Default for UnitTemplates {
                 attack_speed: 0.8,
                 cost: HashMap::from([("energy".to_string(), 100), ("materials".to_string(), 25)]),
                 build_time: 60.0,
+                movement_domain: MovementDomain::Land,
+                movement_type: MovementType::Ground,
+                stealth: None,
+                weapons: Weapons::new([Weapon::new(WeaponType::Melee, 20.0, 1.25, 2.5)]),
+                class: UnitType::new(UnitClassification::Infantry).biological(),
             },
         );
 
@@ -655,6 +749,11 @@ impl Default for UnitTemplates {
                 attack_speed: 1.0,
                 cost: HashMap::from([("energy".to_string(), 60)]),
                 build_time: 35.0,
+                movement_domain: MovementDomain::Amphibious,
+                movement_type: MovementType::Ground,
+                stealth: None,
+                weapons: Weapons::new([Weapon::new(WeaponType::Magic, 8.0, 1.0, 12.0)]),
+                class: UnitType::new(UnitClassification::Infantry).biological(),
             },
         );
 
@@ -669,6 +768,11 @@ impl Default for UnitTemplates {
                 attack_speed: 0.6,
                 cost: HashMap::from([("energy".to_string(), 120), ("materials".to_string(), 30)]),
                 build_time: 75.0,
+                movement_domain: MovementDomain::Amphibious,
+                movement_type: MovementType::Ground,
+                stealth: None,
+                weapons: Weapons::new([Weapon::new(WeaponType::Melee, 15.0, 1.6, 2.5)]),
+                class: UnitType::new(UnitClassification::Monster).biological(),
             },
         );
 
@@ -684,6 +788,11 @@ impl Default for UnitTemplates {
                 attack_speed: 1.5,
                 cost: HashMap::from([("energy".to_string(), 40)]),
                 build_time: 25.0,
+                movement_domain: MovementDomain::Land,
+                movement_type: MovementType::Ground,
+                stealth: Some(Stealth::while_stationary(2.0)),
+                weapons: Weapons::new([Weapon::new(WeaponType::Ranged, 10.0, 0.7, 15.0)]),
+                class: UnitType::new(UnitClassification::Infantry).biological(),
             },
         );
 
@@ -698,6 +807,36 @@ impl Default for UnitTemplates {
                 attack_speed: 2.0,
                 cost: HashMap::from([("energy".to_string(), 80), ("materials".to_string(), 20)]),
                 build_time: 45.0,
+                movement_domain: MovementDomain::Land,
+                movement_type: MovementType::Ground,
+                stealth: Some(Stealth::permanent()),
+                weapons: Weapons::new([
+                    Weapon::new(WeaponType::Melee, 25.0, 0.5, 2.0).with_penetration(0.3)
+                ]),
+                class: UnitType::new(UnitClassification::Infantry).ethereal(),
+            },
+        );
+
+        templates.insert(
+            "void_harbinger".to_string(),
+            UnitTemplate {
+                unit_type: "void_harbinger".to_string(),
+                model_name: "void_harbinger".to_string(),
+                base_health: 110.0,
+                base_attack: 18.0,
+                base_speed: 6.0,
+                attack_speed: 1.0,
+                cost: HashMap::from([("energy".to_string(), 120), ("materials".to_string(), 40)]),
+                build_time: 70.0,
+                movement_domain: MovementDomain::Hover,
+                movement_type: MovementType::Flying,
+                stealth: None,
+                weapons: Weapons::new([
+                    Weapon::new(WeaponType::Magic, 18.0, 1.0, 14.0).targeting(true, true)
+                ]),
+                class: UnitType::new(UnitClassification::Monster)
+                    .ethereal()
+                    .flying(),
             },
         );
 
@@ -731,14 +870,8 @@ pub fn spawn_unit_from_template(
             Unit {
                 unit_type: template.unit_type.clone(),
                 cult: cult.to_string(),
-                health: template.base_health,
-                max_health: template.base_health,
-                experience: 0,
-                veteran_tier: 0,
-                attack_damage: template.base_attack,
-                movement_speed: template.base_speed,
-                attack_speed: template.attack_speed,
             },
+            Health::new(template.base_health),
             Team {
                 id: team_id,
                 cult: cult.to_string(),
@@ -755,19 +888,33 @@ pub fn spawn_unit_from_template(
                 movement_speed: template.base_speed,
                 is_moving: false,
             },
-            BaseStats {
-                base_attack_damage: template.base_attack,
-                base_health: template.base_health,
-                base_speed: template.base_speed,
-                base_attack_speed: template.attack_speed,
-                initialized: true,
-            },
+            base_unit_stats(
+                template.base_health,
+                template.base_attack,
+                template.base_speed,
+                template.attack_speed,
+            ),
             Experience::default(),
-            VeteranStatus {
-                tier: VeteranTier::Recruit,
-                promotion_ready: false,
-                visual_scale: 1.0,
-                bonuses: VeteranBonus::default(),
+            VeteranStatus::default(),
+        ))
+        .insert((
+            Name::new(template.unit_type.clone()),
+            template.weapons.clone(),
+            template.class.clone(),
+            SpatialData::new(position), // For spatial indexing
+            CollisionTeam(team_id),     // Own projectiles pass through
+            cult_sanity(cult),
+            template.movement_domain,
+            VisionProvider {
+                sight_range: UNIT_SIGHT_RANGE,
+                team: team_id,
+            },
+            Observable {
+                team: team_id,
+                kind: ObservedKind::Unit,
+                type_name: template.unit_type.clone(),
+                health: template.base_health,
+                max_health: template.base_health,
             },
         ))
         .with_children(|parent| {
@@ -821,6 +968,24 @@ pub fn spawn_unit_from_template(
         })
         .id();
 
+    // Void infiltrators slip out of sight
+    if let Some(stealth) = &template.stealth {
+        commands.entity(entity).insert(stealth.clone());
+    }
+
+    // Flyers get their own movement layer instead of ground pathfinding
+    if template.movement_type == MovementType::Flying {
+        commands.entity(entity).insert((
+            MovementController {
+                max_speed: template.base_speed,
+                movement_type: MovementType::Flying,
+                ..default()
+            },
+            Airborne::default(),
+            CollisionMask::flying(),
+        ));
+    }
+
     #[cfg(feature = "web")]
     console::log_1(
         &format!(
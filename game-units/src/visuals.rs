@@ -1,4 +1,4 @@
-use crate::{AuraType, Health, Leader, Selected, SelectionState, Team, Unit};
+use crate::{AuraType, GameAssets, Health, Leader, Promoted, Selected, SelectionState, Team, Unit};
 use bevy::pbr::StandardMaterial;
 use bevy::prelude::*;
 use bevy::render::alpha::AlphaMode;
@@ -122,16 +122,50 @@ pub fn animate_leader_platforms(
     }
 }
 
-/// System to update veteran indicator visuals
+/// Height of the veteran star above the unit
+pub const VETERAN_INDICATOR_Y_OFFSET: f32 = 3.0;
+
+/// Size of the veteran star for a veterancy rank
+pub fn veteran_indicator_scale(rank: u32) -> Vec3 {
+    Vec3::splat(0.5 + rank as f32 * 0.2)
+}
+
+/// System to give promoted units a veteran star coloured and sized by tier
 pub fn update_veteran_indicators(
-    mut indicator_query: Query<(&mut Transform, &ChildOf), With<VeteranIndicator>>,
-    unit_query: Query<&Unit>,
+    mut commands: Commands,
+    mut promotions: MessageReader<Promoted>,
+    children_query: Query<&Children>,
+    mut indicator_query: Query<
+        (&mut Transform, &mut MeshMaterial3d<StandardMaterial>),
+        With<VeteranIndicator>,
+    >,
+    assets: Option<Res<GameAssets>>,
 ) {
-    for (mut transform, parent) in indicator_query.iter_mut() {
-        if let Ok(unit) = unit_query.get(parent.parent()) {
-            // Scale based on veteran tier
-            let scale = 0.5 + (unit.veteran_tier as f32 * 0.2);
-            transform.scale = Vec3::splat(scale);
+    let Some(assets) = assets else {
+        return;
+    };
+
+    for promotion in promotions.read() {
+        let rank = promotion.to.rank();
+
+        let existing = children_query
+            .get(promotion.entity)
+            .ok()
+            .and_then(|children| {
+                children
+                    .iter()
+                    .find(|child| indicator_query.contains(*child))
+            });
+
+        if let Some(indicator) = existing
+            && let Ok((mut transform, mut material)) = indicator_query.get_mut(indicator)
+        {
+            transform.scale = veteran_indicator_scale(rank);
+            material.0 = assets.veteran_star_material(rank);
+        } else if commands.get_entity(promotion.entity).is_ok() {
+            // First promotion: the unit gets its star
+            let indicator = create_veteran_indicator(&mut commands, &assets, rank);
+            commands.entity(promotion.entity).add_child(indicator);
         }
     }
 }
@@ -258,32 +292,33 @@ pub fn create_leader_platform(
         .id()
 }
 
-/// Create veteran star indicator
-pub fn create_veteran_indicator(
-    commands: &mut Commands,
-    meshes: &mut Assets<Mesh>,
-    materials: &mut Assets<StandardMaterial>,
-    tier: u32,
-) -> Entity {
-    let color = match tier {
+/// Star material for a veterancy rank
+pub(crate) fn veteran_indicator_material(rank: u32) -> StandardMaterial {
+    let color = match rank {
         1 => Color::srgb(0.7, 0.7, 0.7),  // Silver
         2 => Color::srgb(1.0, 0.85, 0.0), // Gold
         3 => Color::srgb(0.0, 0.8, 1.0),  // Diamond
         _ => Color::srgb(0.8, 0.0, 0.8),  // Legendary purple
     };
+    StandardMaterial {
+        base_color: color,
+        emissive: color_to_emissive(color) * 2.0,
+        metallic: 0.9,
+        perceptual_roughness: 0.1,
+        ..default()
+    }
+}
 
+/// Create veteran star indicator for a veterancy rank, sharing the star mesh
+/// and per-rank material
+pub fn create_veteran_indicator(commands: &mut Commands, assets: &GameAssets, rank: u32) -> Entity {
     commands
         .spawn((
             Name::new("VeteranIndicator"),
-            Mesh3d(meshes.add(Sphere::new(0.3))),
-            MeshMaterial3d(materials.add(StandardMaterial {
-                base_color: color,
-                emissive: color_to_emissive(color) * 2.0,
-                metallic: 0.9,
-                perceptual_roughness: 0.1,
-                ..default()
-            })),
-            Transform::from_translation(Vec3::new(0.0, 3.0, 0.0)),
+            Mesh3d(assets.veteran_star_mesh.clone()),
+            MeshMaterial3d(assets.veteran_star_material(rank)),
+            Transform::from_translation(Vec3::new(0.0, VETERAN_INDICATOR_Y_OFFSET, 0.0))
+                .with_scale(veteran_indicator_scale(rank)),
             VeteranIndicator,
         ))
         .id()
@@ -450,3 +485,87 @@ fn color_to_emissive(color: Color) -> LinearRgba {
     let srgba = color.to_srgba();
     LinearRgba::rgb(srgba.red, srgba.green, srgba.blue)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::VeteranTier;
+
+    fn veteran_app() -> App {
+        let mut app = App::new();
+        app.init_resource::<Assets<Mesh>>()
+            .init_resource::<Assets<StandardMaterial>>()
+            .add_message::<Promoted>()
+            .add_systems(Update, update_veteran_indicators);
+
+        let world = app.world_mut();
+        let veteran_star_mesh = world.resource_mut::<Assets<Mesh>>().add(Sphere::new(0.3));
+        let mut materials = world.resource_mut::<Assets<StandardMaterial>>();
+        let veteran_star_materials = (0..=VeteranTier::Legendary.rank())
+            .map(|rank| materials.add(veteran_indicator_material(rank)))
+            .collect();
+        world.insert_resource(GameAssets {
+            veteran_star_mesh,
+            veteran_star_materials,
+            ..default()
+        });
+        app
+    }
+
+    fn promote(app: &mut App, entity: Entity, from: VeteranTier, to: VeteranTier) {
+        app.world_mut().write_message(Promoted { entity, from, to });
+        app.update();
+    }
+
+    /// The unit's veteran stars with their scale and material
+    fn stars(app: &App, entity: Entity) -> Vec<(Vec3, Handle<StandardMaterial>)> {
+        let children: Vec<Entity> = app
+            .world()
+            .get::<Children>(entity)
+            .map(|children| children.iter().collect())
+            .unwrap_or_default();
+        children
+            .into_iter()
+            .filter(|child| app.world().get::<VeteranIndicator>(*child).is_some())
+            .map(|child| {
+                let star = app.world().entity(child);
+                (
+                    star.get::<Transform>().unwrap().scale,
+                    star.get::<MeshMaterial3d<StandardMaterial>>()
+                        .unwrap()
+                        .0
+                        .clone(),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn promotions_spawn_and_rescale_the_veteran_star() {
+        let mut app = veteran_app();
+        let unit = app.world_mut().spawn(Transform::default()).id();
+        let material_count = app.world().resource::<Assets<StandardMaterial>>().len();
+        let assets = app.world().resource::<GameAssets>();
+        let (regular, elite) = (
+            assets.veteran_star_material(VeteranTier::Regular.rank()),
+            assets.veteran_star_material(VeteranTier::Elite.rank()),
+        );
+
+        promote(&mut app, unit, VeteranTier::Recruit, VeteranTier::Regular);
+        assert_eq!(
+            stars(&app, unit),
+            vec![(veteran_indicator_scale(1), regular)]
+        );
+
+        // The same star grows and changes colour rather than a second appearing
+        promote(&mut app, unit, VeteranTier::Regular, VeteranTier::Elite);
+        assert_eq!(stars(&app, unit), vec![(veteran_indicator_scale(3), elite)]);
+
+        // Stars share the cached mesh and materials
+        assert_eq!(
+            app.world().resource::<Assets<StandardMaterial>>().len(),
+            material_count
+        );
+        assert_eq!(app.world().resource::<Assets<Mesh>>().len(), 1);
+    }
+}
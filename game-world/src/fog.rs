@@ -1,7 +1,22 @@
 //! Production fog of war system for Cosmic Dominion
 
+use crate::map::{GameMap, TileType, world_to_grid};
+use crate::spawning::LeadershipBuilding;
 use bevy::prelude::*;
-use std::collections::HashMap;
+use game_physics::{AABB, Airborne, Obstacle};
+use std::collections::{HashMap, HashSet};
+
+/// Team id the local player controls
+pub const PLAYER_TEAM: u32 = 1;
+
+/// Height of a vision provider's eyes above the ground it stands on
+pub const VISION_EYE_HEIGHT: f32 = 1.5;
+
+/// How far a cliff rises above its tile for sight purposes
+pub const CLIFF_SIGHT_HEIGHT: f32 = 3.0;
+
+/// Sight height of a blocker without an `AABB` to measure
+const DEFAULT_BLOCKER_HEIGHT: f32 = 2.0;
 
 /// Component marking an entity as having fog of war applied
 #[derive(Component)]
@@ -29,26 +44,174 @@ pub enum VisibilityState {
     Visible,  // Currently visible
 }
 
-/// Resource storing the visibility map for the entire game world
+/// What one team has seen
+#[derive(Clone, Debug, Default)]
+pub struct VisibilityLayer {
+    pub tiles: HashMap<(i32, i32), VisibilityState>,
+}
+
+impl VisibilityLayer {
+    pub fn state(&self, tile: (i32, i32)) -> VisibilityState {
+        self.tiles
+            .get(&tile)
+            .copied()
+            .unwrap_or(VisibilityState::Hidden)
+    }
+}
+
+/// Resource storing the visibility map for the entire game world.
+///
+/// Every team has its own layer in `layers`; `tiles` mirrors the local
+/// player's layer and is what the fog overlay renders.
 #[derive(Resource, Default)]
 pub struct VisibilityMap {
     pub tiles: HashMap<(i32, i32), VisibilityState>,
-    pub sight_blockers: HashMap<(i32, i32), bool>,
+    /// Top of whatever blocks sight on a tile (cliffs, buildings,
+    /// obstacles); a viewer whose eyes are higher sees over it
+    pub sight_blockers: HashMap<(i32, i32), f32>,
+    /// Bumped whenever `sight_blockers` changes, invalidating cached vision
+    pub blockers_revision: u64,
+    pub layers: HashMap<u32, VisibilityLayer>,
+}
+
+impl VisibilityMap {
+    /// Visibility of a tile for a team (including vision shared by allies)
+    pub fn state_for(&self, team: u32, tile: (i32, i32)) -> VisibilityState {
+        self.layers
+            .get(&team)
+            .map_or(VisibilityState::Hidden, |layer| layer.state(tile))
+    }
+
+    /// Whether a team currently sees a tile
+    pub fn is_visible_to(&self, team: u32, tile: (i32, i32)) -> bool {
+        self.state_for(team, tile) == VisibilityState::Visible
+    }
+
+    /// Whether a team has ever seen a tile (including right now)
+    pub fn is_revealed_to(&self, team: u32, tile: (i32, i32)) -> bool {
+        self.state_for(team, tile) != VisibilityState::Hidden
+    }
+
+    pub fn layer_mut(&mut self, team: u32) -> &mut VisibilityLayer {
+        self.layers.entry(team).or_default()
+    }
+
+    /// Replace current vision with what each team (and its allies) sees now.
+    ///
+    /// `seen` holds the tiles each team's own providers see this frame.
+    /// Tiles that drop out of sight stay revealed.
+    pub fn apply_vision(
+        &mut self,
+        seen: &HashMap<u32, HashSet<(i32, i32)>>,
+        shared_vision: &SharedVision,
+    ) {
+        for layer in self.layers.values_mut() {
+            for state in layer.tiles.values_mut() {
+                if *state == VisibilityState::Visible {
+                    *state = VisibilityState::Revealed;
+                }
+            }
+        }
+
+        let teams: HashSet<u32> = self.layers.keys().chain(seen.keys()).copied().collect();
+
+        for team in teams {
+            let layer = self.layer_mut(team);
+            for (&source, tiles) in seen {
+                if !shared_vision.shares_with(team, source) {
+                    continue;
+                }
+                for &tile in tiles {
+                    layer.tiles.insert(tile, VisibilityState::Visible);
+                }
+            }
+        }
+    }
+
+    /// Copy a team's layer into `tiles` for rendering
+    pub fn sync_rendered_layer(&mut self, team: u32) {
+        let Some(layer) = self.layers.get(&team) else {
+            return;
+        };
+
+        for (tile, state) in self.tiles.iter_mut() {
+            *state = layer.state(*tile);
+        }
+        for (&tile, &state) in &layer.tiles {
+            self.tiles.insert(tile, state);
+        }
+    }
+}
+
+/// Which teams share vision with each other. A team always shares with itself.
+#[derive(Resource, Default, Debug)]
+pub struct SharedVision {
+    pub allies: HashMap<u32, HashSet<u32>>,
+}
+
+impl SharedVision {
+    /// Share vision both ways between two teams
+    pub fn share(&mut self, team_a: u32, team_b: u32) {
+        self.allies.entry(team_a).or_default().insert(team_b);
+        self.allies.entry(team_b).or_default().insert(team_a);
+    }
+
+    /// Stop sharing vision between two teams
+    pub fn revoke(&mut self, team_a: u32, team_b: u32) {
+        if let Some(allies) = self.allies.get_mut(&team_a) {
+            allies.remove(&team_b);
+        }
+        if let Some(allies) = self.allies.get_mut(&team_b) {
+            allies.remove(&team_a);
+        }
+    }
+
+    /// Whether `viewer` gets to see what `source` sees
+    pub fn shares_with(&self, viewer: u32, source: u32) -> bool {
+        viewer == source
+            || self
+                .allies
+                .get(&viewer)
+                .is_some_and(|allies| allies.contains(&source))
+    }
+}
+
+/// The team whose fog layer is rendered on this client
+#[derive(Resource, Clone, Copy, Debug)]
+pub struct LocalPlayer {
+    pub team: u32,
+}
+
+impl Default for LocalPlayer {
+    fn default() -> Self {
+        Self { team: PLAYER_TEAM }
+    }
 }
 
 /// Component marking an entity as a vision provider (units, buildings)
 #[derive(Component)]
+#[require(VisionCache)]
 pub struct VisionProvider {
     pub sight_range: f32,
-    pub faction: Faction,
+    pub team: u32,
 }
 
-/// Faction identifier for vision sharing
-#[derive(Component, Clone, Copy, Debug, PartialEq)]
-pub enum Faction {
-    Player,
-    Enemy,
-    Neutral,
+/// Tiles a provider saw on its last field-of-view pass. Reused until the
+/// provider changes tile, range or altitude, or the blockers change.
+#[derive(Component, Default, Debug)]
+pub struct VisionCache {
+    pub key: Option<VisionKey>,
+    pub tiles: HashSet<(i32, i32)>,
+}
+
+/// Everything a provider's field of view depends on
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct VisionKey {
+    pub tile: (i32, i32),
+    pub sight_range: f32,
+    pub airborne: bool,
+    pub tile_size: f32,
+    pub blockers_revision: u64,
 }
 
 /// Component for fog overlay entities
@@ -62,6 +225,8 @@ pub struct FogOverlay {
 pub fn initialize_fog_system(
     mut commands: Commands,
     mut visibility_map: ResMut<VisibilityMap>,
+    local_player: Res<LocalPlayer>,
+    game_map: Res<GameMap>,
     mut meshes: ResMut<Assets<Mesh>>,
     mut materials: ResMut<Assets<StandardMaterial>>,
 ) {
@@ -79,6 +244,12 @@ pub fn initialize_fog_system(
             };
 
             visibility_map.tiles.insert((x, z), initial_state);
+            if initial_state == VisibilityState::Visible {
+                visibility_map
+                    .layer_mut(local_player.team)
+                    .tiles
+                    .insert((x, z), initial_state);
+            }
 
             // Create fog overlay for this tile if not initially visible
             if initial_state != VisibilityState::Visible {
@@ -88,6 +259,7 @@ pub fn initialize_fog_system(
                     &mut materials,
                     x,
                     z,
+                    game_map.tile_size,
                     initial_state,
                 );
             }
@@ -102,9 +274,9 @@ fn spawn_fog_overlay(
     materials: &mut ResMut<Assets<StandardMaterial>>,
     tile_x: i32,
     tile_z: i32,
+    tile_size: f32,
     visibility_state: VisibilityState,
 ) {
-    let tile_size = 10.0;
     let fog_height = 5.0; // Height of fog overlay above terrain
 
     // Create fog mesh (plane above the tile)
@@ -152,10 +324,78 @@ fn spawn_fog_overlay(
     ));
 }
 
-/// Update fog of war based on vision providers
+/// Rebuild `sight_blockers` from cliff tiles and building/obstacle entities.
+///
+/// Only runs when the map or a blocker changed. Each tile stores the highest
+/// point that could block sight on it, and the revision only moves when
+/// something actually changed.
+#[allow(clippy::type_complexity)]
+pub fn update_sight_blockers_system(
+    mut visibility_map: ResMut<VisibilityMap>,
+    game_map: Res<GameMap>,
+    blockers: Query<(&Transform, Option<&AABB>), Or<(With<Obstacle>, With<LeadershipBuilding>)>>,
+    changed_blockers: Query<
+        (),
+        (
+            Or<(With<Obstacle>, With<LeadershipBuilding>)>,
+            Or<(
+                Changed<Transform>,
+                Changed<AABB>,
+                Added<Obstacle>,
+                Added<LeadershipBuilding>,
+            )>,
+        ),
+    >,
+    mut removed_obstacles: RemovedComponents<Obstacle>,
+    mut removed_buildings: RemovedComponents<LeadershipBuilding>,
+    mut removed_aabbs: RemovedComponents<AABB>,
+) {
+    let removed = removed_obstacles.read().count()
+        + removed_buildings.read().count()
+        + removed_aabbs.read().count();
+    if !game_map.is_changed() && changed_blockers.is_empty() && removed == 0 {
+        return;
+    }
+
+    let mut sight_blockers = HashMap::new();
+
+    for (&position, tile) in &game_map.tiles {
+        let top = match tile.tile_type {
+            TileType::Cliff => tile.height + CLIFF_SIGHT_HEIGHT,
+            _ => tile.height,
+        };
+        sight_blockers.insert(position, top);
+    }
+
+    for (transform, aabb) in blockers.iter() {
+        let tile = world_to_grid(transform.translation, game_map.tile_size);
+        let ground = game_map.tiles.get(&tile).map_or(0.0, |tile| tile.height);
+        let top = match aabb {
+            Some(aabb) => transform.translation.y + aabb.half_extents.y,
+            None => ground + DEFAULT_BLOCKER_HEIGHT,
+        };
+        let entry = sight_blockers.entry(tile).or_insert(ground);
+        *entry = entry.max(top);
+    }
+
+    if sight_blockers != visibility_map.sight_blockers {
+        visibility_map.sight_blockers = sight_blockers;
+        visibility_map.blockers_revision += 1;
+    }
+}
+
+/// Update fog of war based on vision providers.
+///
+/// Every team's layer is simulated; only the local player's is rendered.
+/// A provider's field of view is only recomputed when it changes tile or
+/// the blockers change.
+#[allow(clippy::type_complexity)]
 pub fn update_fog_system(
     mut visibility_map: ResMut<VisibilityMap>,
-    vision_providers: Query<(&Transform, &VisionProvider)>,
+    shared_vision: Res<SharedVision>,
+    local_player: Res<LocalPlayer>,
+    game_map: Res<GameMap>,
+    mut vision_providers: Query<(&Transform, &VisionProvider, &mut VisionCache, Has<Airborne>)>,
     mut fog_overlays: Query<(
         &FogOverlay,
         &mut FogOfWar,
@@ -164,54 +404,24 @@ pub fn update_fog_system(
     mut materials: ResMut<Assets<StandardMaterial>>,
     time: Res<Time>,
 ) {
-    let tile_size = 10.0;
-
-    // Reset all tiles to not visible (but keep revealed state)
-    for (_, state) in visibility_map.tiles.iter_mut() {
-        if *state == VisibilityState::Visible {
-            *state = VisibilityState::Revealed;
-        }
+    // Tiles each team's own providers can see this frame
+    let mut seen: HashMap<u32, HashSet<(i32, i32)>> = HashMap::new();
+    for (transform, vision_provider, mut cache, airborne) in vision_providers.iter_mut() {
+        refresh_vision_cache(
+            &mut cache,
+            transform.translation,
+            vision_provider.sight_range,
+            airborne,
+            &visibility_map,
+            &game_map,
+        );
+        seen.entry(vision_provider.team)
+            .or_default()
+            .extend(cache.tiles.iter().copied());
     }
 
-    // Update visibility based on vision providers
-    for (transform, vision_provider) in vision_providers.iter() {
-        // Only process player faction vision
-        if vision_provider.faction != Faction::Player {
-            continue;
-        }
-
-        let provider_tile_x = (transform.translation.x / tile_size).round() as i32;
-        let provider_tile_z = (transform.translation.z / tile_size).round() as i32;
-
-        // Calculate visible tiles using line of sight
-        let sight_range_tiles = (vision_provider.sight_range / tile_size).ceil() as i32;
-
-        for dx in -sight_range_tiles..=sight_range_tiles {
-            for dz in -sight_range_tiles..=sight_range_tiles {
-                let tile_x = provider_tile_x + dx;
-                let tile_z = provider_tile_z + dz;
-
-                // Check if within sight range
-                let distance = ((dx * dx + dz * dz) as f32).sqrt() * tile_size;
-                if distance > vision_provider.sight_range {
-                    continue;
-                }
-
-                // Check line of sight
-                if has_line_of_sight(
-                    provider_tile_x,
-                    provider_tile_z,
-                    tile_x,
-                    tile_z,
-                    &visibility_map.sight_blockers,
-                ) {
-                    visibility_map
-                        .tiles
-                        .insert((tile_x, tile_z), VisibilityState::Visible);
-                }
-            }
-        }
-    }
+    visibility_map.apply_vision(&seen, &shared_vision);
+    visibility_map.sync_rendered_layer(local_player.team);
 
     // Update fog overlay visuals
     for (fog_overlay, mut fog, material_handle) in fog_overlays.iter_mut() {
@@ -240,97 +450,248 @@ pub fn update_fog_system(
     }
 }
 
-/// Check if there's line of sight between two tiles
-fn has_line_of_sight(
-    x1: i32,
-    z1: i32,
-    x2: i32,
-    z2: i32,
-    sight_blockers: &HashMap<(i32, i32), bool>,
-) -> bool {
-    // Bresenham's line algorithm for line of sight
-    let dx = (x2 - x1).abs();
-    let dz = (z2 - z1).abs();
-    let sx = if x1 < x2 { 1 } else { -1 };
-    let sz = if z1 < z2 { 1 } else { -1 };
-
-    let mut x = x1;
-    let mut z = z1;
-    let mut err = dx - dz;
-
-    loop {
-        // Check if current tile blocks sight (skip the starting tile)
-        if (x != x1 || z != z1) && sight_blockers.get(&(x, z)).copied().unwrap_or(false) {
-            return false;
-        }
+/// Recompute a provider's field of view if anything it depends on changed
+fn refresh_vision_cache(
+    cache: &mut VisionCache,
+    position: Vec3,
+    sight_range: f32,
+    airborne: bool,
+    visibility_map: &VisibilityMap,
+    game_map: &GameMap,
+) {
+    let key = VisionKey {
+        tile: world_to_grid(position, game_map.tile_size),
+        sight_range,
+        airborne,
+        tile_size: game_map.tile_size,
+        blockers_revision: visibility_map.blockers_revision,
+    };
+    if cache.key == Some(key) {
+        return;
+    }
 
-        if x == x2 && z == z2 {
-            break;
-        }
+    let eye_height = if airborne {
+        f32::INFINITY // Flyers see over everything
+    } else {
+        game_map
+            .tiles
+            .get(&key.tile)
+            .map_or(0.0, |tile| tile.height)
+            + VISION_EYE_HEIGHT
+    };
+    let range_tiles = (sight_range / game_map.tile_size).max(0.0);
+
+    cache.tiles.clear();
+    compute_field_of_view(
+        key.tile,
+        range_tiles,
+        |tile| {
+            visibility_map
+                .sight_blockers
+                .get(&tile)
+                .is_some_and(|&top| top > eye_height)
+        },
+        |tile| {
+            cache.tiles.insert(tile);
+        },
+    );
+    cache.key = Some(key);
+}
+
+// ==============================================================================
+// SYMMETRIC SHADOWCASTING
+// ==============================================================================
+
+/// Symmetric shadowcasting field of view.
+///
+/// Marks every tile within `range` tiles of `origin` that is visible from it.
+/// Blocking tiles are visible themselves but hide what is behind them, and
+/// visibility is symmetric: if A sees B then B sees A.
+pub fn compute_field_of_view(
+    origin: (i32, i32),
+    range: f32,
+    is_blocking: impl Fn((i32, i32)) -> bool,
+    mut mark_visible: impl FnMut((i32, i32)),
+) {
+    mark_visible(origin);
+
+    let max_depth = range.floor() as i32;
+    for quadrant in Quadrant::ALL {
+        let mut scanner = Scanner {
+            origin,
+            quadrant,
+            range,
+            max_depth,
+            is_blocking: &is_blocking,
+            mark_visible: &mut mark_visible,
+        };
+        scanner.scan(Row {
+            depth: 1,
+            start_slope: -1.0,
+            end_slope: 1.0,
+        });
+    }
+}
 
-        let e2 = 2 * err;
+#[derive(Clone, Copy)]
+enum Quadrant {
+    North,
+    East,
+    South,
+    West,
+}
 
-        if e2 > -dz {
-            err -= dz;
-            x += sx;
+impl Quadrant {
+    const ALL: [Quadrant; 4] = [
+        Quadrant::North,
+        Quadrant::East,
+        Quadrant::South,
+        Quadrant::West,
+    ];
+
+    /// Map (depth, column) within the quadrant to a map tile
+    fn transform(self, origin: (i32, i32), depth: i32, col: i32) -> (i32, i32) {
+        let (x, z) = origin;
+        match self {
+            Quadrant::North => (x + col, z - depth),
+            Quadrant::South => (x + col, z + depth),
+            Quadrant::East => (x + depth, z + col),
+            Quadrant::West => (x - depth, z + col),
         }
+    }
+}
+
+#[derive(Clone, Copy)]
+struct Row {
+    depth: i32,
+    start_slope: f64,
+    end_slope: f64,
+}
+
+impl Row {
+    fn columns(&self) -> std::ops::RangeInclusive<i32> {
+        let depth = self.depth as f64;
+        // Round ties up at the start and down at the end
+        let min_col = (depth * self.start_slope + 0.5).floor() as i32;
+        let max_col = (depth * self.end_slope - 0.5).ceil() as i32;
+        min_col..=max_col
+    }
 
-        if e2 < dx {
-            err += dx;
-            z += sz;
+    fn next(&self) -> Row {
+        Row {
+            depth: self.depth + 1,
+            ..*self
         }
     }
 
-    true
+    /// Floor tiles are only lit if their centre lies inside the row's slopes
+    fn is_symmetric(&self, col: i32) -> bool {
+        let col = col as f64;
+        let depth = self.depth as f64;
+        col >= depth * self.start_slope && col <= depth * self.end_slope
+    }
 }
 
-/// System to reveal tiles around newly spawned units
-pub fn reveal_around_spawn_system(
-    mut visibility_map: ResMut<VisibilityMap>,
-    new_vision_providers: Query<(&Transform, &VisionProvider), Added<VisionProvider>>,
-) {
-    let tile_size = 10.0;
+/// Slope of the left edge of a tile
+fn tile_slope(depth: i32, col: i32) -> f64 {
+    (2 * col - 1) as f64 / (2 * depth) as f64
+}
 
-    for (transform, vision_provider) in new_vision_providers.iter() {
-        if vision_provider.faction != Faction::Player {
-            continue;
-        }
+struct Scanner<'a, B, M> {
+    origin: (i32, i32),
+    quadrant: Quadrant,
+    range: f32,
+    max_depth: i32,
+    is_blocking: &'a B,
+    mark_visible: &'a mut M,
+}
 
-        let provider_tile_x = (transform.translation.x / tile_size).round() as i32;
-        let provider_tile_z = (transform.translation.z / tile_size).round() as i32;
+impl<B, M> Scanner<'_, B, M>
+where
+    B: Fn((i32, i32)) -> bool,
+    M: FnMut((i32, i32)),
+{
+    fn scan(&mut self, mut row: Row) {
+        if row.depth > self.max_depth {
+            return;
+        }
 
-        // Immediately reveal tiles in range
-        let sight_range_tiles = (vision_provider.sight_range / tile_size).ceil() as i32;
+        let mut previous_blocking = None;
+        for col in row.columns() {
+            let tile = self.quadrant.transform(self.origin, row.depth, col);
+            let blocking = (self.is_blocking)(tile);
+            let in_range = ((row.depth * row.depth + col * col) as f32).sqrt() <= self.range;
 
-        for dx in -sight_range_tiles..=sight_range_tiles {
-            for dz in -sight_range_tiles..=sight_range_tiles {
-                let tile_x = provider_tile_x + dx;
-                let tile_z = provider_tile_z + dz;
+            if in_range && (blocking || row.is_symmetric(col)) {
+                (self.mark_visible)(tile);
+            }
 
-                let distance = ((dx * dx + dz * dz) as f32).sqrt() * tile_size;
-                if distance <= vision_provider.sight_range {
-                    visibility_map
-                        .tiles
-                        .insert((tile_x, tile_z), VisibilityState::Visible);
+            match (previous_blocking, blocking) {
+                // Leaving a wall: the visible arc starts at this tile
+                (Some(true), false) => row.start_slope = tile_slope(row.depth, col),
+                // Entering a wall: scan the arc before it one row further out
+                (Some(false), true) => {
+                    let mut next = row.next();
+                    next.end_slope = tile_slope(row.depth, col);
+                    self.scan(next);
                 }
+                _ => {}
             }
+            previous_blocking = Some(blocking);
+        }
+
+        if previous_blocking == Some(false) {
+            self.scan(row.next());
         }
     }
 }
 
+/// System to reveal tiles around newly spawned units
+pub fn reveal_around_spawn_system(
+    mut visibility_map: ResMut<VisibilityMap>,
+    local_player: Res<LocalPlayer>,
+    game_map: Res<GameMap>,
+    mut new_vision_providers: Query<
+        (&Transform, &VisionProvider, &mut VisionCache, Has<Airborne>),
+        Added<VisionProvider>,
+    >,
+) {
+    let mut revealed_any = false;
+
+    for (transform, vision_provider, mut cache, airborne) in new_vision_providers.iter_mut() {
+        // Immediately reveal what the new provider can see
+        refresh_vision_cache(
+            &mut cache,
+            transform.translation,
+            vision_provider.sight_range,
+            airborne,
+            &visibility_map,
+            &game_map,
+        );
+
+        let layer = visibility_map.layer_mut(vision_provider.team);
+        for &tile in &cache.tiles {
+            layer.tiles.insert(tile, VisibilityState::Visible);
+        }
+        revealed_any = true;
+    }
+
+    if revealed_any {
+        visibility_map.sync_rendered_layer(local_player.team);
+    }
+}
+
 /// System to handle entities entering/exiting fog
 pub fn fog_entity_visibility_system(
     visibility_map: Res<VisibilityMap>,
+    game_map: Res<GameMap>,
     mut entities_with_fog: Query<(&Transform, &mut Visibility), With<FogOfWar>>,
 ) {
-    let tile_size = 10.0;
-
     for (transform, mut visibility) in entities_with_fog.iter_mut() {
-        let tile_x = (transform.translation.x / tile_size).round() as i32;
-        let tile_z = (transform.translation.z / tile_size).round() as i32;
+        let tile = world_to_grid(transform.translation, game_map.tile_size);
 
         // Check visibility state of the tile this entity is on
-        if let Some(&visibility_state) = visibility_map.tiles.get(&(tile_x, tile_z)) {
+        if let Some(&visibility_state) = visibility_map.tiles.get(&tile) {
             // Hide entities in fog
             *visibility = match visibility_state {
                 VisibilityState::Visible => Visibility::Visible,
@@ -340,3 +701,201 @@ pub fn fog_entity_visibility_system(
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fog_app() -> App {
+        let mut app = App::new();
+        app.init_resource::<Time>()
+            .init_resource::<GameMap>()
+            .init_resource::<VisibilityMap>()
+            .init_resource::<SharedVision>()
+            .init_resource::<LocalPlayer>()
+            .init_resource::<Assets<StandardMaterial>>()
+            .add_systems(Update, update_fog_system);
+        app
+    }
+
+    #[test]
+    fn vision_is_only_shared_with_allies() {
+        let mut app = fog_app();
+        app.world_mut().spawn((
+            Transform::from_xyz(0.0, 0.0, 0.0),
+            VisionProvider {
+                sight_range: 20.0,
+                team: 1,
+            },
+        ));
+        app.world_mut().spawn((
+            Transform::from_xyz(100.0, 0.0, 0.0),
+            VisionProvider {
+                sight_range: 20.0,
+                team: 2,
+            },
+        ));
+        app.update();
+
+        let map = app.world().resource::<VisibilityMap>();
+        assert!(map.is_visible_to(1, (0, 0)));
+        assert!(
+            !map.is_revealed_to(2, (0, 0)),
+            "enemies don't see our tiles"
+        );
+        assert!(!map.is_revealed_to(1, (10, 0)));
+
+        app.world_mut().resource_mut::<SharedVision>().share(1, 2);
+        app.update();
+
+        let map = app.world().resource::<VisibilityMap>();
+        assert!(map.is_visible_to(2, (0, 0)));
+        assert!(map.is_visible_to(1, (10, 0)));
+
+        // Ending the alliance leaves what was seen revealed but not visible
+        app.world_mut().resource_mut::<SharedVision>().revoke(1, 2);
+        app.update();
+
+        let map = app.world().resource::<VisibilityMap>();
+        assert_eq!(map.state_for(2, (0, 0)), VisibilityState::Revealed);
+        assert!(map.is_visible_to(2, (10, 0)));
+    }
+
+    fn field_of_view(
+        origin: (i32, i32),
+        range: f32,
+        walls: &HashSet<(i32, i32)>,
+    ) -> HashSet<(i32, i32)> {
+        let mut visible = HashSet::new();
+        compute_field_of_view(
+            origin,
+            range,
+            |tile| walls.contains(&tile),
+            |tile| {
+                visible.insert(tile);
+            },
+        );
+        visible
+    }
+
+    #[test]
+    fn walls_shadow_the_tiles_behind_them() {
+        let walls = HashSet::from([(2, 0)]);
+        let visible = field_of_view((0, 0), 5.0, &walls);
+
+        assert!(visible.contains(&(1, 0)));
+        assert!(visible.contains(&(2, 0)), "the wall itself is seen");
+        assert!(!visible.contains(&(3, 0)));
+        assert!(!visible.contains(&(5, 0)));
+        assert!(visible.contains(&(0, 3)), "other directions are open");
+        assert!(!visible.contains(&(6, 0)), "out of range");
+    }
+
+    #[test]
+    fn field_of_view_is_symmetric() {
+        let walls = HashSet::from([(2, 1), (2, 2), (-1, 3), (4, -2), (0, -3), (-3, -1)]);
+        let range = 6.0;
+        let floor: Vec<_> = (-4..=4)
+            .flat_map(|x| (-4..=4).map(move |z| (x, z)))
+            .filter(|tile| !walls.contains(tile))
+            .collect();
+        let views: HashMap<_, _> = floor
+            .iter()
+            .map(|&tile| (tile, field_of_view(tile, range, &walls)))
+            .collect();
+
+        for &a in &floor {
+            for &b in &floor {
+                assert_eq!(
+                    views[&a].contains(&b),
+                    views[&b].contains(&a),
+                    "{a:?} and {b:?} disagree"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn high_ground_sees_over_cliffs() {
+        let mut app = fog_app();
+        app.add_systems(
+            Update,
+            update_sight_blockers_system.before(update_fog_system),
+        );
+
+        // Two lanes with a cliff one tile in front of each viewer; the
+        // team 1 viewer stands on a plateau, the team 2 viewer on the floor
+        let mut game_map = GameMap::default();
+        for x in 0..5 {
+            for z in [0, 5] {
+                let (tile_type, height) = match (x, z) {
+                    (0, 0) => (TileType::Ground, 5.0),
+                    (1, _) => (TileType::Cliff, 0.0),
+                    _ => (TileType::Ground, 0.0),
+                };
+                game_map.tiles.insert(
+                    (x, z),
+                    crate::map::TileInfo {
+                        position: (x, z),
+                        tile_type,
+                        occupied: false,
+                        corruption_level: 0.0,
+                        height,
+                    },
+                );
+            }
+        }
+        app.insert_resource(game_map);
+
+        for (position, team) in [(Vec3::ZERO, 1), (Vec3::new(0.0, 0.0, 50.0), 2)] {
+            app.world_mut().spawn((
+                Transform::from_translation(position),
+                VisionProvider {
+                    sight_range: 40.0,
+                    team,
+                },
+            ));
+        }
+        app.update();
+
+        let map = app.world().resource::<VisibilityMap>();
+        assert!(map.is_visible_to(1, (3, 0)), "eyes above the cliff top");
+        assert!(map.is_visible_to(2, (1, 5)));
+        assert!(!map.is_visible_to(2, (3, 5)), "the cliff hides the lane");
+    }
+
+    #[test]
+    fn sight_blockers_follow_obstacles() {
+        let mut app = App::new();
+        app.init_resource::<GameMap>()
+            .init_resource::<VisibilityMap>()
+            .add_systems(Update, update_sight_blockers_system);
+        app.update();
+        let revision = app.world().resource::<VisibilityMap>().blockers_revision;
+
+        let rock = app
+            .world_mut()
+            .spawn((Transform::from_xyz(20.0, 0.0, 0.0), Obstacle))
+            .id();
+        app.update();
+        let map = app.world().resource::<VisibilityMap>();
+        assert_eq!(
+            map.sight_blockers.get(&(2, 0)),
+            Some(&DEFAULT_BLOCKER_HEIGHT)
+        );
+        assert_eq!(map.blockers_revision, revision + 1);
+
+        // Nothing changed: the revision stays put
+        app.update();
+        assert_eq!(
+            app.world().resource::<VisibilityMap>().blockers_revision,
+            revision + 1
+        );
+
+        app.world_mut().despawn(rock);
+        app.update();
+        let map = app.world().resource::<VisibilityMap>();
+        assert!(!map.sight_blockers.contains_key(&(2, 0)));
+        assert_eq!(map.blockers_revision, revision + 2);
+    }
+}
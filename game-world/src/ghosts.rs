@@ -0,0 +1,400 @@
+//! Last-known-position memory of enemies under the fog of war
+
+use crate::fog::{LocalPlayer, SharedVision, VisibilityMap};
+use crate::map::{GameMap, world_to_grid};
+use bevy::prelude::*;
+use game_physics::{StealthState, is_hidden_from};
+use std::collections::HashMap;
+
+/// Component marking a unit or building that other teams remember seeing.
+///
+/// Owners keep `health` up to date; observers only ever learn it while the
+/// entity is in sight.
+#[derive(Component, Clone, Debug)]
+pub struct Observable {
+    pub team: u32,
+    pub kind: ObservedKind,
+    pub type_name: String,
+    pub health: f32,
+    pub max_health: f32,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ObservedKind {
+    Unit,
+    Building,
+}
+
+/// What a team last saw of an enemy
+#[derive(Clone, Debug)]
+pub struct Ghost {
+    pub entity: Entity,
+    pub team: u32,
+    pub kind: ObservedKind,
+    pub type_name: String,
+    pub position: Vec3,
+    pub tile: (i32, i32),
+    pub health: f32,
+    pub max_health: f32,
+    /// Elapsed time when the snapshot was taken
+    pub last_seen: f32,
+    /// True while the enemy is in sight; false once it is only a memory
+    pub in_sight: bool,
+}
+
+impl Ghost {
+    /// Seconds since the enemy was last seen; 0.0 while in sight
+    pub fn age(&self, now: f32) -> f32 {
+        if self.in_sight {
+            0.0
+        } else {
+            (now - self.last_seen).max(0.0)
+        }
+    }
+}
+
+/// Every team's memory of the enemies it has seen
+#[derive(Resource, Default, Debug)]
+pub struct EnemyMemory {
+    pub teams: HashMap<u32, HashMap<Entity, Ghost>>,
+}
+
+impl EnemyMemory {
+    /// Everything a team knows about, whether in sight or remembered
+    pub fn known_enemies(&self, team: u32) -> impl Iterator<Item = &Ghost> {
+        self.teams
+            .get(&team)
+            .into_iter()
+            .flat_map(|memory| memory.values())
+    }
+
+    /// Known enemy units or buildings, e.g. units for AI targeting
+    pub fn known_of_kind(&self, team: u32, kind: ObservedKind) -> impl Iterator<Item = &Ghost> {
+        self.known_enemies(team)
+            .filter(move |ghost| ghost.kind == kind)
+    }
+
+    /// Known enemies whose last known position is within `radius` of `center`
+    pub fn known_within(
+        &self,
+        team: u32,
+        center: Vec3,
+        radius: f32,
+    ) -> impl Iterator<Item = &Ghost> {
+        self.known_enemies(team)
+            .filter(move |ghost| ghost.position.distance(center) <= radius)
+    }
+
+    /// Enemies a team has lost sight of, at their last known positions
+    pub fn ghosts_for(&self, team: u32) -> impl Iterator<Item = &Ghost> {
+        self.known_enemies(team).filter(|ghost| !ghost.in_sight)
+    }
+
+    /// Last snapshot a team has of a specific enemy
+    pub fn last_known(&self, team: u32, entity: Entity) -> Option<&Ghost> {
+        self.teams.get(&team)?.get(&entity)
+    }
+
+    /// Forget everything a team remembers
+    pub fn clear_team(&mut self, team: u32) {
+        self.teams.remove(&team);
+    }
+}
+
+/// Visual stand-in for a remembered enemy, shown to the local player
+#[derive(Component)]
+pub struct GhostMarker {
+    pub source: Entity,
+}
+
+/// Refresh every team's memory from its visibility layer.
+///
+/// Enemies in sight update their snapshot. Enemies out of sight keep their
+/// last snapshot as a ghost until the ghost's tile is seen again, which also
+/// clears ghosts of enemies that moved away or were destroyed unseen.
+pub fn update_enemy_memory_system(
+    mut memory: ResMut<EnemyMemory>,
+    visibility_map: Res<VisibilityMap>,
+    shared_vision: Res<SharedVision>,
+    game_map: Res<GameMap>,
+    observables: Query<(Entity, &Transform, &Observable, Option<&StealthState>)>,
+    time: Res<Time>,
+) {
+    let now = time.elapsed_seconds();
+
+    for &team in visibility_map.layers.keys() {
+        let team_memory = memory.teams.entry(team).or_default();
+
+        for ghost in team_memory.values_mut() {
+            ghost.in_sight = false;
+        }
+
+        for (entity, transform, observable, stealth) in observables.iter() {
+            // Undetected stealth units are never seen, so never remembered
+            if shared_vision.shares_with(team, observable.team) || is_hidden_from(stealth, team) {
+                continue;
+            }
+
+            let tile = world_to_grid(transform.translation, game_map.tile_size);
+            if !visibility_map.is_visible_to(team, tile) {
+                continue;
+            }
+
+            team_memory.insert(
+                entity,
+                Ghost {
+                    entity,
+                    team: observable.team,
+                    kind: observable.kind,
+                    type_name: observable.type_name.clone(),
+                    position: transform.translation,
+                    tile,
+                    health: observable.health,
+                    max_health: observable.max_health,
+                    last_seen: now,
+                    in_sight: true,
+                },
+            );
+        }
+
+        // Seeing a ghost's tile again without the enemy there dispels it
+        team_memory
+            .retain(|_, ghost| ghost.in_sight || !visibility_map.is_visible_to(team, ghost.tile));
+    }
+}
+
+/// Hide enemies the local player can't currently see (fogged or stealthed)
+pub fn observable_visibility_system(
+    visibility_map: Res<VisibilityMap>,
+    shared_vision: Res<SharedVision>,
+    local_player: Res<LocalPlayer>,
+    game_map: Res<GameMap>,
+    mut observables: Query<(
+        &Transform,
+        &Observable,
+        &mut Visibility,
+        Option<&StealthState>,
+    )>,
+) {
+    for (transform, observable, mut visibility, stealth) in observables.iter_mut() {
+        if shared_vision.shares_with(local_player.team, observable.team) {
+            continue;
+        }
+
+        let tile = world_to_grid(transform.translation, game_map.tile_size);
+        let target = if visibility_map.is_visible_to(local_player.team, tile)
+            && !is_hidden_from(stealth, local_player.team)
+        {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        };
+        visibility.set_if_neq(target);
+    }
+}
+
+/// Keep one ghost marker per remembered enemy of the local player
+pub fn ghost_marker_system(
+    mut commands: Commands,
+    memory: Res<EnemyMemory>,
+    local_player: Res<LocalPlayer>,
+    mut markers: Query<(Entity, &GhostMarker, &mut Transform)>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    let mut ghosts: HashMap<Entity, &Ghost> = memory
+        .ghosts_for(local_player.team)
+        .map(|ghost| (ghost.entity, ghost))
+        .collect();
+
+    // Move existing markers and drop the ones whose ghost is gone
+    for (marker_entity, marker, mut transform) in markers.iter_mut() {
+        match ghosts.remove(&marker.source) {
+            Some(ghost) => transform.translation = ghost.position,
+            None => commands.entity(marker_entity).despawn(),
+        }
+    }
+
+    for ghost in ghosts.into_values() {
+        let size = match ghost.kind {
+            ObservedKind::Unit => Vec3::new(1.0, 2.0, 1.0),
+            ObservedKind::Building => Vec3::new(4.0, 3.0, 4.0),
+        };
+
+        commands.spawn((
+            Name::new(format!("Ghost: {}", ghost.type_name)),
+            Mesh3d(meshes.add(Cuboid::from_size(size))),
+            MeshMaterial3d(materials.add(StandardMaterial {
+                base_color: Color::srgba(0.6, 0.6, 0.7, 0.35),
+                alpha_mode: AlphaMode::Blend,
+                unlit: true,
+                ..default()
+            })),
+            Transform::from_translation(ghost.position),
+            GhostMarker {
+                source: ghost.entity,
+            },
+        ));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fog::{VisionProvider, update_fog_system};
+
+    const SCOUT_TEAM: u32 = 1;
+    const ENEMY_TEAM: u32 = 2;
+
+    fn memory_app() -> (App, Entity, Entity) {
+        let mut app = App::new();
+        app.init_resource::<Time>()
+            .init_resource::<GameMap>()
+            .init_resource::<VisibilityMap>()
+            .init_resource::<SharedVision>()
+            .init_resource::<LocalPlayer>()
+            .init_resource::<EnemyMemory>()
+            .init_resource::<Assets<StandardMaterial>>()
+            .add_systems(
+                Update,
+                (update_fog_system, update_enemy_memory_system).chain(),
+            );
+
+        let scout = app
+            .world_mut()
+            .spawn((
+                Transform::default(),
+                VisionProvider {
+                    sight_range: 30.0,
+                    team: SCOUT_TEAM,
+                },
+            ))
+            .id();
+        let temple = app
+            .world_mut()
+            .spawn((
+                Transform::from_xyz(20.0, 0.0, 0.0),
+                Observable {
+                    team: ENEMY_TEAM,
+                    kind: ObservedKind::Building,
+                    type_name: "temple".to_string(),
+                    health: 100.0,
+                    max_health: 100.0,
+                },
+            ))
+            .id();
+        (app, scout, temple)
+    }
+
+    fn move_scout(app: &mut App, scout: Entity, x: f32) {
+        app.world_mut()
+            .get_mut::<Transform>(scout)
+            .unwrap()
+            .translation
+            .x = x;
+        app.update();
+    }
+
+    fn last_known(app: &App, temple: Entity) -> Option<Ghost> {
+        app.world()
+            .resource::<EnemyMemory>()
+            .last_known(SCOUT_TEAM, temple)
+            .cloned()
+    }
+
+    #[test]
+    fn buildings_leave_ghosts_when_fogged() {
+        let (mut app, scout, temple) = memory_app();
+        app.update();
+        assert!(last_known(&app, temple).unwrap().in_sight);
+
+        move_scout(&mut app, scout, 200.0);
+        let ghost = last_known(&app, temple).expect("remembered after leaving sight");
+        assert!(!ghost.in_sight);
+        assert_eq!(ghost.position, Vec3::new(20.0, 0.0, 0.0));
+        assert_eq!(ghost.tile, (2, 0));
+        assert_eq!(
+            app.world()
+                .resource::<EnemyMemory>()
+                .ghosts_for(SCOUT_TEAM)
+                .count(),
+            1
+        );
+    }
+
+    #[test]
+    fn ghosts_update_when_seen_again() {
+        let (mut app, scout, temple) = memory_app();
+        app.update();
+        move_scout(&mut app, scout, 200.0);
+
+        // Damage dealt while fogged isn't known until the scout returns
+        app.world_mut()
+            .get_mut::<Observable>(temple)
+            .unwrap()
+            .health = 40.0;
+        app.update();
+        assert_eq!(last_known(&app, temple).unwrap().health, 100.0);
+
+        move_scout(&mut app, scout, 0.0);
+        let ghost = last_known(&app, temple).unwrap();
+        assert!(ghost.in_sight);
+        assert_eq!(ghost.health, 40.0);
+    }
+
+    #[test]
+    fn memory_can_be_queried_by_kind_and_area() {
+        let (mut app, scout, temple) = memory_app();
+        let raider = app
+            .world_mut()
+            .spawn((
+                Transform::from_xyz(0.0, 0.0, 20.0),
+                Observable {
+                    team: ENEMY_TEAM,
+                    kind: ObservedKind::Unit,
+                    type_name: "raider".to_string(),
+                    health: 50.0,
+                    max_health: 50.0,
+                },
+            ))
+            .id();
+        app.update();
+        move_scout(&mut app, scout, 200.0);
+        app.world_mut()
+            .resource_mut::<Time>()
+            .advance_by(std::time::Duration::from_secs(3));
+        app.update();
+
+        let memory = app.world().resource::<EnemyMemory>();
+        let units: Vec<Entity> = memory
+            .known_of_kind(SCOUT_TEAM, ObservedKind::Unit)
+            .map(|ghost| ghost.entity)
+            .collect();
+        assert_eq!(units, vec![raider]);
+        let near_temple: Vec<Entity> = memory
+            .known_within(SCOUT_TEAM, Vec3::new(25.0, 0.0, 0.0), 10.0)
+            .map(|ghost| ghost.entity)
+            .collect();
+        assert_eq!(near_temple, vec![temple]);
+
+        let now = app.world().resource::<Time>().elapsed_seconds();
+        let ghost = memory.last_known(SCOUT_TEAM, raider).unwrap();
+        assert!((ghost.age(now) - 3.0).abs() < 1e-4);
+        assert_eq!(memory.known_enemies(ENEMY_TEAM).count(), 0);
+    }
+
+    #[test]
+    fn ghosts_of_unseen_deaths_last_until_reobserved() {
+        let (mut app, scout, temple) = memory_app();
+        app.update();
+        move_scout(&mut app, scout, 200.0);
+
+        app.world_mut().despawn(temple);
+        app.update();
+        let ghost = last_known(&app, temple).expect("destroyed out of sight");
+        assert!(!ghost.in_sight);
+
+        move_scout(&mut app, scout, 0.0);
+        assert!(last_known(&app, temple).is_none());
+    }
+}
@@ -7,12 +7,19 @@ use bevy::prelude::*;
 use tracing::info;
 
 pub mod fog;
+pub mod ghosts;
 pub mod map;
 pub mod spawning;
 pub mod terrain;
 
-pub use fog::{Faction, FogOfWar, VisibilityMap, VisionProvider};
-pub use map::{GameMap, MapTile, PathfindingGrid, find_path};
+pub use fog::{
+    FogOfWar, LocalPlayer, PLAYER_TEAM, SharedVision, VisibilityLayer, VisibilityMap,
+    VisibilityState, VisionCache, VisionProvider, compute_field_of_view,
+};
+pub use ghosts::{EnemyMemory, Ghost, GhostMarker, Observable, ObservedKind};
+pub use map::{
+    GameMap, MapTile, MovementDomain, PathfindingGrid, PathfindingLayer, find_path, find_path_for,
+};
 pub use spawning::{CultLeader, InitialCreature, LeadershipBuilding, PlayerUnit, Totem};
 pub use terrain::{BiomeType, TerrainConfig, TerrainTile};
 
@@ -25,6 +32,9 @@ impl Plugin for GameWorldPlugin {
         app.init_resource::<GameMap>()
             .init_resource::<PathfindingGrid>()
             .init_resource::<VisibilityMap>()
+            .init_resource::<SharedVision>()
+            .init_resource::<LocalPlayer>()
+            .init_resource::<EnemyMemory>()
             .init_resource::<TerrainConfig>();
 
         // Add startup systems in the correct order
@@ -33,6 +43,7 @@ impl Plugin for GameWorldPlugin {
             (
                 map::initialize_map,
                 terrain::generate_terrain_system,
+                map::build_terrain_collider_system,
                 fog::initialize_fog_system,
                 spawning::spawn_starting_scene,
             )
@@ -43,10 +54,18 @@ impl Plugin for GameWorldPlugin {
         app.add_systems(
             Update,
             (
-                fog::update_fog_system,
-                fog::reveal_around_spawn_system,
-                fog::fog_entity_visibility_system,
+                (
+                    fog::update_sight_blockers_system,
+                    fog::reveal_around_spawn_system,
+                    fog::update_fog_system,
+                    fog::fog_entity_visibility_system,
+                    ghosts::update_enemy_memory_system,
+                    ghosts::observable_visibility_system,
+                    ghosts::ghost_marker_system,
+                )
+                    .chain(),
                 map::update_tile_occupation_system,
+                map::corrupted_ground_sanity_system,
             ),
         );
 
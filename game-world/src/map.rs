@@ -1,7 +1,8 @@
 //! Map management and grid system for Cosmic Dominion
 
 use bevy::prelude::*;
-use std::collections::HashMap;
+use game_physics::{Sanity, SanitySource, TerrainHeightfield, create_heightfield_collider};
+use std::collections::{HashMap, HashSet};
 use tracing::info;
 
 /// Resource representing the game map
@@ -26,6 +27,33 @@ impl Default for GameMap {
     }
 }
 
+impl GameMap {
+    /// Sample tile heights into a physics heightfield (one sample per tile
+    /// centre), used for grounding, terrain raycasts and the terrain collider
+    pub fn to_heightfield(&self) -> TerrainHeightfield {
+        let half_width = self.width / 2;
+        let half_height = self.height / 2;
+        let width = (2 * half_width + 1) as usize;
+        let depth = (2 * half_height + 1) as usize;
+
+        let mut heights = Vec::with_capacity(width * depth);
+        for z in -half_height..=half_height {
+            for x in -half_width..=half_width {
+                heights.push(self.tiles.get(&(x, z)).map_or(0.0, |tile| tile.height));
+            }
+        }
+
+        let origin = grid_to_world(-half_width, -half_height, self.tile_size);
+        TerrainHeightfield::new(
+            Vec2::new(origin.x, origin.z),
+            self.tile_size,
+            width,
+            depth,
+            heights,
+        )
+    }
+}
+
 /// Information about a single map tile
 #[derive(Clone, Debug)]
 pub struct TileInfo {
@@ -54,11 +82,117 @@ pub struct MapTile {
     pub tile_type: TileType,
 }
 
+/// How a unit moves across terrain, used to pick a pathfinding layer
+#[derive(Component, Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum MovementDomain {
+    /// Ground and bridges only
+    #[default]
+    Land,
+    /// Land plus water (e.g. Deep cult units)
+    Amphibious,
+    /// Water only; bridges can be passed under
+    Naval,
+    /// Anything except cliffs, including water and deep void
+    Hover,
+}
+
+impl MovementDomain {
+    pub const ALL: [MovementDomain; 4] = [
+        MovementDomain::Land,
+        MovementDomain::Amphibious,
+        MovementDomain::Naval,
+        MovementDomain::Hover,
+    ];
+}
+
+/// Walkability and cost for one movement domain
+#[derive(Clone, Debug, Default)]
+pub struct PathfindingLayer {
+    pub walkable: HashMap<(i32, i32), bool>,
+    pub movement_costs: HashMap<(i32, i32), f32>,
+}
+
 /// Resource for pathfinding and movement
+///
+/// `walkable` and `movement_costs` hold the land layer; the other domains
+/// live in `domain_layers`. Use `is_walkable`/`movement_cost` to read any
+/// domain uniformly.
 #[derive(Resource, Default)]
 pub struct PathfindingGrid {
     pub walkable: HashMap<(i32, i32), bool>,
     pub movement_costs: HashMap<(i32, i32), f32>,
+    pub domain_layers: HashMap<MovementDomain, PathfindingLayer>,
+}
+
+impl PathfindingGrid {
+    /// Set every domain's walkability and cost for a tile from its terrain
+    pub fn set_tile(&mut self, position: (i32, i32), tile_type: TileType, corruption_level: f32) {
+        for domain in MovementDomain::ALL {
+            self.set_walkable(
+                domain,
+                position,
+                is_tile_walkable_for(domain, tile_type, corruption_level),
+            );
+            self.set_movement_cost(
+                domain,
+                position,
+                calculate_domain_movement_cost(domain, tile_type, corruption_level),
+            );
+        }
+    }
+
+    pub fn is_walkable(&self, domain: MovementDomain, position: (i32, i32)) -> bool {
+        let walkable = match domain {
+            MovementDomain::Land => &self.walkable,
+            _ => match self.domain_layers.get(&domain) {
+                Some(layer) => &layer.walkable,
+                None => return false,
+            },
+        };
+        walkable.get(&position).copied().unwrap_or(false)
+    }
+
+    pub fn movement_cost(&self, domain: MovementDomain, position: (i32, i32)) -> f32 {
+        let costs = match domain {
+            MovementDomain::Land => &self.movement_costs,
+            _ => match self.domain_layers.get(&domain) {
+                Some(layer) => &layer.movement_costs,
+                None => return 999.0,
+            },
+        };
+        costs.get(&position).copied().unwrap_or(999.0)
+    }
+
+    pub fn set_walkable(&mut self, domain: MovementDomain, position: (i32, i32), walkable: bool) {
+        match domain {
+            MovementDomain::Land => self.walkable.insert(position, walkable),
+            _ => self
+                .domain_layers
+                .entry(domain)
+                .or_default()
+                .walkable
+                .insert(position, walkable),
+        };
+    }
+
+    pub fn set_movement_cost(&mut self, domain: MovementDomain, position: (i32, i32), cost: f32) {
+        match domain {
+            MovementDomain::Land => self.movement_costs.insert(position, cost),
+            _ => self
+                .domain_layers
+                .entry(domain)
+                .or_default()
+                .movement_costs
+                .insert(position, cost),
+        };
+    }
+
+    /// Block a tile for every domain (e.g. a building placed on it)
+    pub fn block_tile(&mut self, position: (i32, i32)) {
+        for domain in MovementDomain::ALL {
+            self.set_walkable(domain, position, false);
+        }
+    }
 }
 
 /// Initialize the game map
@@ -86,26 +220,19 @@ pub fn initialize_map(
             // Calculate corruption level
             let corruption_level = calculate_tile_corruption(distance_from_center);
 
-            // Determine if walkable
-            let walkable = is_tile_walkable(tile_type, corruption_level);
-
-            // Calculate movement cost
-            let movement_cost = calculate_movement_cost(tile_type, corruption_level);
-
             // Store tile info
             let tile_info = TileInfo {
                 position: (x, z),
                 tile_type,
                 occupied: false,
                 corruption_level,
-                height: 0.0, // Will be set by terrain generation
+                height: 0.0, // Set by terrain generation
             };
 
             game_map.tiles.insert((x, z), tile_info);
-            pathfinding_grid.walkable.insert((x, z), walkable);
-            pathfinding_grid
-                .movement_costs
-                .insert((x, z), movement_cost);
+
+            // Walkability and movement cost for every movement domain
+            pathfinding_grid.set_tile((x, z), tile_type, corruption_level);
         }
     }
 
@@ -114,6 +241,7 @@ pub fn initialize_map(
         for dz in -1..=1 {
             if let Some(tile) = game_map.tiles.get_mut(&(dx, dz)) {
                 tile.corruption_level = 0.0;
+                pathfinding_grid.set_tile((dx, dz), tile.tile_type, 0.0);
             }
             pathfinding_grid.walkable.insert((dx, dz), true);
             pathfinding_grid.movement_costs.insert((dx, dz), 1.0);
@@ -121,6 +249,14 @@ pub fn initialize_map(
     }
 }
 
+/// Publish generated tile heights as the physics heightfield and spawn the
+/// matching terrain collider so bodies rest on the actual ground
+pub fn build_terrain_collider_system(mut commands: Commands, game_map: Res<GameMap>) {
+    let mut heightfield = game_map.to_heightfield();
+    heightfield.entity = Some(create_heightfield_collider(&mut commands, &heightfield));
+    commands.insert_resource(heightfield);
+}
+
 /// Determine tile type based on position
 fn determine_tile_type(x: i32, z: i32, distance: f32) -> TileType {
     // Create some interesting patterns
@@ -156,25 +292,52 @@ fn calculate_tile_corruption(distance: f32) -> f32 {
     (base_corruption + variation).min(1.0)
 }
 
-/// Check if a tile is walkable
-fn is_tile_walkable(tile_type: TileType, corruption_level: f32) -> bool {
-    match tile_type {
-        TileType::Ground => true,
-        TileType::Bridge => true,
-        TileType::Water => false,
-        TileType::Cliff => false,
-        TileType::Void => corruption_level < 0.9, // Can walk on less corrupted void
+/// Check if a tile is walkable for land units
+pub fn is_tile_walkable(tile_type: TileType, corruption_level: f32) -> bool {
+    is_tile_walkable_for(MovementDomain::Land, tile_type, corruption_level)
+}
+
+/// Check if a tile is passable for a movement domain
+pub fn is_tile_walkable_for(
+    domain: MovementDomain,
+    tile_type: TileType,
+    corruption_level: f32,
+) -> bool {
+    match (domain, tile_type) {
+        (_, TileType::Cliff) => false,
+        (MovementDomain::Naval, TileType::Water | TileType::Bridge) => true,
+        (MovementDomain::Naval, _) => false,
+        (_, TileType::Ground | TileType::Bridge) => true,
+        (MovementDomain::Land, TileType::Water) => false,
+        (MovementDomain::Amphibious | MovementDomain::Hover, TileType::Water) => true,
+        (MovementDomain::Hover, TileType::Void) => true,
+        (_, TileType::Void) => corruption_level < 0.9, // Can walk on less corrupted void
     }
 }
 
-/// Calculate movement cost for pathfinding
-fn calculate_movement_cost(tile_type: TileType, corruption_level: f32) -> f32 {
-    let base_cost = match tile_type {
-        TileType::Ground => 1.0,
-        TileType::Bridge => 1.2,
-        TileType::Water => 999.0, // Very high cost (not walkable)
-        TileType::Cliff => 999.0, // Very high cost (not walkable)
-        TileType::Void => 2.0 + corruption_level * 3.0,
+/// Calculate land movement cost for pathfinding
+pub fn calculate_movement_cost(tile_type: TileType, corruption_level: f32) -> f32 {
+    calculate_domain_movement_cost(MovementDomain::Land, tile_type, corruption_level)
+}
+
+/// Calculate movement cost for pathfinding in a movement domain
+pub fn calculate_domain_movement_cost(
+    domain: MovementDomain,
+    tile_type: TileType,
+    corruption_level: f32,
+) -> f32 {
+    let base_cost = match (domain, tile_type) {
+        (_, TileType::Cliff) => 999.0, // Very high cost (not walkable)
+        (MovementDomain::Naval, TileType::Water) => 1.0,
+        (MovementDomain::Naval, TileType::Bridge) => 1.2, // Passing under
+        (MovementDomain::Naval, _) => 999.0,
+        (MovementDomain::Hover, TileType::Ground | TileType::Water | TileType::Bridge) => 1.0,
+        (MovementDomain::Hover, TileType::Void) => 1.5,
+        (_, TileType::Ground) => 1.0,
+        (_, TileType::Bridge) => 1.2,
+        (MovementDomain::Amphibious, TileType::Water) => 1.3,
+        (_, TileType::Water) => 999.0, // Very high cost (not walkable)
+        (_, TileType::Void) => 2.0 + corruption_level * 3.0,
     };
 
     // Corruption increases movement cost
@@ -193,33 +356,84 @@ pub fn world_to_grid(position: Vec3, tile_size: f32) -> (i32, i32) {
     (x, z)
 }
 
-/// System to update tile occupation based on entities
+/// System to update tile occupation based on entities.
+///
+/// The map is only flagged as changed when a tile's occupation flips, so
+/// systems watching `GameMap` for terrain changes don't rerun every frame.
 pub fn update_tile_occupation_system(
     mut game_map: ResMut<GameMap>,
     occupants: Query<&Transform, (With<MapTile>, Changed<Transform>)>,
 ) {
-    // Clear all occupation flags
-    for tile in game_map.tiles.values_mut() {
-        tile.occupied = false;
+    let tile_size = game_map.tile_size;
+    let occupied: HashSet<(i32, i32)> = occupants
+        .iter()
+        .map(|transform| world_to_grid(transform.translation, tile_size))
+        .collect();
+
+    let mut flipped = false;
+    for (position, tile) in game_map.bypass_change_detection().tiles.iter_mut() {
+        let now_occupied = occupied.contains(position);
+        if tile.occupied != now_occupied {
+            tile.occupied = now_occupied;
+            flipped = true;
+        }
+    }
+    if flipped {
+        game_map.set_changed();
     }
+}
+
+/// Corruption below this level is harmless to stand on
+pub const CORRUPTION_SANITY_THRESHOLD: f32 = 0.5;
+
+/// Sanity lost per second standing on fully corrupted ground
+pub const CORRUPTION_SANITY_DRAIN: f32 = 2.0;
+
+/// System to drain the sanity of units standing on corrupted tiles
+pub fn corrupted_ground_sanity_system(
+    time: Res<Time>,
+    game_map: Res<GameMap>,
+    mut query: Query<(&Transform, &mut Sanity)>,
+) {
+    let dt = time.delta_seconds();
 
-    // Mark occupied tiles
-    for transform in occupants.iter() {
-        let (x, z) = world_to_grid(transform.translation, game_map.tile_size);
-        if let Some(tile) = game_map.tiles.get_mut(&(x, z)) {
-            tile.occupied = true;
+    for (transform, mut sanity) in query.iter_mut() {
+        let tile = world_to_grid(transform.translation, game_map.tile_size);
+        let Some(info) = game_map.tiles.get(&tile) else {
+            continue;
+        };
+        if info.corruption_level <= CORRUPTION_SANITY_THRESHOLD {
+            continue;
         }
+
+        let severity = (info.corruption_level - CORRUPTION_SANITY_THRESHOLD)
+            / (1.0 - CORRUPTION_SANITY_THRESHOLD);
+        sanity.drain(
+            SanitySource::Corruption,
+            severity * CORRUPTION_SANITY_DRAIN * dt,
+            None,
+        );
     }
 }
 
-/// Find a path between two points using A* pathfinding
+/// Find a path between two points for land units using A* pathfinding
 pub fn find_path(
     start: (i32, i32),
     goal: (i32, i32),
     pathfinding_grid: &PathfindingGrid,
+) -> Option<Vec<(i32, i32)>> {
+    find_path_for(MovementDomain::Land, start, goal, pathfinding_grid)
+}
+
+/// Find a path using the walkability and cost layer of a movement domain
+pub fn find_path_for(
+    domain: MovementDomain,
+    start: (i32, i32),
+    goal: (i32, i32),
+    pathfinding_grid: &PathfindingGrid,
 ) -> Option<Vec<(i32, i32)>> {
     use std::cmp::Ordering;
-    use std::collections::{BinaryHeap, HashSet};
+    use std::collections::BinaryHeap;
 
     #[derive(Clone, Eq, PartialEq)]
     struct Node {
@@ -292,22 +506,12 @@ pub fn find_path(
             let neighbor = (current.position.0 + dx, current.position.1 + dz);
 
             // Check if walkable
-            if !pathfinding_grid
-                .walkable
-                .get(&neighbor)
-                .copied()
-                .unwrap_or(false)
-            {
+            if !pathfinding_grid.is_walkable(domain, neighbor) {
                 continue;
             }
 
             let tentative_g_score = g_score[&current.position]
-                + (pathfinding_grid
-                    .movement_costs
-                    .get(&neighbor)
-                    .copied()
-                    .unwrap_or(999.0)
-                    * 100.0) as i32;
+                + (pathfinding_grid.movement_cost(domain, neighbor) * 100.0) as i32;
 
             if tentative_g_score < *g_score.get(&neighbor).unwrap_or(&i32::MAX) {
                 came_from.insert(neighbor, current.position);
@@ -360,3 +564,67 @@ pub fn debug_draw_map_grid(game_map: Res<GameMap>, mut gizmos: Gizmos) {
         Color::srgb(0.0, 1.0, 0.0),
     );
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A 7x5 grid of ground split by a river at x = 3 with a cliff at z = 3;
+    /// the only dry crossing is at z = 4
+    fn river_grid() -> PathfindingGrid {
+        let mut grid = PathfindingGrid::default();
+        for x in 0..7 {
+            for z in 0..5 {
+                let tile_type = match (x, z) {
+                    (3, 0..=2) => TileType::Water,
+                    (3, 3) => TileType::Cliff,
+                    _ => TileType::Ground,
+                };
+                grid.set_tile((x, z), tile_type, 0.0);
+            }
+        }
+        grid
+    }
+
+    #[test]
+    fn land_units_path_around_water_and_cliffs() {
+        let grid = river_grid();
+        let path =
+            find_path_for(MovementDomain::Land, (0, 0), (6, 0), &grid).expect("land path exists");
+
+        assert!(path.contains(&(3, 4)), "crosses at the dry tile");
+        assert!(
+            path.iter()
+                .all(|&tile| grid.is_walkable(MovementDomain::Land, tile))
+        );
+
+        // Close the dry crossing and there is no way over
+        let mut grid = grid;
+        grid.set_tile((3, 4), TileType::Cliff, 0.0);
+        assert!(find_path_for(MovementDomain::Land, (0, 0), (6, 0), &grid).is_none());
+    }
+
+    #[test]
+    fn amphibious_units_cross_water() {
+        let mut grid = river_grid();
+        grid.set_tile((3, 4), TileType::Cliff, 0.0);
+
+        let path = find_path_for(MovementDomain::Amphibious, (0, 0), (6, 0), &grid)
+            .expect("amphibious path exists");
+        assert!(
+            path.iter().any(|&(x, z)| x == 3 && z <= 2),
+            "swims the river"
+        );
+        assert!(!path.contains(&(3, 3)) && !path.contains(&(3, 4)));
+    }
+
+    #[test]
+    fn naval_units_stay_on_water() {
+        let grid = river_grid();
+
+        let path =
+            find_path_for(MovementDomain::Naval, (3, 0), (3, 2), &grid).expect("naval path exists");
+        assert_eq!(path, [(3, 0), (3, 1), (3, 2)]);
+        assert!(find_path_for(MovementDomain::Naval, (3, 0), (6, 0), &grid).is_none());
+    }
+}
@@ -1,11 +1,13 @@
 //! World entity spawning system for Cosmic Dominion
 
-use crate::fog::{Faction, VisionProvider};
+use crate::fog::{PLAYER_TEAM, VisionProvider};
+use crate::ghosts::{Observable, ObservedKind};
 use bevy::asset::RenderAssetUsages;
 use bevy::mesh::Indices;
 use bevy::prelude::*;
 use bevy::render::render_resource::PrimitiveTopology;
 use game_assets::{Cult, models};
+use game_physics::{CollisionTeam, Detector, EldritchAura, Health, UnitClassification};
 use tracing::info;
 
 /// Marker component for the cult leader
@@ -132,10 +134,23 @@ fn spawn_leadership_building(
         SceneRoot(temple_model.clone()),
         Transform::from_translation(position).with_scale(Vec3::splat(2.0)),
         LeadershipBuilding { cult },
+        Health::new(1000.0),
+        // Not this module's `UnitType`, which picks a creature model
+        game_physics::UnitType::new(UnitClassification::Building),
         VisionProvider {
             sight_range: 50.0,
-            faction: Faction::Player,
+            team: PLAYER_TEAM,
         },
+        Observable {
+            team: PLAYER_TEAM,
+            kind: ObservedKind::Building,
+            type_name: "leadership_building".to_string(),
+            health: 1000.0,
+            max_health: 1000.0,
+        },
+        // Temples sense stealthed units around the base
+        Detector { radius: 25.0 },
+        CollisionTeam(PLAYER_TEAM),
         Name::new("Leadership Building"),
     ));
 
@@ -199,7 +214,7 @@ fn spawn_cult_leader(
         CultLeader { cult, level: 1 },
         VisionProvider {
             sight_range: 40.0,
-            faction: Faction::Player,
+            team: PLAYER_TEAM,
         },
         Name::new("Cult Leader"),
     ));
@@ -244,7 +259,7 @@ fn spawn_player_unit(
         PlayerUnit { unit_type },
         VisionProvider {
             sight_range: 30.0,
-            faction: Faction::Player,
+            team: PLAYER_TEAM,
         },
         Name::new("Player Unit"),
     ));
@@ -278,6 +293,11 @@ fn spawn_initial_creature(
         MeshMaterial3d(creature_material),
         Transform::from_translation(position),
         InitialCreature { creature_type },
+        // Just looking at it frays the mind
+        EldritchAura {
+            radius: 8.0,
+            drain_per_second: 3.0,
+        },
         Name::new("Corrupted Creature"),
     ));
 }
@@ -1,5 +1,6 @@
 //! Production terrain generation and biome system for Cosmic Dominion
 
+use crate::map::GameMap;
 use bevy::asset::RenderAssetUsages;
 use bevy::mesh::Indices;
 use bevy::prelude::*;
@@ -90,6 +91,7 @@ pub fn generate_terrain_system(
     mut commands: Commands,
     mut meshes: ResMut<Assets<Mesh>>,
     mut materials: ResMut<Assets<StandardMaterial>>,
+    mut game_map: ResMut<GameMap>,
     terrain_config: Res<TerrainConfig>,
 ) {
     let mut rng = StdRng::seed_from_u64(terrain_config.seed);
@@ -150,6 +152,9 @@ pub fn generate_terrain_system(
             // Create tile mesh with height variation
             let height_variation = biome.get_height_variation();
             let tile_height = rng.random_range(-height_variation..height_variation);
+            if let Some(tile) = game_map.tiles.get_mut(&(x, z)) {
+                tile.height = tile_height;
+            }
 
             let tile_mesh = meshes.add(create_tile_mesh(
                 terrain_config.tile_size,
@@ -183,9 +188,10 @@ pub fn generate_terrain_system(
             commands.spawn((
                 Mesh3d(tile_mesh),
                 MeshMaterial3d(tile_material),
+                // The mesh already carries the tile height
                 Transform::from_xyz(
                     x as f32 * terrain_config.tile_size,
-                    tile_height,
+                    0.0,
                     z as f32 * terrain_config.tile_size,
                 ),
                 TerrainTile {